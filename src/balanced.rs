@@ -0,0 +1,155 @@
+//! Balanced numeral systems (e.g. SNAFU: base 5 with digits `=`, `-`, `0`, `1`, `2`).
+
+use anyhow::Result;
+
+/// Codec converting integers from/to balanced base-N digit strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalancedCodec {
+    /// Digit symbols, sorted by value.
+    alphabet: Vec<char>,
+    /// Value of the first digit in the alphabet.
+    lowest: i64,
+}
+
+impl BalancedCodec {
+    /// Construct a new instance: the base is the number of symbols in `alphabet` (sorted by
+    /// value), the first of which is worth `lowest`.
+    ///
+    /// The alphabet must contain both negative and positive digits so that every integer is
+    /// representable.
+    pub fn new(alphabet: &str, lowest: i64) -> Result<Self> {
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        let highest = lowest + alphabet.len() as i64 - 1;
+        if lowest >= 0 || highest <= 0 {
+            anyhow::bail!("balanced alphabet must contain negative, zero and positive digits");
+        }
+
+        for (idx, symbol) in alphabet.iter().enumerate() {
+            if alphabet[idx + 1..].contains(symbol) {
+                anyhow::bail!("duplicate digit '{symbol}' in alphabet");
+            }
+        }
+
+        Ok(Self { alphabet, lowest })
+    }
+
+    /// SNAFU codec (base 5, digits `=`, `-`, `0`, `1`, `2`).
+    pub fn snafu() -> Self {
+        Self {
+            alphabet: vec!['=', '-', '0', '1', '2'],
+            lowest: -2,
+        }
+    }
+
+    /// Numeral base.
+    pub fn base(&self) -> i64 {
+        self.alphabet.len() as i64
+    }
+
+    /// Encode `n` into its digit string (most significant digit first).
+    pub fn encode(&self, n: i64) -> String {
+        // Zero is the digit with value 0.
+        let zero = self.alphabet[(-self.lowest) as usize];
+        if n == 0 {
+            return zero.to_string();
+        }
+
+        // Computations are carried out as i128 so that i64 extremes can't overflow.
+        let (base, lowest) = (self.base() as i128, self.lowest as i128);
+        let mut n = n as i128;
+        let mut digits: Vec<char> = vec![];
+        while n != 0 {
+            let digit = (n - lowest).rem_euclid(base) + lowest;
+            digits.push(self.alphabet[(digit - lowest) as usize]);
+            n = (n - digit) / base;
+        }
+
+        digits.iter().rev().collect()
+    }
+
+    /// Decode digit string into its value.
+    pub fn decode(&self, s: &str) -> Result<i64> {
+        if s.is_empty() {
+            anyhow::bail!("empty numeral");
+        }
+
+        // Prefixes of a valid numeral may exceed i64 even if the whole value doesn't.
+        let value = s.chars().try_fold(0i128, |value, symbol| {
+            let digit = self
+                .alphabet
+                .iter()
+                .position(|c| *c == symbol)
+                .ok_or_else(|| anyhow::format_err!("invalid digit '{symbol}' in '{s}'"))?
+                as i128
+                + self.lowest as i128;
+
+            value
+                .checked_mul(self.base() as i128)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or_else(|| anyhow::format_err!("numeral '{s}' overflows i64"))
+        })?;
+
+        i64::try_from(value).map_err(|_| anyhow::format_err!("numeral '{s}' overflows i64"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snafu() {
+        // Day 25 example.
+        const TABLE: [(i64, &str); 15] = [
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (6, "11"),
+            (7, "12"),
+            (8, "2="),
+            (9, "2-"),
+            (10, "20"),
+            (15, "1=0"),
+            (20, "1-0"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+        ];
+
+        let codec = BalancedCodec::snafu();
+        for (n, snafu) in TABLE {
+            assert_eq!(snafu, codec.encode(n));
+            assert_eq!(n, codec.decode(snafu).unwrap());
+        }
+
+        assert_eq!("0", codec.encode(0));
+        assert_eq!("-", codec.encode(-1));
+        assert_eq!(-2022, codec.decode(&codec.encode(-2022)).unwrap());
+        assert_eq!(i64::MAX, codec.decode(&codec.encode(i64::MAX)).unwrap());
+        assert_eq!(i64::MIN, codec.decode(&codec.encode(i64::MIN)).unwrap());
+
+        assert!(codec.decode("").is_err());
+        assert!(codec.decode("12a").is_err());
+        assert!(codec.decode(&"2".repeat(30)).is_err());
+    }
+
+    #[test]
+    fn custom_alphabets() {
+        // Balanced ternary.
+        let ternary = BalancedCodec::new("T01", -1).unwrap();
+        assert_eq!("1T", ternary.encode(2));
+        assert_eq!("T1", ternary.encode(-2));
+        assert_eq!(8, ternary.decode("10T").unwrap());
+
+        // Even base with asymmetric digits.
+        let quaternary = BalancedCodec::new("-012", -1).unwrap();
+        for n in -100..100 {
+            assert_eq!(n, quaternary.decode(&quaternary.encode(n)).unwrap());
+        }
+
+        assert!(BalancedCodec::new("0123", 0).is_err());
+        assert!(BalancedCodec::new("-0-", -1).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Memoization utilities.
+
+use std::{
+    borrow::Borrow,
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+};
+
+/// Memoization cache, optionally bounded to a maximum number of entries in which case the least
+/// recently used entry is evicted first.
+#[derive(Debug, Clone)]
+pub struct Cache<K, V> {
+    /// Cached values with their last use tick.
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by last use tick (only maintained for bounded caches).
+    recency: BTreeMap<u64, K>,
+    /// Maximum number of entries (`None` if unbounded).
+    capacity: Option<usize>,
+    tick: u64,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            capacity: None,
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+}
+
+impl<K, V> Cache<K, V>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Construct a new empty unbounded instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a new empty instance holding at most `capacity` entries.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Get the value cached for `key`, marking it as recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some((value, last_use)) => {
+                self.hits += 1;
+                if self.capacity.is_some() {
+                    let key = self.recency.remove(last_use).expect("tracked key");
+                    self.recency.insert(self.tick, key);
+                }
+                *last_use = self.tick;
+                Some(value)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Cache `value` for `key`, evicting the least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == Some(0) {
+            return;
+        }
+
+        self.tick += 1;
+        if let Some(capacity) = self.capacity {
+            match self.entries.get(&key) {
+                Some((_, last_use)) => {
+                    self.recency.remove(last_use);
+                }
+                None if self.entries.len() == capacity => {
+                    if let Some((_, lru)) = self.recency.pop_first() {
+                        self.entries.remove(&lru);
+                    }
+                }
+                None => {}
+            }
+            self.recency.insert(self.tick, key.clone());
+        }
+        self.entries.insert(key, (value, self.tick));
+    }
+
+    /// Get the value cached for `key`, or compute it with `f` and cache it.
+    ///
+    /// `f` is given the cache itself, so recursive functions can memoize their sub-problems:
+    ///
+    /// ```
+    /// use day1::cache::Cache;
+    ///
+    /// fn fibonacci(n: u64, cache: &mut Cache<u64, u64>) -> u64 {
+    ///     if n < 2 {
+    ///         return n;
+    ///     }
+    ///     cache.memoize(n, |cache| fibonacci(n - 1, cache) + fibonacci(n - 2, cache))
+    /// }
+    ///
+    /// assert_eq!(12586269025, fibonacci(50, &mut Cache::new()));
+    /// ```
+    pub fn memoize<F>(&mut self, key: K, f: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+        V: Clone,
+    {
+        if let Some(value) = self.get(&key) {
+            return value.clone();
+        }
+
+        let value = f(self);
+        self.insert(key, value.clone());
+        value
+    }
+
+    /// Number of cached entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups that found a cached value.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of lookups that didn't find a cached value.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Remove all the entries (hit and miss counters are kept).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+/// Derive a compact 64 bit key from a (possibly large) hashable state.
+///
+/// Different states may map to the same key: only use it where a collision is acceptable or
+/// known not to happen.
+pub fn fingerprint<T>(state: &T) -> u64
+where
+    T: Hash + ?Sized,
+{
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Derive an order-insensitive key from a set of small indices (e.g. the opened valves), as a
+/// bitmask.
+///
+/// # Panic
+/// Panics if any index is greater than 63.
+pub fn bitmask<I>(indices: I) -> u64
+where
+    I: IntoIterator<Item = usize>,
+{
+    indices.into_iter().fold(0, |mask, idx| {
+        assert!(idx < 64, "index {idx} doesn't fit in a 64 bit mask");
+        mask | 1 << idx
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn memoization() {
+        fn paths(x: usize, y: usize, cache: &mut Cache<(usize, usize), u64>) -> u64 {
+            if x == 0 || y == 0 {
+                return 1;
+            }
+            cache.memoize((x, y), |cache| {
+                paths(x - 1, y, cache) + paths(x, y - 1, cache)
+            })
+        }
+
+        let mut cache = Cache::new();
+        assert_eq!(137846528820, paths(20, 20, &mut cache));
+        assert_eq!(400, cache.len());
+        assert_eq!(400, cache.misses());
+        // Every sub-problem is looked up once per neighbour it is reached from.
+        assert_eq!(1 + 19 * 20 * 2 - 400, cache.hits());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn lru_eviction() {
+        let mut cache: Cache<&str, usize> = Cache::bounded(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(Some(&1), cache.get("a"));
+        // "b" is now the least recently used entry.
+        cache.insert("c", 3);
+        assert_eq!(2, cache.len());
+        assert_eq!(None, cache.get("b"));
+        assert_eq!(Some(&1), cache.get("a"));
+        assert_eq!(Some(&3), cache.get("c"));
+
+        // Updating an entry doesn't evict anything.
+        cache.insert("a", 10);
+        assert_eq!(Some(&10), cache.get("a"));
+        assert_eq!(Some(&3), cache.get("c"));
+
+        let mut cache: Cache<u8, u8> = Cache::bounded(0);
+        cache.insert(1, 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(bitmask([0, 3, 5]), bitmask([5, 0, 3, 3]));
+        assert_eq!(0b101001, bitmask([0, 3, 5]));
+        assert_eq!(0, bitmask([]));
+        assert_eq!(fingerprint(&[1, 2, 3][..]), fingerprint(&vec![1, 2, 3]));
+        assert_ne!(fingerprint("ab"), fingerprint("ba"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Calories carried by the Elves (day 1).
+
+use crate::parse::ParseMode;
+use anyhow::Result;
+use std::{
+    fmt::Display,
+    io::{BufRead, Lines},
+};
+
+/// Elf with the calories of the food items carried.
+#[derive(Debug)]
+pub struct Elf {
+    /// Position of the Elf in the input, starting from 1.
+    pub idx: usize,
+    /// Total calories carried.
+    pub cals: usize,
+    /// Calories of each food item.
+    pub foods: Vec<usize>,
+}
+
+impl Elf {
+    /// Construct a new instance carrying no food.
+    pub fn new(idx: usize) -> Self {
+        Self {
+            idx,
+            cals: 0,
+            foods: vec![],
+        }
+    }
+
+    /// Number of food items.
+    pub fn items(&self) -> usize {
+        self.foods.len()
+    }
+}
+
+impl Default for Elf {
+    fn default() -> Self {
+        Elf::new(0)
+    }
+}
+
+impl Display for Elf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Elf #{} carries {} cals", self.idx, self.cals)
+    }
+}
+
+/// Lazy iterator over the Elves read from `reader`, the food items carried by each Elf being
+/// listed one per line and separated from the next Elf by a blank line.
+pub struct Elves<R> {
+    lines: Lines<R>,
+    /// Index of the next Elf.
+    idx: usize,
+    /// Number of lines read.
+    line: usize,
+    mode: ParseMode,
+    /// Whether the input is over (or an error was found).
+    done: bool,
+}
+
+impl<R: BufRead> Elves<R> {
+    /// Construct a new instance reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self::with_mode(reader, ParseMode::Strict)
+    }
+
+    /// Construct a new instance reading from `reader` in the given parse mode: in lenient mode,
+    /// whitespace around the calories is trimmed and runs of blank lines separate a single pair
+    /// of Elves, like [`ParseMode::normalize_tokens`] does for a whole input.
+    pub fn with_mode(reader: R, mode: ParseMode) -> Self {
+        Self {
+            lines: reader.lines(),
+            idx: 1,
+            line: 0,
+            mode,
+            done: false,
+        }
+    }
+
+    /// Collect the Elves, sorted by descending calories.
+    pub fn ranked(self) -> Result<Vec<Elf>> {
+        let mut elfs = self.collect::<Result<Vec<Elf>>>()?;
+
+        // Inverted sort by cals.
+        elfs.sort_by(|a, b| {
+            b.cals
+                .partial_cmp(&a.cals)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(elfs)
+    }
+}
+
+impl<R: BufRead> Iterator for Elves<R> {
+    type Item = Result<Elf>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut elf = Elf::new(self.idx);
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(anyhow::format_err!("line {}: {e}", self.line + 1)));
+                }
+                // Last Elf, when the input doesn't end with a blank line.
+                None => {
+                    self.done = true;
+                    return (elf.items() > 0).then_some(Ok(elf));
+                }
+            };
+
+            self.line += 1;
+
+            let line = match self.mode {
+                ParseMode::Strict => line.as_str(),
+                ParseMode::Lenient => line.trim(),
+            };
+            if line.is_empty() {
+                // Leading and repeated blank lines carry no Elf in lenient mode.
+                if self.mode == ParseMode::Lenient && elf.items() == 0 {
+                    continue;
+                }
+                self.idx += 1;
+                return Some(Ok(elf));
+            }
+
+            match line
+                .parse::<usize>()
+                .ok()
+                .and_then(|line_cals| Some((line_cals, elf.cals.checked_add(line_cals)?)))
+            {
+                Some((line_cals, cals)) => {
+                    elf.foods.push(line_cals);
+                    elf.cals = cals;
+                }
+                None => {
+                    self.done = true;
+                    return Some(Err(anyhow::format_err!(
+                        "line {}: invalid calories value '{line}'",
+                        self.line
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Parse the calories carried by each Elf, sorted by descending calories.
+pub fn rank_elves(input: &str) -> Result<Vec<Elf>> {
+    Elves::new(input.as_bytes()).ranked()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streaming() {
+        // The last Elf is yielded with or without a trailing blank line.
+        for input in [
+            "1000\n\n2000\n3000",
+            "1000\n\n2000\n3000\n",
+            "1000\n\n2000\n3000\n\n",
+        ] {
+            let elves: Vec<(usize, usize, usize)> = Elves::new(input.as_bytes())
+                .map(|elf| elf.map(|elf| (elf.idx, elf.cals, elf.items())))
+                .collect::<Result<_>>()
+                .unwrap();
+            assert_eq!(vec![(1, 1000, 1), (2, 5000, 2)], elves, "{input:?}");
+        }
+
+        // Elves are yielded before the rest of the input is read.
+        let mut elves = Elves::new("1000\n\nabc\n\n2000".as_bytes());
+        assert_eq!(1000, elves.next().unwrap().unwrap().cals);
+        assert!(elves.next().unwrap().is_err());
+        assert!(elves.next().is_none());
+        assert!(Elves::new("".as_bytes()).next().is_none());
+    }
+
+    #[test]
+    fn lenient_mode() {
+        let input = "\r\n 1000 \r\n\r\n\n\n2000\t\n3000\n\n\n";
+        let elves: Vec<(usize, usize)> = Elves::with_mode(input.as_bytes(), ParseMode::Lenient)
+            .map(|elf| elf.map(|elf| (elf.idx, elf.cals)))
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(vec![(1, 1000), (2, 5000)], elves);
+        assert!(Elves::new(input.as_bytes()).ranked().is_err());
+
+        // Same Elves as the normalized input, parsed strictly.
+        let normalized = ParseMode::Lenient.normalize_tokens(input);
+        let cals = |elves: Vec<Elf>| elves.iter().map(|elf| elf.cals).collect::<Vec<_>>();
+        assert_eq!(
+            cals(rank_elves(&normalized).unwrap()),
+            cals(
+                Elves::with_mode(input.as_bytes(), ParseMode::Lenient)
+                    .ranked()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn ranking() {
+        let elves = rank_elves("1000\n2000\n\n4000\n\n500\n").unwrap();
+        let ranking: Vec<(usize, usize)> = elves.iter().map(|elf| (elf.idx, elf.cals)).collect();
+        assert_eq!(vec![(2, 4000), (1, 3000), (3, 500)], ranking);
+        assert_eq!("Elf #2 carries 4000 cals", elves[0].to_string());
+        assert_eq!(vec![1000, 2000], elves[1].foods);
+        assert!(rank_elves("").unwrap().is_empty());
+        assert_eq!(
+            "line 4: invalid calories value ' 500'",
+            rank_elves("1000\n\n2000\n 500").unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Minimal command line arguments parser shared by the day binaries.
+
+use anyhow::Result;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+/// Parsed command line arguments.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Options taking a value (`--name value` or `--name=value`).
+    values: HashMap<String, String>,
+    /// Boolean flags (`--name`).
+    flags: HashSet<String>,
+    /// Positional arguments.
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Parse the process arguments (program name excluded).
+    ///
+    /// `options` lists the accepted options taking a value, `flags` the accepted boolean flags
+    /// (both including the leading `--`).
+    pub fn from_env(options: &[&str], flags: &[&str]) -> Result<Self> {
+        Self::parse(std::env::args().skip(1), options, flags)
+    }
+
+    /// Parse the given arguments.
+    pub fn parse<I>(args: I, options: &[&str], flags: &[&str]) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                parsed.positional.push(arg);
+                continue;
+            }
+
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            if options.contains(&name.as_str()) {
+                let value = match value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .ok_or_else(|| anyhow::format_err!("option `{name}` requires a value"))?,
+                };
+                parsed.values.insert(name, value);
+            } else if flags.contains(&name.as_str()) && value.is_none() {
+                parsed.flags.insert(name);
+            } else {
+                anyhow::bail!("unexpected argument `{name}`");
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Whether the given flag is set.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// Raw value of the given option.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Value of the given option parsed as `T`.
+    pub fn parse_value<T>(&self, name: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|e| anyhow::format_err!("invalid value `{value}` for `{name}`: {e}"))
+            })
+            .transpose()
+    }
+
+    /// Positional arguments.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args> {
+        Args::parse(
+            args.iter().map(|arg| arg.to_string()),
+            &["--top", "--trace"],
+            &["--stats"],
+        )
+    }
+
+    #[test]
+    fn parsing() {
+        let parsed = args(&["--top", "5", "input.dat", "--stats", "--trace=out.json"]).unwrap();
+        assert_eq!(Some(5), parsed.parse_value::<usize>("--top").unwrap());
+        assert_eq!(Some("out.json"), parsed.value("--trace"));
+        assert!(parsed.flag("--stats"));
+        assert_eq!(["input.dat"], parsed.positional());
+
+        let parsed = args(&[]).unwrap();
+        assert_eq!(None, parsed.parse_value::<usize>("--top").unwrap());
+        assert!(!parsed.flag("--stats"));
+
+        assert!(args(&["--top"]).is_err());
+        assert!(args(&["--unknown"]).is_err());
+        assert!(args(&["--stats=yes"]).is_err());
+        assert!(args(&["--top", "x"])
+            .unwrap()
+            .parse_value::<usize>("--top")
+            .is_err());
+
+        // Space separated option value, not taken as a positional argument.
+        let parsed = args(&["--trace", "out.json", "input.dat"]).unwrap();
+        assert_eq!(Some("out.json"), parsed.value("--trace"));
+        assert_eq!(["input.dat"], parsed.positional());
+    }
+}
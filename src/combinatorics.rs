@@ -0,0 +1,183 @@
+//! Combinatorics iterators over slices.
+
+/// Pairs, combinations and permutations of the elements of a slice, by position (equal elements
+/// at different positions are distinct).
+pub trait Combinatorics<T> {
+    /// Iterate over the unordered pairs of elements, in lexicographic order of positions.
+    fn pairs(&self) -> Pairs<'_, T>;
+
+    /// Iterate over the combinations of `k` elements, in lexicographic order of positions.
+    fn combinations(&self, k: usize) -> Combinations<'_, T>;
+
+    /// Iterate over the permutations of all the elements, in lexicographic order of positions.
+    fn permutations(&self) -> Permutations<'_, T>;
+}
+
+impl<T> Combinatorics<T> for [T] {
+    fn pairs(&self) -> Pairs<'_, T> {
+        Pairs {
+            items: self,
+            i: 0,
+            j: 1,
+        }
+    }
+
+    fn combinations(&self, k: usize) -> Combinations<'_, T> {
+        Combinations {
+            items: self,
+            indices: (k <= self.len()).then(|| (0..k).collect()),
+        }
+    }
+
+    fn permutations(&self) -> Permutations<'_, T> {
+        Permutations {
+            items: self,
+            indices: Some((0..self.len()).collect()),
+        }
+    }
+}
+
+/// Iterator over the unordered pairs of elements of a slice (see [`Combinatorics::pairs`]).
+#[derive(Debug, Clone)]
+pub struct Pairs<'a, T> {
+    items: &'a [T],
+    i: usize,
+    j: usize,
+}
+
+impl<'a, T> Iterator for Pairs<'a, T> {
+    type Item = (&'a T, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.j >= self.items.len() {
+            self.i += 1;
+            self.j = self.i + 1;
+            if self.j >= self.items.len() {
+                return None;
+            }
+        }
+
+        let pair = (&self.items[self.i], &self.items[self.j]);
+        self.j += 1;
+        Some(pair)
+    }
+}
+
+/// Iterator over the `k` elements combinations of a slice (see
+/// [`Combinatorics::combinations`]).
+#[derive(Debug, Clone)]
+pub struct Combinations<'a, T> {
+    items: &'a [T],
+    /// Positions of the next combination (`None` when exhausted).
+    indices: Option<Vec<usize>>,
+}
+
+impl<'a, T> Iterator for Combinations<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.indices.as_mut()?;
+        let combination = indices.iter().map(|idx| &self.items[*idx]).collect();
+
+        // Advance the rightmost position that can still move right, resetting the following
+        // ones right after it.
+        let (n, k) = (self.items.len(), indices.len());
+        match (0..k).rev().find(|i| indices[*i] < n - k + i) {
+            Some(i) => {
+                indices[i] += 1;
+                for j in i + 1..k {
+                    indices[j] = indices[j - 1] + 1;
+                }
+            }
+            None => self.indices = None,
+        }
+
+        Some(combination)
+    }
+}
+
+/// Iterator over the permutations of a slice (see [`Combinatorics::permutations`]).
+#[derive(Debug, Clone)]
+pub struct Permutations<'a, T> {
+    items: &'a [T],
+    /// Positions of the next permutation (`None` when exhausted).
+    indices: Option<Vec<usize>>,
+}
+
+impl<'a, T> Iterator for Permutations<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.indices.as_mut()?;
+        let permutation = indices.iter().map(|idx| &self.items[*idx]).collect();
+
+        // Next lexicographic permutation: swap the last ascent with its smallest greater
+        // successor, then reverse the (descending) tail.
+        match (1..indices.len())
+            .rev()
+            .find(|i| indices[i - 1] < indices[*i])
+        {
+            Some(i) => {
+                let j = (i..indices.len())
+                    .rev()
+                    .find(|j| indices[*j] > indices[i - 1])
+                    .expect("ascent successor");
+                indices.swap(i - 1, j);
+                indices[i..].reverse();
+            }
+            None => self.indices = None,
+        }
+
+        Some(permutation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pairs() {
+        let pairs: Vec<(&u8, &u8)> = [1, 2, 3, 4].pairs().collect();
+        assert_eq!(
+            vec![(&1, &2), (&1, &3), (&1, &4), (&2, &3), (&2, &4), (&3, &4)],
+            pairs
+        );
+        assert_eq!(0, [1].pairs().count());
+        assert_eq!(0, Vec::<u8>::new().pairs().count());
+    }
+
+    #[test]
+    fn combinations() {
+        let combinations: Vec<String> = ['a', 'b', 'c', 'd']
+            .combinations(3)
+            .map(|c| c.into_iter().collect())
+            .collect();
+        assert_eq!(vec!["abc", "abd", "acd", "bcd"], combinations);
+
+        let items: Vec<usize> = (0..10).collect();
+        for k in 0..=10 {
+            // Binomial coefficient.
+            let count = (1..=k).fold(1, |c, i| c * (10 - k + i) / i);
+            assert_eq!(count, items.combinations(k).count());
+        }
+        assert_eq!(0, items.combinations(11).count());
+        assert_eq!(
+            vec![Vec::<&u8>::new()],
+            [].combinations(0).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn permutations() {
+        let permutations: Vec<String> = ['a', 'b', 'c']
+            .permutations()
+            .map(|p| p.into_iter().collect())
+            .collect();
+        assert_eq!(vec!["abc", "acb", "bac", "bca", "cab", "cba"], permutations);
+
+        // Equal elements are distinct by position.
+        assert_eq!(24, [1, 1, 1, 1].permutations().count());
+        assert_eq!(1, [0; 0].permutations().count());
+    }
+}
@@ -0,0 +1,157 @@
+//! Frequency counter (multiset).
+
+use std::{
+    collections::{hash_map, HashMap},
+    hash::Hash,
+};
+
+/// Multiset counting the occurrences of each distinct value.
+#[derive(Debug, Clone)]
+pub struct Counter<T> {
+    counts: HashMap<T, usize>,
+    /// Total number of occurrences.
+    len: usize,
+}
+
+impl<T> Default for Counter<T> {
+    fn default() -> Self {
+        Self {
+            counts: HashMap::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Counter<T>
+where
+    T: Eq + Hash,
+{
+    /// Construct a new empty instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an occurrence of `value`.
+    pub fn add(&mut self, value: T) {
+        *self.counts.entry(value).or_default() += 1;
+        self.len += 1;
+    }
+
+    /// Remove an occurrence of `value`, returning whether it was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        match self.counts.get_mut(value) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(value);
+                }
+                self.len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of occurrences of `value`.
+    pub fn count(&self, value: &T) -> usize {
+        self.counts.get(value).copied().unwrap_or_default()
+    }
+
+    /// Total number of occurrences.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the counter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of distinct values.
+    pub fn len_distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Distinct values with their number of occurrences, most common first (ties in arbitrary
+    /// order).
+    pub fn most_common(&self) -> Vec<(&T, usize)> {
+        let mut counts: Vec<(&T, usize)> = self.iter().collect();
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    }
+
+    /// Iterate over the distinct values with their number of occurrences, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(value, count)| (value, *count))
+    }
+}
+
+impl<T> FromIterator<T> for Counter<T>
+where
+    T: Eq + Hash,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T> Extend<T> for Counter<T>
+where
+    T: Eq + Hash,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        iter.into_iter().for_each(|value| self.add(value));
+    }
+}
+
+impl<T> IntoIterator for Counter<T> {
+    type Item = (T, usize);
+    type IntoIter = hash_map::IntoIter<T, usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.counts.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counting() {
+        let mut counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(11, counter.len());
+        assert_eq!(5, counter.len_distinct());
+        assert_eq!(5, counter.count(&'a'));
+        assert_eq!(0, counter.count(&'z'));
+        assert_eq!((&'a', 5), counter.most_common()[0]);
+
+        assert!(counter.remove(&'c'));
+        assert!(!counter.remove(&'c'));
+        assert!(!counter.remove(&'z'));
+        assert_eq!(10, counter.len());
+        assert_eq!(4, counter.len_distinct());
+
+        counter.extend("cc".chars());
+        assert_eq!(2, counter.count(&'c'));
+
+        let mut counts: Vec<(char, usize)> = counter.into_iter().collect();
+        counts.sort();
+        assert_eq!(
+            vec![('a', 5), ('b', 2), ('c', 2), ('d', 1), ('r', 2)],
+            counts
+        );
+
+        let empty: Counter<u8> = Counter::new();
+        assert!(empty.is_empty());
+        assert!(empty.most_common().is_empty());
+    }
+}
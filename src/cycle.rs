@@ -0,0 +1,159 @@
+//! Cycle detection on iterated functions.
+//!
+//! Sequences are defined by an initial state `x0` and a state function `f`, as `x0`, `f(x0)`,
+//! `f(f(x0))`, ... Every function over a finite set of states eventually enters a cycle; on
+//! infinite state spaces the detection only terminates if the sequence does.
+
+/// Cycle of a sequence of iterated states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first state of the first repetition.
+    pub start: usize,
+    /// Cycle length (greater than 0).
+    pub len: usize,
+}
+
+impl Cycle {
+    /// Index of the first state equal to the state at index `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            return n;
+        }
+
+        self.start + (n - self.start) % self.len
+    }
+}
+
+/// Find the cycle of the sequence using Floyd's tortoise and hare algorithm.
+pub fn floyd<T, F>(initial: T, f: F) -> Cycle
+where
+    T: PartialEq + Clone,
+    F: Fn(&T) -> T,
+{
+    // Find a repetition x_i == x_2i: i is a multiple of the cycle length.
+    let mut tortoise = f(&initial);
+    let mut hare = f(&tortoise);
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    // Tortoise and hare, walking at the same speed from x_0 and x_i, meet at the cycle start.
+    let mut start: usize = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    let mut len: usize = 1;
+    hare = f(&tortoise);
+    while tortoise != hare {
+        hare = f(&hare);
+        len += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// Find the cycle of the sequence using Brent's algorithm (fewer state function evaluations than
+/// [`floyd`]).
+pub fn brent<T, F>(initial: T, f: F) -> Cycle
+where
+    T: PartialEq + Clone,
+    F: Fn(&T) -> T,
+{
+    // Search successive powers of two for the cycle length.
+    let mut power: usize = 1;
+    let mut len: usize = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = f(&initial);
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = f(&hare);
+        len += 1;
+    }
+
+    // Hare starts `len` steps ahead of the tortoise: they meet at the cycle start.
+    let mut start: usize = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..len {
+        hare = f(&hare);
+    }
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        start += 1;
+    }
+
+    Cycle { start, len }
+}
+
+/// State at index `n` of the sequence, skipping the repetitions of its cycle (so `n` can be way
+/// larger than the number of distinct states).
+pub fn nth_state<T, F>(initial: T, f: F, n: usize) -> T
+where
+    T: PartialEq + Clone,
+    F: Fn(&T) -> T,
+{
+    let cycle = brent(initial.clone(), &f);
+    (0..cycle.reduce(n)).fold(initial, |state, _| f(&state))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detection() {
+        // 2, 0, 6, 3, 1, 6, 3, 1, ...
+        let f = |x: &u32| [6, 6, 0, 1, 4, 3, 3, 4, 0][*x as usize];
+        let cycle = Cycle { start: 2, len: 3 };
+        assert_eq!(cycle, floyd(2, f));
+        assert_eq!(cycle, brent(2, f));
+
+        // Pure cycle and fixed point.
+        let f = |x: &u64| (x + 1) % 7;
+        assert_eq!(Cycle { start: 0, len: 7 }, floyd(0, f));
+        assert_eq!(Cycle { start: 0, len: 7 }, brent(0, f));
+        let f = |x: &u64| x / 2;
+        assert_eq!(Cycle { start: 7, len: 1 }, floyd(100, f));
+        assert_eq!(Cycle { start: 7, len: 1 }, brent(100, f));
+
+        // Quadratic map, checked against brute force.
+        let f = |x: &u64| (x * x + 1) % 255;
+        let mut seen = vec![];
+        let mut x = 3;
+        while !seen.contains(&x) {
+            seen.push(x);
+            x = f(&x);
+        }
+        let start = seen.iter().position(|y| *y == x).unwrap();
+        let cycle = Cycle {
+            start,
+            len: seen.len() - start,
+        };
+        assert_eq!(cycle, floyd(3, f));
+        assert_eq!(cycle, brent(3, f));
+    }
+
+    #[test]
+    fn extrapolation() {
+        let cycle = Cycle { start: 2, len: 3 };
+        assert_eq!(1, cycle.reduce(1));
+        assert_eq!(4, cycle.reduce(4));
+        assert_eq!(2, cycle.reduce(5));
+        assert_eq!(4, cycle.reduce(1_000_000_000_000));
+
+        let f = |x: &u32| [6, 6, 0, 1, 4, 3, 3, 4, 0][*x as usize];
+        assert_eq!(2, nth_state(2, f, 0));
+        assert_eq!(6, nth_state(2, f, 2));
+        assert_eq!(6, nth_state(2, f, 1_000_000_000_001));
+    }
+}
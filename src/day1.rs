@@ -1,92 +1,212 @@
-use std::{
-    fmt::Display,
-    fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
-};
-
-fn read_input_lines<P>(path: P) -> Result<Vec<String>, io::Error>
-where
-    P: AsRef<Path>,
-{
-    let input_file = File::open(path)?;
-    BufReader::new(input_file).lines().collect()
+use anyhow::Result;
+use day1::{calories::Elves, cli::Args, examples, parse::ParseMode, Elf};
+use std::{fmt::Display, process::ExitCode};
+
+/// Elves whose calories are summed in part 2, by default.
+const TOP: usize = 3;
+/// Percentiles of the calories listed in the statistics.
+const PERCENTILES: [usize; 6] = [10, 25, 50, 75, 90, 99];
+
+/// Calories carried by the Elf carrying the most.
+fn part1(elves: &[Elf]) -> usize {
+    elves.first().map(|elf| elf.cals).unwrap_or_default()
 }
 
-#[derive(Debug)]
-struct Elf {
-    idx: usize,
-    cals: usize,
+/// Calories carried by the `top` Elves carrying the most.
+fn part2(elves: &[Elf], top: usize) -> usize {
+    elves.iter().take(top).map(|elf| elf.cals).sum()
 }
 
-impl Elf {
-    fn new(idx: usize, cals: usize) -> Self {
-        Self { idx, cals }
-    }
+/// Statistics of the calories carried by the Elves.
+#[derive(Debug, PartialEq)]
+struct Stats {
+    mean: f64,
+    median: f64,
+    /// Population standard deviation.
+    std_dev: f64,
+    /// Calories by percentile (nearest rank).
+    percentiles: Vec<(usize, usize)>,
 }
 
-impl Default for Elf {
-    fn default() -> Self {
-        Elf::new(0, 0)
+impl Stats {
+    /// Compute the statistics of the ranked Elves (`None` if there are none).
+    fn new(elves: &[Elf]) -> Option<Self> {
+        let mut cals: Vec<usize> = elves.iter().map(|elf| elf.cals).collect();
+        cals.sort_unstable();
+        let len = cals.len();
+        if len == 0 {
+            return None;
+        }
+
+        let mean = cals.iter().map(|cals| *cals as f64).sum::<f64>() / len as f64;
+        let median = match len % 2 {
+            0 => (cals[len / 2 - 1] as f64 + cals[len / 2] as f64) / 2.0,
+            _ => cals[len / 2] as f64,
+        };
+        let variance = cals
+            .iter()
+            .map(|cals| (*cals as f64 - mean).powi(2))
+            .sum::<f64>()
+            / len as f64;
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|p| (*p, cals[(p * len).div_ceil(100).max(1) - 1]))
+            .collect();
+
+        Some(Self {
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            percentiles,
+        })
     }
 }
 
-impl Display for Elf {
+impl Display for Stats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Elf #{} carries {} cals", self.idx, self.cals)
+        writeln!(f, "Mean: {:.2} cals", self.mean)?;
+        writeln!(f, "Median: {:.1} cals", self.median)?;
+        write!(f, "Standard deviation: {:.2} cals", self.std_dev)?;
+        for (percentile, cals) in &self.percentiles {
+            write!(f, "\n{percentile}th percentile: {cals} cals")?;
+        }
+
+        Ok(())
     }
 }
 
-/// Returns a vector of elves, sorted by cals.
-fn elves_cals(lines: &[String]) -> Vec<Elf> {
-    let mut elfs: Vec<Elf> = Default::default();
-    let mut idx: usize = 1;
-    let mut cals: usize = 0;
-
-    for line in lines {
-        if line.is_empty() {
-            elfs.push(Elf::new(idx, cals));
-            idx += 1;
-            cals = 0;
-            continue;
-        }
+/// Detail of the food items carried by each ranked Elf.
+fn detail(elves: &[Elf]) -> String {
+    elves
+        .iter()
+        .enumerate()
+        .map(|(rank, elf)| {
+            let foods: Vec<String> = elf.foods.iter().map(usize::to_string).collect();
+            let plural = if elf.items() == 1 { "" } else { "s" };
+            format!(
+                "{}. {elf} in {} item{plural}: {}\n",
+                rank + 1,
+                elf.items(),
+                foods.join(", ")
+            )
+        })
+        .collect()
+}
 
-        cals += line.parse::<usize>().unwrap();
+/// Ranking report formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl TryFrom<&str> for ReportFormat {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Ok(match value {
+            "csv" => Self::Csv,
+            "json" => Self::Json,
+            f => anyhow::bail!("'{f}' is not a valid report format (csv, json)"),
+        })
     }
+}
 
-    // Inverted sort by cals.
-    elfs.sort_by(|a, b| {
-        b.cals
-            .partial_cmp(&a.cals)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+/// Full ranking of the elves (rank, index, total calories, item count) in the given format.
+fn report(elves: &[Elf], format: ReportFormat) -> String {
+    let mut report = String::new();
+    match format {
+        ReportFormat::Csv => {
+            report.push_str("rank,elf,calories,items\n");
+            for (rank, elf) in elves.iter().enumerate() {
+                report.push_str(&format!(
+                    "{},{},{},{}\n",
+                    rank + 1,
+                    elf.idx,
+                    elf.cals,
+                    elf.items()
+                ));
+            }
+        }
+        ReportFormat::Json => {
+            let rows: Vec<String> = elves
+                .iter()
+                .enumerate()
+                .map(|(rank, elf)| {
+                    format!(
+                        "{{\"rank\":{},\"elf\":{},\"calories\":{},\"items\":{}}}",
+                        rank + 1,
+                        elf.idx,
+                        elf.cals,
+                        elf.items()
+                    )
+                })
+                .collect();
+            report.push_str(&format!("[{}]\n", rows.join(",")));
+        }
+    }
 
-    elfs
+    report
 }
 
-fn main() -> io::Result<()> {
-    let input_lines = read_input_lines("./input/day1.dat")?;
-    let elves_cals = elves_cals(&input_lines);
+fn run() -> Result<()> {
+    let args = Args::from_env(
+        &["--report", "--top", examples::OPTION],
+        &["--stats", "--detail", ParseMode::FLAG],
+    )?;
+    // The inventory is streamed, Elf by Elf.
+    let input = examples::open(&args, 1)?;
+    let elves = Elves::with_mode(input, ParseMode::from_args(&args)).ranked()?;
+    let top = args.parse_value("--top")?.unwrap_or(TOP);
+    if top == 0 {
+        anyhow::bail!("at least one Elf must be selected with '--top'");
+    }
+
+    // Ranking report.
+    if let Some(format) = args.value("--report") {
+        print!("{}", report(&elves, ReportFormat::try_from(format)?));
+        return Ok(());
+    }
+
+    // Per Elf detail.
+    if args.flag("--detail") {
+        print!("{}", detail(&elves));
+    }
+
+    // Part 1
+    println!("Top Elf carries {} calories", part1(&elves));
 
-    // Part 1 & Part 2
+    // Part 2
+    elves.iter().take(top).for_each(|elf| println!("{elf}"));
     println!(
-        "Top three Elves carry a total of {} calories",
-        elves_cals
-            .iter()
-            .take(3)
-            .map(|elf| {
-                println!("{elf}");
-                elf.cals
-            })
-            .sum::<usize>()
+        "Top {top} Elves carry a total of {} calories",
+        part2(&elves, top)
     );
 
+    // Statistics.
+    if args.flag("--stats") {
+        match Stats::new(&elves) {
+            Some(stats) => println!("{stats}"),
+            None => println!("No Elves to compute statistics on"),
+        }
+    }
+
     Ok(())
 }
 
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
 #[cfg(test)]
 mod test {
-    use super::elves_cals;
+    use super::{detail, examples, part1, part2, report, ReportFormat, Stats, TOP};
+    use day1::rank_elves;
 
     #[test]
     fn test_on_example() {
@@ -95,9 +215,92 @@ mod test {
             "10000",
         ];
 
-        let elf = &elves_cals(&lines.iter().map(|str| str.to_string()).collect::<Vec<_>>())[0];
+        let elves = rank_elves(&lines.join("\n")).unwrap();
+        let elf = &elves[0];
 
         assert_eq!(4, elf.idx);
         assert_eq!(24000, elf.cals);
+        assert_eq!(24000, part1(&elves));
+        assert_eq!(45000, part2(&elves, TOP));
+        assert_eq!(24000, part2(&elves, 1));
+        assert_eq!(55000, part2(&elves, 10));
+    }
+
+    #[test]
+    fn ranking_report() {
+        let elves = rank_elves("1000\n2000\n\n4000\n\n500").unwrap();
+        assert_eq!(
+            "rank,elf,calories,items\n1,2,4000,1\n2,1,3000,2\n3,3,500,1\n",
+            report(&elves, ReportFormat::Csv)
+        );
+        assert_eq!(
+            concat!(
+                r#"[{"rank":1,"elf":2,"calories":4000,"items":1},"#,
+                r#"{"rank":2,"elf":1,"calories":3000,"items":2},"#,
+                r#"{"rank":3,"elf":3,"calories":500,"items":1}]"#,
+                "\n"
+            ),
+            report(&elves, ReportFormat::Json)
+        );
+        assert!(ReportFormat::try_from("xml").is_err());
+    }
+
+    #[test]
+    fn detail_output() {
+        let elves = rank_elves("1000\n2000\n\n4000\n\n500").unwrap();
+        assert_eq!(
+            concat!(
+                "1. Elf #2 carries 4000 cals in 1 item: 4000\n",
+                "2. Elf #1 carries 3000 cals in 2 items: 1000, 2000\n",
+                "3. Elf #3 carries 500 cals in 1 item: 500\n",
+            ),
+            detail(&elves)
+        );
+        assert_eq!("", detail(&[]));
+    }
+
+    #[test]
+    fn statistics() {
+        let elves = rank_elves("1000\n\n5000\n\n2000\n3000\n\n1000").unwrap();
+        let stats = Stats::new(&elves).unwrap();
+        assert_eq!(3000.0, stats.mean);
+        assert_eq!(3000.0, stats.median);
+        assert_eq!(2000.0, stats.std_dev);
+        assert_eq!(
+            vec![
+                (10, 1000),
+                (25, 1000),
+                (50, 1000),
+                (75, 5000),
+                (90, 5000),
+                (99, 5000)
+            ],
+            stats.percentiles
+        );
+
+        let stats = Stats::new(&rank_elves("7").unwrap()).unwrap();
+        assert_eq!((7.0, 7.0, 0.0), (stats.mean, stats.median, stats.std_dev));
+        assert!(stats.percentiles.iter().all(|(_, cals)| *cals == 7));
+        assert_eq!(None, Stats::new(&[]));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for lines in [
+            &["1000", "abc", ""][..],
+            &["-1000", ""],
+            &[" 1000", ""],
+            &["18446744073709551615", "1", ""],
+        ] {
+            assert!(rank_elves(&lines.join("\n")).is_err());
+        }
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(1, |input| {
+            let elves = rank_elves(input)?;
+            Ok((part1(&elves), part2(&elves, TOP)))
+        });
     }
 }
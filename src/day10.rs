@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
+use day1::{cli::Args, examples, parse::ParseMode, trace::Tracer};
 use std::{
     fmt::{self, Display},
-    fs,
     process::ExitCode,
 };
 
@@ -84,7 +84,7 @@ impl Display for Crt {
         let row_len = self.pixels.len() / 6;
         for (idx, pixel) in self.pixels.iter().enumerate() {
             if idx % row_len == 0 {
-                write!(f, "\n")?;
+                writeln!(f)?;
             }
             write!(
                 f,
@@ -154,7 +154,7 @@ impl Cpu {
             Some(CpuInstruction::Noop) => {}
             Some(CpuInstruction::Addx(i)) => {
                 if self.elapsed_cycles < 2 {
-                    return Ok(self.cycle()?);
+                    return self.cycle();
                 }
                 self.register += i;
             }
@@ -168,9 +168,25 @@ impl Cpu {
         Ok(())
     }
 
-    /// Parse and load the given CPU instruction.
-    fn load(&mut self, instruction_string: &str) -> Result<()> {
-        self.instruction_memory = Some(CpuInstruction::try_from(instruction_string)?);
+    /// Execute the whole program. If a [`Tracer`] is given, the register timeline is recorded
+    /// after each instruction.
+    fn run(&mut self, program: &[CpuInstruction], mut tracer: Option<&mut Tracer>) -> Result<()> {
+        for (idx, instruction) in program.iter().enumerate() {
+            self.instruction_memory = Some(*instruction);
+            self.cycle()?;
+
+            // Register timeline.
+            if let Some(tracer) = tracer.as_deref_mut() {
+                tracer.record(
+                    "cpu",
+                    idx + 1,
+                    &[
+                        ("cycle", self.tot_cycles.into()),
+                        ("register", self.register.into()),
+                    ],
+                )?;
+            }
+        }
 
         Ok(())
     }
@@ -210,23 +226,47 @@ impl TryFrom<&str> for CpuInstruction {
     }
 }
 
-fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day10.dat")?;
+/// Parse the CPU program.
+fn parse(input: &str) -> Result<Vec<CpuInstruction>> {
+    input.lines().map(CpuInstruction::try_from).collect()
+}
 
+/// Sum of the signal strengths during the 20th, 60th, 100th, 140th, 180th and 220th cycles.
+fn part1(program: &[CpuInstruction], tracer: Option<&mut Tracer>) -> Result<isize> {
     let mut cpu = Cpu::new();
-    for instruction_string in input.lines() {
-        cpu.load(instruction_string).unwrap();
-        cpu.cycle().unwrap();
-    }
+    cpu.run(program, tracer)?;
+    Ok(cpu.tot_signal_strenght)
+}
+
+/// Image rendered on the CRT screen.
+fn part2(program: &[CpuInstruction]) -> Result<String> {
+    let mut cpu = Cpu::new();
+    cpu.run(program, None)?;
+    Ok(cpu.crt.to_string())
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(
+        &[Tracer::OPTIONS[0], Tracer::OPTIONS[1], examples::OPTION],
+        &[ParseMode::FLAG],
+    )?;
+    let mut tracer = Tracer::from_args(&args)?;
+    let input = examples::input(&args, 10)?;
+    let program = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
 
     // Part 1
     println!(
         "The sum of signal strenghts is: {}",
-        cpu.tot_signal_strenght
+        part1(&program, tracer.as_mut())?
     );
 
     // Part 2
-    println!("The eight capital letters are: {}", cpu.crt);
+    println!("The eight capital letters are: {}", part2(&program)?);
+
+    // Flush the trace, reporting any error writing it.
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
+    }
 
     Ok(())
 }
@@ -401,16 +441,45 @@ noop"#;
 ######......######......######......####
 #######.......#######.......#######....."#;
 
-        let mut cpu = Cpu::new();
-        for instruction_string in INPUT.lines() {
-            cpu.load(instruction_string).unwrap();
-            cpu.cycle().unwrap();
-        }
+        let program = parse(INPUT).unwrap();
 
         // Part 1
-        assert_eq!(13140, cpu.tot_signal_strenght);
+        assert_eq!(13140, part1(&program, None).unwrap());
 
         // Part 2
-        assert_eq!(PART_TWO_OUTPUT, cpu.crt.to_string());
+        assert_eq!(PART_TWO_OUTPUT, part2(&program).unwrap());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for instruction_string in [
+            "",
+            "nop",
+            "addx",
+            "addx a",
+            "addx 1.5",
+            "addx 99999999999999999999",
+        ] {
+            assert!(
+                CpuInstruction::try_from(instruction_string).is_err(),
+                "{instruction_string}"
+            );
+        }
+
+        // Sprite moved off screen.
+        assert!(Cpu::new()
+            .run(&[CpuInstruction::Addx(-5), CpuInstruction::Noop], None)
+            .is_err());
+
+        // Program longer than the CRT screen.
+        assert!(Cpu::new().run(&[CpuInstruction::Noop; 241], None).is_err());
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(10, |input| {
+            let program = parse(input)?;
+            Ok((part1(&program, None)?, part2(&program)?))
+        });
     }
 }
@@ -0,0 +1,197 @@
+use anyhow::Result;
+use day1::{cli::Args, examples, nested::Nested, parse::ParseMode, span::SpanError};
+use std::{cmp::Ordering, process::ExitCode};
+
+/// Distress signal packet.
+///
+/// Packets are ordered following the puzzle rules (see [`Nested::cmp_by`]), equality is
+/// consistent with the ordering so `3` and `[[3]]` are equal packets.
+#[derive(Debug, Clone)]
+struct Packet(Nested<u32>);
+
+impl Packet {
+    /// Divider packets added in part 2.
+    fn dividers() -> [Self; 2] {
+        [2, 6].map(|n| Self(Nested::List(vec![Nested::List(vec![Nested::Value(n)])])))
+    }
+}
+
+impl TryFrom<&str> for Packet {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(Self(value.parse()?))
+    }
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Packet {}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.compare(&other.0)
+    }
+}
+
+/// Parse the pairs of packets, separated by blank lines.
+fn parse(input: &str) -> Result<Vec<(Packet, Packet)>> {
+    let packet = |line: &str| -> Result<Packet> {
+        Ok(Packet(
+            line.parse().map_err(|e: SpanError| e.within(input, line))?,
+        ))
+    };
+
+    input
+        .split("\n\n")
+        .map(|pair| match pair.trim_end_matches('\n').split_once('\n') {
+            Some((left, right)) if !right.contains('\n') => Ok((packet(left)?, packet(right)?)),
+            _ => Err(SpanError::new(input, pair, "expected a pair of packets").into()),
+        })
+        .collect()
+}
+
+/// Sum of the indices of the pairs in the right order.
+fn part1(pairs: &[(Packet, Packet)]) -> usize {
+    pairs
+        .iter()
+        .enumerate()
+        .filter(|(_, (left, right))| left < right)
+        .map(|(idx, _)| idx + 1)
+        .sum()
+}
+
+/// Decoder key: product of the indices of the divider packets once all the packets are sorted.
+fn part2(pairs: &[(Packet, Packet)]) -> usize {
+    let [first, second] = Packet::dividers();
+    // Index of each divider is 1 + number of packets coming before it (the first divider comes
+    // before the second one), no need to actually sort them.
+    let before = |divider: &Packet| {
+        pairs
+            .iter()
+            .flat_map(|(left, right)| [left, right])
+            .filter(|packet| *packet < divider)
+            .count()
+    };
+
+    (before(&first) + 1) * (before(&second) + 2)
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 13)?;
+    let pairs = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!(
+        "Sum of the indices of the pairs in the right order: {}",
+        part1(&pairs)
+    );
+
+    // Part 2
+    println!("Decoder key for the distress signal: {}", part2(&pairs));
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn example_test() {
+        const INPUT: &str = r#"[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]
+"#;
+
+        let pairs = parse(INPUT).unwrap();
+        assert_eq!(8, pairs.len());
+        assert_eq!(13, part1(&pairs));
+        assert_eq!(140, part2(&pairs));
+
+        // Sorting all the packets with the dividers.
+        let mut packets: Vec<Packet> = pairs
+            .into_iter()
+            .flat_map(|(left, right)| [left, right])
+            .chain(Packet::dividers())
+            .collect();
+        packets.sort();
+        assert_eq!("[]", packets[0].0.to_string());
+        assert_eq!("[[2]]", packets[9].0.to_string());
+        assert_eq!("[[6]]", packets[13].0.to_string());
+        assert_eq!("[9]", packets[17].0.to_string());
+
+        assert_eq!(
+            Packet::try_from("[[3]]").unwrap(),
+            Packet::try_from("3").unwrap()
+        );
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "[1]",
+            "[1]\n[2]\n[3]",
+            "[1]\n[2]\n\n[1]",
+            "[1]\n[2\n",
+            "[1]\n[-2]\n",
+            "[1]\n[ 2]\n",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Errors point at the offending token in the input.
+        let error = parse("[1]\n[2]\n\n[3]\n[4,,5]\n").unwrap_err();
+        let error = error.downcast_ref::<SpanError>().unwrap();
+        assert_eq!((5, 4), (error.line(), error.column()));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(13, |input| {
+            let pairs = parse(input)?;
+            Ok((part1(&pairs), part2(&pairs)))
+        });
+    }
+}
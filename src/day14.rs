@@ -1,12 +1,20 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
 use anyhow::{Ok, Result};
+use day1::{cli::Args, direction::Direction8, examples, parse::ParseMode, span::SpanError};
 use std::{
+    collections::HashMap,
     fmt::{self, Display},
-    fs,
     process::ExitCode,
 };
 
 const SOURCE: Point = Point { x: 500, y: 0 };
 
+/// Number of cells above which the cave slice switches to the [`BitRows`] occupancy grid.
+const BITSET_THRESHOLD: usize = 1 << 16;
+/// Maximum number of cells of a cave slice (64 MiB of [`BitRows`] occupancy grid).
+const MAX_CELLS: usize = 1 << 29;
+
 /// Abyss kind.
 enum Abyss {
     Void,
@@ -14,7 +22,7 @@ enum Abyss {
 }
 
 /// Point on the rock slice.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 struct Point {
     x: usize,
     y: usize,
@@ -53,13 +61,15 @@ impl TryFrom<&str> for Point {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let (x, y) = value
             .split_once(',')
-            .ok_or_else(|| anyhow::format_err!("missing coordinates"))?;
+            .ok_or_else(|| anyhow::format_err!("expected ',' after x coordinate"))?;
+        // Spaces around the comma are tolerated.
+        let (x, y) = (x.trim_end_matches(' '), y.trim_start_matches(' '));
 
         Ok(Point::new(
             x.parse::<usize>()
-                .map_err(|_| anyhow::format_err!("coordinates must be unsigned integers"))?,
+                .map_err(|_| anyhow::format_err!("x coordinate must be an unsigned integer"))?,
             y.parse::<usize>()
-                .map_err(|_| anyhow::format_err!("coordinates must be unsigned integers"))?,
+                .map_err(|_| anyhow::format_err!("y coordinate must be an unsigned integer"))?,
         ))
     }
 }
@@ -79,6 +89,19 @@ impl RockPath {
         }
     }
 
+    /// Iterate over the points of the path.
+    fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.verts.windows(2).flat_map(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            (a.y.min(b.y)..=a.y.max(b.y)).flat_map(move |y| {
+                (a.x.min(b.x)..=a.x.max(b.x))
+                    .map(move |x| Point::new(x, y))
+                    // Diagonal segments aren't part of the path.
+                    .filter(move |_| a.x == b.x || a.y == b.y)
+            })
+        })
+    }
+
     /// Check wheter `self` contains [`Point`].
     fn contains(&self, point: Point) -> bool {
         let mut idx: usize = 0;
@@ -106,6 +129,56 @@ impl RockPath {
     }
 }
 
+/// Occupancy grid storing each row of the cave slice as a bitset (one bit per cell, set when
+/// rock or sand occupies it).
+#[derive(Debug, Clone)]
+struct BitRows {
+    /// Smallest x coordinate covered by the grid.
+    min_x: usize,
+    /// Number of rows.
+    rows: usize,
+    /// Number of 64 bits words per row.
+    words: usize,
+    /// Row-major bits.
+    bits: Vec<u64>,
+}
+
+impl BitRows {
+    /// Construct a new empty instance covering `min_x..=max_x` and `0..rows`.
+    fn new(min_x: usize, max_x: usize, rows: usize) -> Self {
+        let words = (max_x - min_x) / 64 + 1;
+        Self {
+            min_x,
+            rows,
+            words,
+            bits: vec![0; words * rows],
+        }
+    }
+
+    /// Word index and bit mask of the given point, if covered by the grid.
+    fn locate(&self, point: Point) -> Option<(usize, u64)> {
+        let x = point.x.checked_sub(self.min_x)?;
+        if x >= self.words * 64 || point.y >= self.rows {
+            return None;
+        }
+
+        Some((point.y * self.words + x / 64, 1 << (x % 64)))
+    }
+
+    /// Check wheter the given point is occupied.
+    fn contains(&self, point: Point) -> bool {
+        self.locate(point)
+            .is_some_and(|(word, mask)| self.bits[word] & mask != 0)
+    }
+
+    /// Mark the given point as occupied.
+    fn insert(&mut self, point: Point) {
+        if let Some((word, mask)) = self.locate(point) {
+            self.bits[word] |= mask;
+        }
+    }
+}
+
 /// Cave slice.
 #[derive(Debug, Clone)]
 struct CaveSlice {
@@ -117,11 +190,29 @@ struct CaveSlice {
     falling: SandGrain,
     /// Deposited grains of sand.
     sand: Vec<SandGrain>,
+    /// Occupancy grid, used instead of scanning rock paths and sand for large cave slices.
+    occupancy: Option<BitRows>,
 }
 
 impl CaveSlice {
-    /// Construct a new instance.
-    fn new(paths: &[RockPath]) -> Self {
+    /// Construct a new instance, selecting the [`BitRows`] occupancy grid when the slice spans
+    /// more than [`BITSET_THRESHOLD`] cells, rejecting slices of more than [`MAX_CELLS`] cells.
+    fn new(paths: &[RockPath]) -> Result<Self> {
+        let mut cave_slice = Self::with_occupancy(paths, false);
+        match cave_slice.cells() {
+            Some(cells) if cells <= MAX_CELLS => {
+                if cells > BITSET_THRESHOLD {
+                    cave_slice = Self::with_occupancy(paths, true);
+                }
+            }
+            _ => anyhow::bail!("cave slice spans more than {MAX_CELLS} cells"),
+        }
+
+        Ok(cave_slice)
+    }
+
+    /// Construct a new instance, with or without the [`BitRows`] occupancy grid.
+    fn with_occupancy(paths: &[RockPath], bitset: bool) -> Self {
         let mut max_y: usize = 0;
         for path in paths {
             for point in &path.verts {
@@ -131,46 +222,87 @@ impl CaveSlice {
             }
         }
 
-        Self {
+        let mut cave_slice = Self {
             rock_paths: paths.to_vec(),
             max_y,
             falling: SandGrain::new(),
             sand: vec![],
+            occupancy: None,
+        };
+
+        if bitset {
+            let (min_x, max_x) = cave_slice.x_bounds();
+            // Sand rests at most on the floor, two rows below the lowest rock.
+            let mut occupancy = BitRows::new(min_x, max_x, max_y + 3);
+            for path in paths {
+                path.points().for_each(|point| occupancy.insert(point));
+            }
+            cave_slice.occupancy = Some(occupancy);
+        }
+
+        cave_slice
+    }
+
+    /// Smallest and largest x coordinates reachable by rock or sand (saturating at the bounds
+    /// of `usize`).
+    fn x_bounds(&self) -> (usize, usize) {
+        // Sand spreads at most one column per row on both sides of the source.
+        let spread = self.max_y.saturating_add(2);
+        self.rock_paths
+            .iter()
+            .flat_map(|path| path.verts.iter())
+            .fold(
+                (
+                    SOURCE.x.saturating_sub(spread),
+                    SOURCE.x.saturating_add(spread),
+                ),
+                |(min_x, max_x), point| (min_x.min(point.x), max_x.max(point.x)),
+            )
+    }
+
+    /// Number of cells reachable by rock or sand, down to the floor (`None` on overflow).
+    fn cells(&self) -> Option<usize> {
+        let (min_x, max_x) = self.x_bounds();
+        (max_x - min_x)
+            .checked_add(1)?
+            .checked_mul(self.max_y.checked_add(3)?)
+    }
+
+    /// Check wheter rock or deposited sand occupies the given position.
+    fn is_occupied(&self, position: Point) -> bool {
+        if let Some(occupancy) = &self.occupancy {
+            return occupancy.contains(position);
+        }
+
+        // Iterate over deposited grains reverse because sand accumulates from bottom to top,
+        // so it will take less iteations to find a deposited grain reversing.
+        self.sand
+            .iter()
+            .rev()
+            .any(|sand_grain| position == sand_grain.position)
+            || self.rock_paths.iter().any(|path| path.contains(position))
+    }
+
+    /// Deposit the falling grain of sand.
+    fn deposit(&mut self) {
+        if let Some(occupancy) = &mut self.occupancy {
+            occupancy.insert(self.falling.position);
         }
+        self.sand.push(self.falling);
     }
 
     /// Calculates next valid position for falling grain of sand and updates `falling` grain
     /// position accordingly. Return `FallingState`.
     fn fall(&mut self, abyss_kind: &Abyss) -> Result<FallingState> {
-        for move_option in [
-            MoveOption::Down,
-            MoveOption::DownLeft,
-            MoveOption::DownRight,
+        for direction in [
+            Direction8::Down,
+            Direction8::DownLeft,
+            Direction8::DownRight,
         ] {
             // Check if virtual position is a valid position.
-            let virtual_position = self.falling.virtual_position(move_option)?;
-            let mut occupied = false;
-
-            // Check if deposited grain of sand occupies position.
-            // Iterate over deposited grains reverse because sand accumulates from bottom to top,
-            // so it will take less iteations to find a deposited grain reversing.
-            for sand_grain in self.sand.iter().rev() {
-                if virtual_position == sand_grain.position {
-                    occupied = true;
-                }
-            }
-
-            // Check if rock path occupies position.
-            if self.sand.is_empty() || !occupied {
-                for path in &self.rock_paths {
-                    if path.contains(virtual_position) {
-                        occupied = true;
-                    }
-                }
-            }
-
+            let virtual_position = self.falling.virtual_position(direction)?;
             // Virtual position is a valid position.
-            if !occupied {
+            if !self.is_occupied(virtual_position) {
                 return Ok(if virtual_position.y < self.max_y {
                     self.falling.update(virtual_position);
                     FallingState::Falling
@@ -179,7 +311,7 @@ impl CaveSlice {
                         Abyss::Void => FallingState::IntoTheVoid,
                         Abyss::Floor => {
                             if virtual_position.y >= self.max_y + 2 {
-                                self.sand.push(self.falling);
+                                self.deposit();
                                 FallingState::Deposited
                             } else {
                                 self.falling.update(virtual_position);
@@ -192,13 +324,13 @@ impl CaveSlice {
         }
 
         if self.falling.position == SOURCE {
-            self.sand.push(self.falling);
+            self.deposit();
             return Ok(FallingState::Blocking);
         }
 
         // No valid virtual position found. The grain of sand reached its rest state: append
         // `self.falling` to `self.sand`.
-        self.sand.push(self.falling);
+        self.deposit();
         Ok(FallingState::Deposited)
     }
 
@@ -222,21 +354,25 @@ impl TryFrom<&str> for CaveSlice {
     type Error = anyhow::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Ok(CaveSlice::new(
+        CaveSlice::new(
             &value
                 .trim()
                 .split('\n')
                 .map(|line| -> Result<RockPath> {
                     Ok(RockPath::new(
                         &line
-                            .replace(' ', "")
                             .split("->")
-                            .map(|coordinates| -> Result<Point> { Point::try_from(coordinates) })
+                            .map(|coordinates| -> Result<Point> {
+                                // Errors point at the offending coordinates in the input.
+                                let coordinates = coordinates.trim();
+                                Point::try_from(coordinates)
+                                    .map_err(|e| SpanError::new(value, coordinates, e).into())
+                            })
                             .collect::<Result<Vec<Point>>>()?,
                     ))
                 })
                 .collect::<Result<Vec<RockPath>>>()?,
-        ))
+        )
     }
 }
 
@@ -259,27 +395,15 @@ impl SandGrain {
     }
 
     /// Return sand grain virtual position.
-    fn virtual_position(&self, move_option: MoveOption) -> Result<Point> {
+    fn virtual_position(&self, direction: Direction8) -> Result<Point> {
         let mut virtual_position = self.position;
-
-        match move_option {
-            MoveOption::Down => virtual_position.traslate(0, 1)?,
-            MoveOption::DownLeft => virtual_position.traslate(-1, 1)?,
-            MoveOption::DownRight => virtual_position.traslate(1, 1)?,
-        };
+        let (x, y) = direction.unit_vector();
+        virtual_position.traslate(x, y)?;
 
         Ok(virtual_position)
     }
 }
 
-/// Move options of the grain of sand.
-#[derive(Debug)]
-enum MoveOption {
-    Down,
-    DownLeft,
-    DownRight,
-}
-
 /// Falling state of the grain of sand.
 #[derive(Debug)]
 enum FallingState {
@@ -293,21 +417,160 @@ enum FallingState {
     Blocking,
 }
 
+/// Tile of the cave slice in the liquid-flow physics mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Rock,
+    /// Water passing through.
+    Flowing,
+    /// Water at rest in a basin.
+    Settled,
+}
+
+/// Cave slice filled by water pouring from the source (liquid-flow physics mode, à la AoC 2018
+/// day 17): water falls down, spreads horizontally on rock and settled water, and settles in
+/// basins.
+#[derive(Debug)]
+struct Reservoir {
+    tiles: HashMap<Point, Tile>,
+    /// Min y coordinate of the rock paths.
+    min_y: usize,
+    /// Max y coordinate before the void.
+    max_y: usize,
+}
+
+impl Reservoir {
+    /// Pour water from the source into the cave slice until it either settled or flows into
+    /// the void.
+    fn new(cave_slice: &CaveSlice) -> Self {
+        let tiles: HashMap<Point, Tile> = cave_slice
+            .rock_paths
+            .iter()
+            .flat_map(RockPath::points)
+            .map(|point| (point, Tile::Rock))
+            .collect();
+        let min_y = tiles.keys().map(|point| point.y).min().unwrap_or_default();
+
+        let mut reservoir = Self {
+            tiles,
+            min_y,
+            max_y: cave_slice.max_y,
+        };
+        reservoir.flow(SOURCE);
+
+        reservoir
+    }
+
+    /// Check wheter water can rest on the given point.
+    fn is_solid(&self, point: Point) -> bool {
+        matches!(self.tiles.get(&point), Some(Tile::Rock | Tile::Settled))
+    }
+
+    /// Water reaches the given empty point.
+    fn flow(&mut self, point: Point) {
+        self.tiles.insert(point, Tile::Flowing);
+        if point.y >= self.max_y {
+            return;
+        }
+
+        let below = Point::new(point.x, point.y + 1);
+        if !self.tiles.contains_key(&below) {
+            self.flow(below);
+        }
+        if !self.is_solid(below) {
+            // Water falls away.
+            return;
+        }
+
+        let (left, left_wall) = self.spread(point, Direction8::Left);
+        let (right, right_wall) = self.spread(point, Direction8::Right);
+        if left_wall && right_wall {
+            for x in left..=right {
+                self.tiles.insert(Point::new(x, point.y), Tile::Settled);
+            }
+        }
+    }
+
+    /// Spread water horizontally from the given point, returning the last x coordinate reached
+    /// and whether it's bounded by a wall (otherwise water falls from there).
+    fn spread(&mut self, point: Point, direction: Direction8) -> (usize, bool) {
+        let mut current = point;
+        loop {
+            let below = Point::new(current.x, current.y + 1);
+            if !self.tiles.contains_key(&below) {
+                self.flow(below);
+            }
+            if !self.is_solid(below) {
+                return (current.x, false);
+            }
+
+            let mut next = current;
+            if next.traslate(direction.unit_vector().0, 0).is_err() {
+                // Nothing to rest on past the left edge of the slice.
+                return (current.x, false);
+            }
+            if self.tiles.get(&next) == Some(&Tile::Rock) {
+                return (current.x, true);
+            }
+            self.tiles.insert(next, Tile::Flowing);
+            current = next;
+        }
+    }
+
+    /// Count tiles reached by water and tiles of settled water, within the rock paths rows.
+    fn count_water(&self) -> (usize, usize) {
+        self.tiles
+            .iter()
+            .filter(|(point, _)| (self.min_y..=self.max_y).contains(&point.y))
+            .fold((0, 0), |(reached, settled), (_, tile)| match tile {
+                Tile::Rock => (reached, settled),
+                Tile::Flowing => (reached + 1, settled),
+                Tile::Settled => (reached + 1, settled + 1),
+            })
+    }
+}
+
+/// Parse the cave slice from the rock paths scan.
+fn parse(input: &str) -> Result<CaveSlice> {
+    CaveSlice::try_from(input)
+}
+
+/// Units of sand at rest before sand starts flowing into the abyss.
+fn part1(cave_slice: &CaveSlice) -> Result<usize> {
+    cave_slice.clone().count_sand_grains(Abyss::Void)
+}
+
+/// Units of sand at rest once the source of the sand is blocked.
+fn part2(cave_slice: &CaveSlice) -> Result<usize> {
+    cave_slice.clone().count_sand_grains(Abyss::Floor)
+}
+
 fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day14.dat")?;
+    let args = Args::from_env(&["--physics", examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 14)?;
+    let cave_slice = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+
+    match args.value("--physics").unwrap_or("sand") {
+        "sand" => {}
+        "water" => {
+            let (reached, settled) = Reservoir::new(&cave_slice).count_water();
+            println!("Number of tiles reached by water is: {reached}");
+            println!("Number of tiles of settled water is: {settled}");
+            return Ok(());
+        }
+        p => anyhow::bail!("'{p}' is not a valid physics mode (sand, water)"),
+    }
 
     // Part 1
-    let mut cave_slice = CaveSlice::try_from(input.as_str())?;
     println!(
         "Number of deposited grains of sand before falling into the abyss is: {}",
-        cave_slice.count_sand_grains(Abyss::Void)?
+        part1(&cave_slice)?
     );
 
     // Part 2
-    let mut cave_slice = CaveSlice::try_from(input.as_str())?;
     println!(
         "Number of deposited grains of sand before blocking the sand source is: {}",
-        cave_slice.count_sand_grains(Abyss::Floor)?
+        part2(&cave_slice)?
     );
 
     Ok(())
@@ -331,8 +594,91 @@ mod test {
         const INPUT: &str = r#"498,4 -> 498,6 -> 496,6
 503,4 -> 502,4 -> 502,9 -> 494,9"#;
 
-        let mut cave_slice = CaveSlice::try_from(INPUT).unwrap();
-        assert_eq!(24, cave_slice.count_sand_grains(Abyss::Void).unwrap());
-        assert_eq!(93, cave_slice.count_sand_grains(Abyss::Floor).unwrap());
+        let cave_slice = parse(INPUT).unwrap();
+        assert_eq!(24, part1(&cave_slice).unwrap());
+        assert_eq!(93, part2(&cave_slice).unwrap());
+
+        // Same results on the bitset occupancy grid.
+        let paths = cave_slice.rock_paths;
+        let cave_slice = CaveSlice::with_occupancy(&paths, true);
+        assert!(cave_slice.occupancy.is_some());
+        assert_eq!(24, part1(&cave_slice).unwrap());
+        assert_eq!(93, part2(&cave_slice).unwrap());
+
+        // Leading blank line.
+        let cave_slice = parse(&format!("\n{INPUT}"));
+        assert_eq!(24, part1(&cave_slice.unwrap()).unwrap());
+
+        // Spaces around the commas.
+        let cave_slice = parse("498, 4 -> 498 ,6 -> 496 , 6\n503,4 -> 502,4 -> 502,9 -> 494,9");
+        assert_eq!(24, part1(&cave_slice.unwrap()).unwrap());
+    }
+
+    #[test]
+    fn water_physics() {
+        // AoC 2018 day 17 example.
+        const INPUT: &str = r#"495,2 -> 495,7 -> 501,7 -> 501,3
+498,2 -> 498,4
+506,1 -> 506,2
+498,10 -> 498,13 -> 504,13 -> 504,10"#;
+
+        let reservoir = Reservoir::new(&parse(INPUT).unwrap());
+        assert_eq!((57, 29), reservoir.count_water());
+
+        // Water flows straight into the void without rocks to rest on.
+        let reservoir = Reservoir::new(&parse("0,5 -> 0,6").unwrap());
+        assert_eq!((2, 0), reservoir.count_water());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "498,4 ->",
+            "498",
+            "a,4",
+            "-1,4",
+            "498,4 -> 498",
+            "498,4 -> ,6",
+            "4 98,4 -> 498,6",
+            // Too large to simulate, or overflowing the number of cells.
+            "496,999999999996",
+            "18446744073709551615,4",
+            "0,18446744073709551615",
+        ] {
+            assert!(CaveSlice::try_from(input).is_err(), "{input}");
+        }
+
+        let error = CaveSlice::try_from("498,4 -> 498,6\n503,4 -> 5024 -> 502,9").unwrap_err();
+        assert_eq!(
+            "line 2, column 10: expected ',' after x coordinate
+  |
+2 | 503,4 -> 5024 -> 502,9
+  |          ^^^^",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(14, |input| {
+            let cave_slice = parse(input)?;
+            Ok((part1(&cave_slice)?, part2(&cave_slice)?))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_cave_slice(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day14.dat")).unwrap();
+        b.iter(|| parse(&input).unwrap());
     }
 }
@@ -0,0 +1,311 @@
+use anyhow::Result;
+use day1::{
+    cli::Args,
+    examples,
+    interval::{self, Interval},
+    parse::ParseMode,
+    span::SpanError,
+};
+use std::{collections::HashSet, process::ExitCode};
+
+/// Row checked in part 1.
+const ROW: i64 = 2_000_000;
+/// Maximum coordinate of the distress beacon searched in part 2.
+const MAX_COORDINATE: i64 = 4_000_000;
+/// Row checked and maximum coordinate searched in the examples.
+const EXAMPLE_SCALE: (i64, i64) = (10, 20);
+/// Bound of the absolute value of coordinates, keeping the coverage arithmetic from
+/// overflowing.
+const COORDINATE_BOUND: i64 = 1 << 40;
+
+/// Point on the cave map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl TryFrom<&str> for Point {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let coordinate = |s: &str, prefix: &str| -> Result<i64> {
+            s.strip_prefix(prefix)
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| anyhow::format_err!("invalid coordinate '{s}'"))
+        };
+
+        match value.split_once(", ") {
+            Some((x, y)) => Ok(Self {
+                x: coordinate(x, "x=")?,
+                y: coordinate(y, "y=")?,
+            }),
+            None => anyhow::bail!("expected coordinates as 'x=<x>, y=<y>'"),
+        }
+    }
+}
+
+impl Point {
+    /// Manhattan distance from `other`.
+    fn distance(&self, other: &Point) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+/// Sensor with its closest beacon.
+#[derive(Debug)]
+struct Sensor {
+    position: Point,
+    beacon: Point,
+    /// Distance of the closest beacon: no other beacon is this close to the sensor.
+    radius: u64,
+}
+
+impl TryFrom<&str> for Sensor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (position, beacon) = value
+            .strip_prefix("Sensor at ")
+            .and_then(|s| s.split_once(": closest beacon is at "))
+            .ok_or_else(|| {
+                anyhow::format_err!(
+                    "expected 'Sensor at <position>: closest beacon is at <position>'"
+                )
+            })?;
+        let (position, beacon) = (Point::try_from(position)?, Point::try_from(beacon)?);
+
+        if [position.x, position.y, beacon.x, beacon.y]
+            .iter()
+            .any(|c| c.abs() > COORDINATE_BOUND)
+        {
+            anyhow::bail!("coordinates must be within ±{COORDINATE_BOUND}");
+        }
+
+        Ok(Self {
+            position,
+            beacon,
+            radius: position.distance(&beacon),
+        })
+    }
+}
+
+impl Sensor {
+    /// Whether the sensor rules out a beacon at the given point.
+    fn covers(&self, point: &Point) -> bool {
+        self.position.distance(point) <= self.radius
+    }
+
+    /// Positions covered by the sensor on the given row.
+    fn coverage(&self, row: i64) -> Option<Interval> {
+        let reach = self.radius.checked_sub(self.position.y.abs_diff(row))? as i64;
+        Interval::new(self.position.x - reach, self.position.x + reach)
+    }
+
+    /// Lines running just outside the sensor coverage, as `(x + y, x - y)` constants of the
+    /// two diagonal directions.
+    fn boundaries(&self) -> ([i64; 2], [i64; 2]) {
+        let (a, b) = (
+            self.position.x + self.position.y,
+            self.position.x - self.position.y,
+        );
+        let r = self.radius as i64 + 1;
+        ([a - r, a + r], [b - r, b + r])
+    }
+}
+
+/// Parse the sensors, one per line.
+fn parse(input: &str) -> Result<Vec<Sensor>> {
+    input
+        .lines()
+        .map(|line| Sensor::try_from(line).map_err(|e| SpanError::new(input, line, e).into()))
+        .collect()
+}
+
+/// Merged coverage of the sensors on the given row.
+fn coverage(sensors: &[Sensor], row: i64) -> Vec<Interval> {
+    interval::merge(sensors.iter().filter_map(|sensor| sensor.coverage(row)))
+}
+
+/// Number of positions on the given row where a beacon can't be.
+fn part1(sensors: &[Sensor], row: i64) -> u64 {
+    let beacons: HashSet<Point> = sensors
+        .iter()
+        .map(|sensor| sensor.beacon)
+        .filter(|beacon| beacon.y == row)
+        .collect();
+
+    // Beacons on the row are covered by their own sensor.
+    coverage(sensors, row)
+        .iter()
+        .map(Interval::len)
+        .sum::<u64>()
+        - beacons.len() as u64
+}
+
+/// Tuning frequency of the only position in `0..=max` on both axes where the distress beacon
+/// can be.
+fn part2(sensors: &[Sensor], max: i64) -> Option<i64> {
+    let inside = |p: &Point| (0..=max).contains(&p.x) && (0..=max).contains(&p.y);
+    let uncovered = |p: &Point| sensors.iter().all(|sensor| !sensor.covers(p));
+
+    // The uncovered position is next to covered ones on each side, so it lies on the lines
+    // running just outside the coverage of the sensors: either where two of them cross, or
+    // where one meets the edge of the search area (or at a corner of the search area).
+    let (a, b): (Vec<[i64; 2]>, Vec<[i64; 2]>) = sensors.iter().map(Sensor::boundaries).unzip();
+    let (a, b) = (a.concat(), b.concat());
+    let crossings = a.iter().flat_map(|a| {
+        b.iter()
+            .filter(move |b| (a + *b) % 2 == 0)
+            .map(move |b| Point {
+                x: (a + b) / 2,
+                y: (a - b) / 2,
+            })
+    });
+    let edges = a
+        .iter()
+        .flat_map(|a| [(0, *a), (max, a - max), (*a, 0), (a - max, max)])
+        .chain(
+            b.iter()
+                .flat_map(|b| [(0, -b), (max, max - b), (*b, 0), (b + max, max)]),
+        )
+        .chain([(0, 0), (0, max), (max, 0), (max, max)])
+        .map(|(x, y)| Point { x, y });
+
+    crossings
+        .chain(edges)
+        .find(|p| inside(p) && uncovered(p))
+        .map(|p| p.x * 4_000_000 + p.y)
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&["--row", "--max", examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 15)?;
+    let sensors = parse(&ParseMode::from_args(&args).normalize(&input))?;
+    let (row, max) = match args.value(examples::OPTION) {
+        Some(_) => EXAMPLE_SCALE,
+        None => (ROW, MAX_COORDINATE),
+    };
+    let row = args.parse_value("--row")?.unwrap_or(row);
+    let max = args.parse_value("--max")?.unwrap_or(max);
+    if !(0..=COORDINATE_BOUND).contains(&max) {
+        anyhow::bail!("maximum coordinate must be within 0 and {COORDINATE_BOUND}");
+    }
+
+    // Part 1
+    println!(
+        "Positions where a beacon can't be on row {row}: {}",
+        part1(&sensors, row)
+    );
+
+    // Part 2
+    match part2(&sensors, max) {
+        Some(frequency) => println!("Tuning frequency of the distress beacon: {frequency}"),
+        None => println!("No position found for the distress beacon"),
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3"#;
+
+    #[test]
+    fn example_test() {
+        let sensors = parse(INPUT).unwrap();
+        assert_eq!(14, sensors.len());
+        assert_eq!(9, sensors[6].radius);
+        assert_eq!(Interval::new(2, 14), sensors[6].coverage(10));
+        assert_eq!(None, sensors[6].coverage(17));
+
+        assert_eq!(vec![Interval::new(-2, 24).unwrap()], coverage(&sensors, 10));
+        assert_eq!(26, part1(&sensors, 10));
+        assert_eq!(Some(56000011), part2(&sensors, 20));
+    }
+
+    #[test]
+    fn search_area_edges() {
+        let sensor = |x: i64, y: i64, r: i64| {
+            format!(
+                "Sensor at x={x}, y={y}: closest beacon is at x={}, y={y}",
+                x + r
+            )
+        };
+
+        // Single position sensors covering all of the 0..=4 square but a position on its edge.
+        let input: Vec<String> = (0..=4)
+            .flat_map(|x| (0..=4).map(move |y| (x, y)))
+            .filter(|p| *p != (4, 2))
+            .map(|(x, y)| sensor(x, y, 0))
+            .collect();
+        assert_eq!(
+            Some(4 * 4_000_000 + 2),
+            part2(&parse(&input.join("\n")).unwrap(), 4)
+        );
+
+        // Only the corner farthest from the sensor is uncovered.
+        let sensors = parse(&sensor(0, 0, 7)).unwrap();
+        assert_eq!(Some(4 * 4_000_000 + 4), part2(&sensors, 4));
+
+        // Fully covered area.
+        let sensors = parse(&sensor(2, 2, 4)).unwrap();
+        assert_eq!(None, part2(&sensors, 4));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "Sensor at x=2, y=18",
+            "Sensor at x=2, y=18: closest beacon is at x=-2",
+            "Sensor at x=2 y=18: closest beacon is at x=-2, y=15",
+            "Sensor at x=2, y=18: closest beacon is at x=-2, y=a",
+            "Sensor at x=2, y=18: closest beacon is at x=-2, y=15 ",
+            "Sensor at x=9223372036854775807, y=0: closest beacon is at x=0, y=0",
+            "Sensor at x=0, y=0: closest beacon is at x=0, y=-1099511627777",
+            "sensor at x=2, y=18: closest beacon is at x=-2, y=15",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        let sensors = parse("").unwrap();
+        assert_eq!(0, part1(&sensors, 10));
+        assert_eq!(Some(0), part2(&sensors, 0));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(15, |input| {
+            let sensors = parse(input)?;
+            Ok((
+                part1(&sensors, EXAMPLE_SCALE.0),
+                part2(&sensors, EXAMPLE_SCALE.1)
+                    .ok_or_else(|| anyhow::format_err!("no position found"))?,
+            ))
+        });
+    }
+}
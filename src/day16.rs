@@ -0,0 +1,376 @@
+use anyhow::Result;
+use day1::{cache::Cache, cli::Args, examples, parse::ParseMode, span::SpanError};
+use std::{
+    collections::{HashMap, VecDeque},
+    process::ExitCode,
+};
+
+/// Valve everyone starts from.
+const START: &str = "AA";
+/// Minutes before the volcano erupts.
+const TIME: u32 = 30;
+/// Minutes left after teaching an elephant to help.
+const TIME_WITH_ELEPHANT: u32 = 26;
+
+/// Scanned valve.
+#[derive(Debug, PartialEq, Eq)]
+struct Valve<'a> {
+    name: &'a str,
+    rate: u32,
+    /// Valves reachable through tunnels.
+    tunnels: Vec<&'a str>,
+}
+
+impl<'a> TryFrom<&'a str> for Valve<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let (name, rest) = value
+            .strip_prefix("Valve ")
+            .and_then(|s| s.split_once(" has flow rate="))
+            .ok_or_else(|| anyhow::format_err!("expected 'Valve <name> has flow rate=<rate>'"))?;
+        let (rate, tunnels) = rest
+            .split_once("; ")
+            .ok_or_else(|| anyhow::format_err!("expected '; ' after flow rate"))?;
+        let tunnels = tunnels
+            .strip_prefix("tunnels lead to valves ")
+            .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))
+            .ok_or_else(|| anyhow::format_err!("expected 'tunnels lead to valves <names>'"))?;
+
+        let valid_name = |name: &'a str| match !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_uppercase())
+        {
+            true => Ok(name),
+            false => anyhow::bail!("invalid valve name '{name}'"),
+        };
+
+        Ok(Self {
+            name: valid_name(name)?,
+            rate: rate
+                .parse()
+                .map_err(|_| anyhow::format_err!("invalid flow rate '{rate}'"))?,
+            tunnels: tunnels
+                .split(", ")
+                .map(valid_name)
+                .collect::<Result<Vec<&str>>>()?,
+        })
+    }
+}
+
+/// Network of the valves worth opening.
+#[derive(Debug)]
+struct Network {
+    /// Flow rate of each valve worth opening.
+    rates: Vec<u32>,
+    /// Minutes to walk between the valves worth opening (`None` if unreachable), the starting
+    /// valve being last.
+    distances: Vec<Vec<Option<u32>>>,
+}
+
+impl Network {
+    /// Maximum number of valves worth opening.
+    const MAX_VALVES: usize = 20;
+
+    /// Reduce the scanned valves to the ones with a positive flow rate, with the distances
+    /// between them.
+    fn new(valves: &[Valve]) -> Result<Self> {
+        let mut indices: HashMap<&str, usize> = HashMap::with_capacity(valves.len());
+        for (idx, valve) in valves.iter().enumerate() {
+            if indices.insert(valve.name, idx).is_some() {
+                anyhow::bail!("duplicate valve '{}'", valve.name);
+            }
+        }
+        let tunnels = valves
+            .iter()
+            .map(|valve| {
+                valve
+                    .tunnels
+                    .iter()
+                    .map(|name| {
+                        indices
+                            .get(name)
+                            .copied()
+                            .ok_or_else(|| anyhow::format_err!("no such valve '{name}'"))
+                    })
+                    .collect::<Result<Vec<usize>>>()
+            })
+            .collect::<Result<Vec<Vec<usize>>>>()?;
+
+        // Opened valves are tracked in a bitmask, indexing a table of the best pressure released
+        // for each set of opened valves.
+        let mut relevant: Vec<usize> = (0..valves.len())
+            .filter(|idx| valves[*idx].rate > 0)
+            .collect();
+        if relevant.len() > Self::MAX_VALVES {
+            anyhow::bail!(
+                "at most {} valves can have a positive flow rate",
+                Self::MAX_VALVES
+            );
+        }
+        relevant.push(
+            *indices
+                .get(START)
+                .ok_or_else(|| anyhow::format_err!("missing starting valve '{START}'"))?,
+        );
+
+        // Breadth-first search from each relevant valve.
+        let distances = relevant
+            .iter()
+            .map(|from| {
+                let mut distance: Vec<Option<u32>> = vec![None; valves.len()];
+                distance[*from] = Some(0);
+                let mut queue = VecDeque::from([*from]);
+                while let Some(idx) = queue.pop_front() {
+                    let next = distance[idx].map(|d| d + 1);
+                    for tunnel in &tunnels[idx] {
+                        if distance[*tunnel].is_none() {
+                            distance[*tunnel] = next;
+                            queue.push_back(*tunnel);
+                        }
+                    }
+                }
+
+                relevant.iter().map(|to| distance[*to]).collect()
+            })
+            .collect();
+
+        Ok(Self {
+            rates: relevant[..relevant.len() - 1]
+                .iter()
+                .map(|idx| valves[*idx].rate)
+                .collect(),
+            distances,
+        })
+    }
+
+    /// Highest pressure released in the given time for each set of opened valves (indexed by the
+    /// bitmask of their indices), opening valves in any order (`None` for the sets that can't be
+    /// opened in time).
+    fn best_by_opened(&self, time: u32) -> Vec<Option<u64>> {
+        let mut best: Vec<Option<u64>> = vec![None; 1 << self.rates.len()];
+        self.search(self.rates.len(), time, 0, 0, &mut best, &mut Cache::new());
+        best
+    }
+
+    /// Depth-first search of the orders valves can be opened in from the valve at `position`.
+    ///
+    /// What can still be released only depends on the position, the time left and the opened
+    /// valves: `seen` memoizes the most pressure released reaching each of these states, and a
+    /// state reached again without releasing more isn't searched twice.
+    fn search(
+        &self,
+        position: usize,
+        time: u32,
+        opened: usize,
+        pressure: u64,
+        best: &mut [Option<u64>],
+        seen: &mut Cache<(usize, u32, usize), u64>,
+    ) {
+        let state = (position, time, opened);
+        if seen
+            .get(&state)
+            .is_some_and(|released| *released >= pressure)
+        {
+            return;
+        }
+        seen.insert(state, pressure);
+        best[opened] = best[opened].max(Some(pressure));
+
+        for (valve, rate) in self.rates.iter().enumerate() {
+            if opened & 1 << valve != 0 {
+                continue;
+            }
+
+            // Walk to the valve and open it, with time left for the pressure to be released.
+            let Some(distance) = self.distances[position][valve] else {
+                continue;
+            };
+            if let Some(left) = time.checked_sub(distance + 1).filter(|left| *left > 0) {
+                self.search(
+                    valve,
+                    left,
+                    opened | 1 << valve,
+                    pressure + *rate as u64 * left as u64,
+                    best,
+                    seen,
+                );
+            }
+        }
+    }
+}
+
+/// Parse the valves scan, one valve per line.
+fn parse(input: &str) -> Result<Network> {
+    let valves = input
+        .lines()
+        .map(|line| Valve::try_from(line).map_err(|e| SpanError::new(input, line, e).into()))
+        .collect::<Result<Vec<Valve>>>()?;
+
+    Network::new(&valves)
+}
+
+/// Most pressure that can be released alone in 30 minutes.
+fn part1(network: &Network) -> u64 {
+    network
+        .best_by_opened(TIME)
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or_default()
+}
+
+/// Most pressure that can be released in 26 minutes with the help of an elephant.
+fn part2(network: &Network) -> u64 {
+    // You and the elephant open disjoint sets of valves.
+    let mut best: Vec<(usize, u64)> = network
+        .best_by_opened(TIME_WITH_ELEPHANT)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(opened, pressure)| Some((opened, pressure?)))
+        .collect();
+    best.sort_unstable_by_key(|(_, pressure)| std::cmp::Reverse(*pressure));
+
+    let mut most: u64 = 0;
+    for (idx, (yours, pressure)) in best.iter().enumerate() {
+        // Sets are sorted by decreasing pressure: no later pair can do better.
+        if pressure * 2 <= most {
+            break;
+        }
+        for (elephants, elephant_pressure) in &best[idx..] {
+            if pressure + elephant_pressure <= most {
+                break;
+            }
+            if yours & elephants == 0 {
+                most = pressure + elephant_pressure;
+            }
+        }
+    }
+
+    most
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 16)?;
+    let network = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!("Most pressure released alone: {}", part1(&network));
+
+    // Part 2
+    println!(
+        "Most pressure released with the elephant: {}",
+        part2(&network)
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II"#;
+
+    #[test]
+    fn example_test() {
+        let network = parse(INPUT).unwrap();
+        assert_eq!(vec![13, 2, 20, 3, 22, 21], network.rates);
+        // From AA to HH, through DD, EE, FF and GG.
+        assert_eq!(Some(5), network.distances[6][4]);
+        assert_eq!(Some(0), network.distances[2][2]);
+
+        assert_eq!(1651, part1(&network));
+        assert_eq!(1707, part2(&network));
+    }
+
+    #[test]
+    fn degenerate_networks() {
+        // Nothing worth opening.
+        let network = parse("Valve AA has flow rate=0; tunnel leads to valve AA").unwrap();
+        assert_eq!(0, part1(&network));
+        assert_eq!(0, part2(&network));
+
+        // Unreachable valve, and a valve too far to be worth opening.
+        let network = parse(
+            "Valve AA has flow rate=0; tunnel leads to valve BB
+Valve BB has flow rate=0; tunnel leads to valve CC
+Valve CC has flow rate=5; tunnel leads to valve AA
+Valve DD has flow rate=100; tunnel leads to valve CC",
+        )
+        .unwrap();
+        assert_eq!(None, network.distances[2][1]);
+        assert_eq!(5 * 27, part1(&network));
+        assert_eq!(5 * 23, part2(&network));
+
+        // Elephant opening the valves you don't.
+        let network = parse(
+            "Valve AA has flow rate=0; tunnels lead to valves BB, CC
+Valve BB has flow rate=10; tunnel leads to valve AA
+Valve CC has flow rate=10; tunnel leads to valve AA",
+        )
+        .unwrap();
+        assert_eq!(10 * 28 + 10 * 25, part1(&network));
+        assert_eq!(2 * 10 * 24, part2(&network));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "Valve BB has flow rate=0; tunnel leads to valve BB",
+            "Valve AA has flow rate=0; tunnel leads to valve BB",
+            concat!(
+                "Valve AA has flow rate=0; tunnel leads to valve AA\n",
+                "Valve AA has flow rate=1; tunnel leads to valve AA"
+            ),
+            "Valve AA has flow rate=-1; tunnel leads to valve AA",
+            "Valve AA has flow rate=0, tunnel leads to valve AA",
+            "Valve AA has flow rate=0; tunnels lead to valves AA,AA",
+            "Valve aa has flow rate=0; tunnel leads to valve aa",
+            "Valve AA has flow rate=0; tunnel leads to valve ",
+            "Valve AA has flow rate=0; tunnel leads to the valve AA",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Too many valves worth opening.
+        let input: String = (b'A'..=b'U')
+            .map(|c| {
+                format!(
+                    "Valve B{} has flow rate=1; tunnel leads to valve AA\n",
+                    c as char
+                )
+            })
+            .collect();
+        let input = format!("{input}Valve AA has flow rate=0; tunnel leads to valve BA");
+        assert!(parse(&input).is_err());
+        assert!(parse(input.replacen("rate=1", "rate=0", 1).as_str()).is_ok());
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(16, |input| {
+            let network = parse(input)?;
+            Ok((part1(&network), part2(&network)))
+        });
+    }
+}
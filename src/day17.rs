@@ -0,0 +1,254 @@
+use anyhow::Result;
+use day1::{cli::Args, cycle, examples, parse::ParseMode};
+use std::process::ExitCode;
+
+/// Rocks counted in part 1.
+const ROCKS: u64 = 2022;
+/// Rocks counted in part 2.
+const MANY_ROCKS: u64 = 1_000_000_000_000;
+
+/// Rock shapes, in falling order, as rows from the bottom up: bit 6 is the leftmost column of the
+/// chamber, and each rock starts two units away from the left wall.
+const SHAPES: [&[u8]; 5] = [
+    &[0b0011110],
+    &[0b0001000, 0b0011100, 0b0001000],
+    &[0b0011100, 0b0000100, 0b0000100],
+    &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+    &[0b0011000, 0b0011000],
+];
+
+/// Jet of hot gas pushing the falling rocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jet {
+    Left,
+    Right,
+}
+
+impl TryFrom<char> for Jet {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            '<' => Self::Left,
+            '>' => Self::Right,
+            c => anyhow::bail!("invalid jet '{c}'"),
+        })
+    }
+}
+
+/// Chamber the rocks fall into, 7 units wide.
+///
+/// Only the topmost [`Chamber::DEPTH`] rows are kept: rocks never fall deeper than that below
+/// the top of the tower, so the chamber has a finite number of states and its evolution
+/// eventually repeats.
+#[derive(Debug, Default, Clone)]
+struct Chamber {
+    /// Rows of the tower from the bottom up, bit 6 being the leftmost column.
+    rows: Vec<u8>,
+    /// Rows dropped from the bottom of the tower.
+    trimmed: u64,
+    /// Index of the next rock shape.
+    rock: usize,
+    /// Index of the next jet.
+    jet: usize,
+}
+
+/// Chambers are equal if the rocks falling next land the same way (the number of rows trimmed
+/// from the bottom doesn't matter).
+impl PartialEq for Chamber {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows && self.rock == other.rock && self.jet == other.jet
+    }
+}
+
+impl Chamber {
+    /// Rows kept below the top of the tower.
+    const DEPTH: usize = 64;
+
+    /// Height of the tower.
+    fn height(&self) -> u64 {
+        self.trimmed + self.rows.len() as u64
+    }
+
+    /// Whether the rock with its bottom row at height `y` overlaps the tower.
+    fn collides(&self, rock: &[u8], y: usize) -> bool {
+        rock.iter()
+            .zip(self.rows.iter().skip(y))
+            .any(|(rock, row)| rock & row != 0)
+    }
+
+    /// Drop the next rock until it comes to rest.
+    fn drop_rock(&mut self, jets: &[Jet]) {
+        let mut rock: Vec<u8> = SHAPES[self.rock].to_vec();
+        self.rock = (self.rock + 1) % SHAPES.len();
+        let mut y = self.rows.len() + 3;
+
+        loop {
+            let jet = jets[self.jet];
+            self.jet = (self.jet + 1) % jets.len();
+            let pushed: Option<Vec<u8>> = match jet {
+                Jet::Left if rock.iter().all(|row| row & 0b1000000 == 0) => {
+                    Some(rock.iter().map(|row| row << 1).collect())
+                }
+                Jet::Right if rock.iter().all(|row| row & 0b0000001 == 0) => {
+                    Some(rock.iter().map(|row| row >> 1).collect())
+                }
+                _ => None,
+            };
+            if let Some(pushed) = pushed.filter(|pushed| !self.collides(pushed, y)) {
+                rock = pushed;
+            }
+
+            if y == 0 || self.collides(&rock, y - 1) {
+                break;
+            }
+            y -= 1;
+        }
+
+        for (idx, row) in rock.iter().enumerate() {
+            match self.rows.get_mut(y + idx) {
+                Some(tower) => *tower |= row,
+                None => self.rows.push(*row),
+            }
+        }
+
+        if let Some(excess) = self.rows.len().checked_sub(Self::DEPTH) {
+            self.rows.drain(..excess);
+            self.trimmed += excess as u64;
+        }
+    }
+}
+
+/// Parse the jet pattern.
+fn parse(input: &str) -> Result<Vec<Jet>> {
+    let jets = input
+        .strip_suffix('\n')
+        .unwrap_or(input)
+        .chars()
+        .map(Jet::try_from)
+        .collect::<Result<Vec<Jet>>>()?;
+
+    if jets.is_empty() {
+        anyhow::bail!("jet pattern must not be empty");
+    }
+
+    Ok(jets)
+}
+
+/// Height of the tower after the given number of rocks have fallen, skipping the repetitions of
+/// the rocks and jets cycle.
+fn height_after(jets: &[Jet], rocks: u64) -> u64 {
+    let next = |chamber: &Chamber| {
+        let mut chamber = chamber.clone();
+        chamber.drop_rock(jets);
+        chamber
+    };
+    let cycle = cycle::brent(Chamber::default(), next);
+
+    // Rock counts beyond usize are cycled anyway.
+    let rocks = usize::try_from(rocks).unwrap_or(usize::MAX);
+    let reduced = cycle.reduce(rocks);
+    let mut heights: Vec<u64> = vec![0];
+    let mut chamber = Chamber::default();
+    while heights.len() <= reduced.max(cycle.start + cycle.len) {
+        chamber.drop_rock(jets);
+        heights.push(chamber.height());
+    }
+
+    let repetitions = ((rocks - reduced) / cycle.len) as u64;
+    heights[reduced] + repetitions * (heights[cycle.start + cycle.len] - heights[cycle.start])
+}
+
+/// Height of the tower after 2022 rocks.
+fn part1(jets: &[Jet]) -> u64 {
+    height_after(jets, ROCKS)
+}
+
+/// Height of the tower after 1000000000000 rocks.
+fn part2(jets: &[Jet]) -> u64 {
+    height_after(jets, MANY_ROCKS)
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 17)?;
+    let jets = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!("Tower height after {ROCKS} rocks: {}", part1(&jets));
+
+    // Part 2
+    println!("Tower height after {MANY_ROCKS} rocks: {}", part2(&jets));
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+    #[test]
+    fn example_test() {
+        let jets = parse(INPUT).unwrap();
+
+        let mut chamber = Chamber::default();
+        for _ in 0..3 {
+            chamber.drop_rock(&jets);
+        }
+        // The third rock lands on top of the second one.
+        assert_eq!(
+            vec![0b0011110, 0b0001000, 0b0011100, 0b1111000, 0b0010000, 0b0010000],
+            chamber.rows
+        );
+        assert_eq!(6, chamber.height());
+
+        assert_eq!(3068, part1(&jets));
+        assert_eq!(1514285714288, part2(&jets));
+    }
+
+    #[test]
+    fn cycle_extrapolation() {
+        // Heights extrapolated from the cycle match the simulation.
+        let jets = parse(INPUT).unwrap();
+        let mut chamber = Chamber::default();
+        for rocks in 1..=500 {
+            chamber.drop_rock(&jets);
+            assert_eq!(chamber.height(), height_after(&jets, rocks));
+        }
+        assert_eq!(0, height_after(&jets, 0));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in ["", "\n", "<>x", "<> ", "<\n>"] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Jets always pushing to the same wall.
+        let jets = parse("<").unwrap();
+        let mut chamber = Chamber::default();
+        for _ in 0..ROCKS {
+            chamber.drop_rock(&jets);
+        }
+        assert_eq!(chamber.height(), part1(&jets));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(17, |input| {
+            let jets = parse(input)?;
+            Ok((part1(&jets), part2(&jets)))
+        });
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::Result;
+use day1::{cli::Args, examples, parse::ParseMode, span::SpanError};
+use std::process::ExitCode;
+
+/// Minutes to crack geodes in part 1.
+const TIME: u32 = 24;
+/// Minutes to crack geodes in part 2.
+const LONGER_TIME: u32 = 32;
+/// Blueprints not eaten by the elephants in part 2.
+const UNEATEN_BLUEPRINTS: usize = 3;
+
+/// Resources (and kinds of robots collecting them).
+const ORE: usize = 0;
+const CLAY: usize = 1;
+const OBSIDIAN: usize = 2;
+const GEODE: usize = 3;
+
+/// Robot factory blueprint.
+#[derive(Debug, PartialEq, Eq)]
+struct Blueprint {
+    id: u32,
+    /// Cost of each kind of robot, in each resource.
+    costs: [[u32; 4]; 4],
+}
+
+impl TryFrom<&str> for Blueprint {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (id, robots) = value
+            .strip_prefix("Blueprint ")
+            .and_then(|s| s.split_once(": "))
+            .ok_or_else(|| anyhow::format_err!("expected 'Blueprint <id>: '"))?;
+        let number = |s: &str| {
+            s.parse::<u32>()
+                .map_err(|_| anyhow::format_err!("invalid number '{s}'"))
+        };
+
+        let mut costs = [[0; 4]; 4];
+        let mut robots = robots
+            .strip_suffix('.')
+            .ok_or_else(|| anyhow::format_err!("expected '.' at the end of the blueprint"))?
+            .split(". ");
+        for (robot, kind) in ["ore", "clay", "obsidian", "geode"].iter().enumerate() {
+            let cost = robots
+                .next()
+                .and_then(|s| s.strip_prefix(&format!("Each {kind} robot costs ")))
+                .ok_or_else(|| anyhow::format_err!("expected the cost of the {kind} robot"))?;
+            for resource in cost.split(" and ") {
+                let (amount, resource) = resource
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow::format_err!("expected '<amount> <resource>'"))?;
+                let resource = match resource {
+                    "ore" => ORE,
+                    "clay" => CLAY,
+                    "obsidian" => OBSIDIAN,
+                    r => anyhow::bail!("robots can't be built out of '{r}'"),
+                };
+                costs[robot][resource] = number(amount)?;
+            }
+        }
+        if robots.next().is_some() {
+            anyhow::bail!("unexpected data after the geode robot cost");
+        }
+
+        Ok(Self {
+            id: number(id)?,
+            costs,
+        })
+    }
+}
+
+/// Resources and robots at a given time.
+#[derive(Debug, Clone, Copy)]
+struct State {
+    /// Minutes left.
+    time: u32,
+    robots: [u32; 4],
+    resources: [u32; 4],
+}
+
+impl Blueprint {
+    /// Largest number of geodes that can be opened in the given time.
+    fn max_geodes(&self, time: u32) -> u32 {
+        // There's no point having more robots collecting a resource than can be spent in a
+        // minute, since only one robot can be built per minute.
+        let mut max_robots = [u32::MAX; 4];
+        for resource in [ORE, CLAY, OBSIDIAN] {
+            max_robots[resource] = self
+                .costs
+                .iter()
+                .map(|cost| cost[resource])
+                .max()
+                .unwrap_or_default();
+        }
+
+        let mut best: u32 = 0;
+        let start = State {
+            time,
+            robots: [1, 0, 0, 0],
+            resources: [0; 4],
+        };
+        self.search(start, &max_robots, &mut best);
+        best
+    }
+
+    /// Branch and bound search on which robot to build next.
+    ///
+    /// # Note
+    /// States aren't memoized: skipping ahead to each robot build, few states are reached twice
+    /// (about one in fifteen on the example blueprints), and hashing every state costs far more
+    /// than searching the repeated ones again.
+    fn search(&self, state: State, max_robots: &[u32; 4], best: &mut u32) {
+        // Geodes opened by the time left with the current robots.
+        let geodes = state.resources[GEODE] + state.robots[GEODE] * state.time;
+        *best = geodes.max(*best);
+
+        // Even building a geode robot every minute left can't beat the best.
+        if geodes + state.time * state.time.saturating_sub(1) / 2 <= *best {
+            return;
+        }
+
+        for robot in [GEODE, OBSIDIAN, CLAY, ORE] {
+            if state.robots[robot] >= max_robots[robot] {
+                continue;
+            }
+
+            // Wait for the resources to build the robot, then build it.
+            let wait = (0..4)
+                .map(|resource| {
+                    let missing =
+                        self.costs[robot][resource].saturating_sub(state.resources[resource]);
+                    match (missing, state.robots[resource]) {
+                        (0, _) => Some(0),
+                        (_, 0) => None,
+                        (missing, robots) => Some(missing.div_ceil(robots)),
+                    }
+                })
+                .try_fold(0, |wait, resource_wait| Some(wait.max(resource_wait?)));
+            // A robot built in the last minute doesn't collect anything.
+            let Some(minutes) = wait.map(|wait| wait + 1).filter(|m| *m < state.time) else {
+                continue;
+            };
+
+            let mut next = state;
+            next.time -= minutes;
+            for resource in 0..4 {
+                next.resources[resource] = state.resources[resource]
+                    + state.robots[resource] * minutes
+                    - self.costs[robot][resource];
+            }
+            next.robots[robot] += 1;
+            self.search(next, max_robots, best);
+        }
+    }
+}
+
+/// Parse the blueprints, one per line.
+fn parse(input: &str) -> Result<Vec<Blueprint>> {
+    input
+        .lines()
+        .map(|line| Blueprint::try_from(line).map_err(|e| SpanError::new(input, line, e).into()))
+        .collect()
+}
+
+/// Sum of the quality levels of the blueprints.
+fn part1(blueprints: &[Blueprint]) -> u32 {
+    blueprints
+        .iter()
+        .map(|blueprint| blueprint.id * blueprint.max_geodes(TIME))
+        .sum()
+}
+
+/// Product of the largest numbers of geodes opened in 32 minutes with the first 3 blueprints.
+fn part2(blueprints: &[Blueprint]) -> u64 {
+    blueprints
+        .iter()
+        .take(UNEATEN_BLUEPRINTS)
+        .map(|blueprint| blueprint.max_geodes(LONGER_TIME) as u64)
+        .product()
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 19)?;
+    let blueprints = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+
+    // Part 1
+    println!(
+        "Sum of the quality levels of the blueprints: {}",
+        part1(&blueprints)
+    );
+
+    // Part 2
+    println!(
+        "Product of the geodes opened with the first {UNEATEN_BLUEPRINTS} blueprints: {}",
+        part2(&blueprints)
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = concat!(
+        "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. ",
+        "Each obsidian robot costs 3 ore and 14 clay. ",
+        "Each geode robot costs 2 ore and 7 obsidian.\n",
+        "Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. ",
+        "Each obsidian robot costs 3 ore and 8 clay. ",
+        "Each geode robot costs 3 ore and 12 obsidian.",
+    );
+
+    #[test]
+    fn example_test() {
+        let blueprints = parse(INPUT).unwrap();
+        assert_eq!(
+            Blueprint {
+                id: 1,
+                costs: [[4, 0, 0, 0], [2, 0, 0, 0], [3, 14, 0, 0], [2, 0, 7, 0]],
+            },
+            blueprints[0]
+        );
+
+        assert_eq!(9, blueprints[0].max_geodes(TIME));
+        assert_eq!(12, blueprints[1].max_geodes(TIME));
+        assert_eq!(33, part1(&blueprints));
+
+        assert_eq!(56, blueprints[0].max_geodes(LONGER_TIME));
+        assert_eq!(62, blueprints[1].max_geodes(LONGER_TIME));
+        assert_eq!(56 * 62, part2(&blueprints));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        let line = INPUT.lines().next().unwrap();
+        for input in [
+            "Blueprint 1",
+            "Blueprint x: Each ore robot costs 4 ore.",
+            &line.replace("clay robot", "sand robot"),
+            &line.replace("14 clay", "14 sand"),
+            &line.replace("14 clay", "-14 clay"),
+            &line.replace("14 clay", "14clay"),
+            &line.replace("obsidian.", "obsidian"),
+            &format!("{line} Each diamond robot costs 1 geode."),
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Blueprints that can't open any geode.
+        let blueprint =
+            Blueprint::try_from(&*line.replace("3 ore and 14 clay", "3 ore and 14 obsidian"))
+                .unwrap();
+        assert_eq!(0, blueprint.max_geodes(TIME));
+        assert_eq!(0, blueprint.max_geodes(0));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(19, |input| {
+            let blueprints = parse(input)?;
+            Ok((part1(&blueprints), part2(&blueprints)))
+        });
+    }
+}
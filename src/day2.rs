@@ -1,65 +1,257 @@
 use anyhow::Result;
+use day1::{
+    cli::Args,
+    examples,
+    parse::ParseMode,
+    read_input,
+    rng::Rng,
+    trace::{TraceValue, Tracer},
+};
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
+    cmp::Ordering,
+    collections::HashSet,
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
     process::ExitCode,
 };
 
+/// Game variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    /// Rock, paper, scissors.
+    Classic,
+    /// Rock, paper, scissors, lizard, Spock, the lizard and Spock being marked by the given
+    /// `(opponent, player)` characters in the strategy guide.
+    Rpsls {
+        lizard: (char, char),
+        spock: (char, char),
+    },
+}
+
+impl Variant {
+    /// Default `(opponent, player)` characters of the lizard.
+    const LIZARD: (char, char) = ('D', 'V');
+    /// Default `(opponent, player)` characters of Spock.
+    const SPOCK: (char, char) = ('E', 'W');
+
+    /// Construct a new instance from its name (`classic` or `rpsls`), with the characters of the
+    /// lizard and Spock given as `<opponent><player>` pairs.
+    fn new(name: &str, lizard: Option<&str>, spock: Option<&str>) -> Result<Self> {
+        let pair = |chars: Option<&str>, default: (char, char)| -> Result<(char, char)> {
+            let Some(chars) = chars else {
+                return Ok(default);
+            };
+            match chars.chars().collect::<Vec<char>>()[..] {
+                [opponent, player] => Ok((opponent, player)),
+                _ => anyhow::bail!("'{chars}' is not a pair of <opponent><player> characters"),
+            }
+        };
+
+        match name {
+            "classic" if lizard.is_none() && spock.is_none() => Ok(Self::Classic),
+            "classic" => anyhow::bail!("lizard and Spock are only played in the rpsls variant"),
+            "rpsls" => {
+                let (lizard, spock) = (pair(lizard, Self::LIZARD)?, pair(spock, Self::SPOCK)?);
+                for column in [
+                    ['A', 'B', 'C', lizard.0, spock.0],
+                    ['X', 'Y', 'Z', lizard.1, spock.1],
+                ] {
+                    if (1..column.len()).any(|idx| column[..idx].contains(&column[idx])) {
+                        anyhow::bail!("characters {column:?} of a column must be distinct");
+                    }
+                }
+
+                Ok(Self::Rpsls { lizard, spock })
+            }
+            v => anyhow::bail!("'{}' is not a valid variant (classic, rpsls)", v),
+        }
+    }
+
+    /// Shapes played in the variant.
+    fn shapes(&self) -> &'static [Shape] {
+        match self {
+            Self::Classic => &Shape::ALL[..3],
+            Self::Rpsls { .. } => &Shape::ALL,
+        }
+    }
+}
+
 #[derive(Debug)]
-/// Turn shapes.
+/// Strategy guide turn: the second column is read as the player's shape in part 1 and as the
+/// desired outcome in part 2.
 struct Turn {
-    player: Shape,
     opponent: Shape,
+    player: Shape,
+    /// Desired outcome (`None` if the second column marks a lizard or Spock).
+    outcome: Option<Outcome>,
 }
 
-impl TryFrom<&str> for Turn {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
+impl Turn {
+    /// Parse a turn of the strategy guide of the given game variant.
+    fn new(value: &str, variant: &Variant) -> Result<Self> {
         let chars: Vec<char> = value.chars().collect();
-        if value.len() != 3 || chars[1] != ' ' {
+        if chars.len() != 3 || chars[1] != ' ' {
             anyhow::bail!("input contains invalid turn format")
         };
 
         Ok(Self {
-            player: Shape::player(chars[2])?,
-            opponent: Shape::opponent(chars[0])?,
+            opponent: Shape::opponent(chars[0], variant)?,
+            player: Shape::player(chars[2], variant)?,
+            outcome: Outcome::try_from(chars[2]).ok(),
         })
     }
 }
 
-impl Turn {
-    fn try_from_part_two(value: &str) -> Result<Self> {
-        let chars: Vec<char> = value.chars().collect();
-        if value.len() != 3 || chars[1] != ' ' {
-            anyhow::bail!("input contains invalid turn format")
+/// Points awarded for the shapes played and the outcomes of the turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Scoring {
+    /// Points of each shape, in the order of [`Shape::ALL`].
+    shapes: [usize; 5],
+    /// Points of a win, a draw and a loss.
+    outcomes: [usize; 3],
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            shapes: Shape::ALL.map(usize::from),
+            outcomes: [Outcome::Win, Outcome::Draw, Outcome::Lose].map(usize::from),
+        }
+    }
+}
+
+impl Scoring {
+    /// Parse the scoring rules from a TOML configuration, overriding the default points:
+    ///
+    /// ```toml
+    /// [shapes]
+    /// rock = 1
+    /// paper = 2
+    /// scissors = 3
+    /// lizard = 4
+    /// spock = 5
+    ///
+    /// [outcomes]
+    /// win = 6
+    /// draw = 3
+    /// lose = 0
+    /// ```
+    ///
+    /// Only the subset of TOML needed is supported: tables of integer values, comments and blank
+    /// lines.
+    fn parse(input: &str) -> Result<Self> {
+        let mut scoring = Self::default();
+        let mut table: Option<&str> = None;
+        let mut keys: HashSet<(&str, &str)> = HashSet::new();
+
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                table = Some(name.trim());
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| anyhow::format_err!("line {}: expected 'key = value'", idx + 1))?;
+            let Some(table) = table else {
+                anyhow::bail!("line {}: key '{key}' is outside of a table", idx + 1);
+            };
+            if !keys.insert((table, key)) {
+                anyhow::bail!("line {}: duplicate key '{key}' in [{table}]", idx + 1);
+            }
+
+            let points = match (table, key) {
+                ("shapes", "rock") => &mut scoring.shapes[0],
+                ("shapes", "paper") => &mut scoring.shapes[1],
+                ("shapes", "scissors") => &mut scoring.shapes[2],
+                ("shapes", "lizard") => &mut scoring.shapes[3],
+                ("shapes", "spock") => &mut scoring.shapes[4],
+                ("outcomes", "win") => &mut scoring.outcomes[0],
+                ("outcomes", "draw") => &mut scoring.outcomes[1],
+                ("outcomes", "lose") => &mut scoring.outcomes[2],
+                _ => anyhow::bail!("line {}: unknown key '{key}' in [{table}]", idx + 1),
+            };
+            *points = value.parse().map_err(|_| {
+                anyhow::format_err!(
+                    "line {}: '{value}' is not a valid number of points",
+                    idx + 1
+                )
+            })?;
+        }
+
+        Ok(scoring)
+    }
+
+    /// Points for playing `player` against `opponent`.
+    fn points(&self, player: Shape, opponent: Shape) -> usize {
+        let outcome = match Outcome::new(player, opponent) {
+            Outcome::Win => 0,
+            Outcome::Draw => 1,
+            Outcome::Lose => 2,
         };
+        let shape = Shape::ALL
+            .iter()
+            .position(|s| *s == player)
+            .unwrap_or_default();
 
-        Ok(Self {
-            player: Shape::player_part_two(chars[2], chars[0])?,
-            opponent: Shape::opponent(chars[0])?,
-        })
+        self.outcomes[outcome] + self.shapes[shape]
     }
 }
 
 /// Player.
 struct Player {
     score: usize,
+    scoring: Scoring,
 }
 
 impl Player {
-    fn new() -> Self {
-        Player { score: 0 }
+    fn new(scoring: Scoring) -> Self {
+        Player { score: 0, scoring }
     }
 
     fn play(&mut self, player: &Shape, opponent: &Shape) {
-        self.score += Into::<usize>::into(Outcome::new(*player, *opponent));
-        self.score += Into::<usize>::into(*player);
+        self.score += self.scoring.points(*player, *opponent);
+    }
+
+    /// Play a turn, recording the shapes, the outcome, the points earned and the running total
+    /// if a [`Tracer`] is given.
+    fn play_traced(
+        &mut self,
+        player: &Shape,
+        opponent: &Shape,
+        event: &str,
+        step: usize,
+        tracer: Option<&mut Tracer>,
+    ) -> Result<()> {
+        self.play(player, opponent);
+        if let Some(tracer) = tracer {
+            tracer.record(
+                event,
+                step,
+                &[
+                    ("player", TraceValue::from(format!("{player:?}"))),
+                    ("opponent", TraceValue::from(format!("{opponent:?}"))),
+                    (
+                        "outcome",
+                        TraceValue::from(format!("{:?}", Outcome::new(*player, *opponent))),
+                    ),
+                    ("points", self.scoring.points(*player, *opponent).into()),
+                    ("total", self.score.into()),
+                ],
+            )?;
+        }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Turn possible outcomes.
 enum Outcome {
     Win,
@@ -69,17 +261,12 @@ enum Outcome {
 
 impl Outcome {
     fn new(player: Shape, opponent: Shape) -> Outcome {
-        match (player, opponent) {
-            (Shape::Rock, Shape::Scissor) => Outcome::Win,
-            (Shape::Rock, Shape::Paper) => Outcome::Lose,
-
-            (Shape::Paper, Shape::Rock) => Outcome::Win,
-            (Shape::Paper, Shape::Scissor) => Outcome::Lose,
-
-            (Shape::Scissor, Shape::Paper) => Outcome::Win,
-            (Shape::Scissor, Shape::Rock) => Outcome::Lose,
-
-            _ => Outcome::Draw,
+        if player.beats(opponent) {
+            Outcome::Win
+        } else if opponent.beats(player) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
         }
     }
 }
@@ -93,7 +280,6 @@ impl From<Outcome> for usize {
             Outcome::Lose => 0,
         }
     }
-    
 }
 
 impl TryFrom<char> for Outcome {
@@ -119,6 +305,8 @@ enum Shape {
     Rock,
     Paper,
     Scissor,
+    Lizard,
+    Spock,
 }
 
 impl From<Shape> for usize {
@@ -128,101 +316,482 @@ impl From<Shape> for usize {
             Shape::Rock => 1,
             Shape::Paper => 2,
             Shape::Scissor => 3,
+            Shape::Lizard => 4,
+            Shape::Spock => 5,
         }
     }
 }
 
 impl Shape {
-    /// Takes the opponent's shape and returns the shape the player needs to play based on the
-    /// desierd outcome.
-    fn from_outcome(opponent: Shape, outcome: Outcome) -> Self {
-        match (outcome, opponent) {
-            (Outcome::Draw, shape) => shape,
+    /// All the shapes, the classic ones first.
+    const ALL: [Shape; 5] = [
+        Self::Rock,
+        Self::Paper,
+        Self::Scissor,
+        Self::Lizard,
+        Self::Spock,
+    ];
 
-            (Outcome::Win, Self::Rock) => Self::Paper,
-            (Outcome::Lose, Self::Rock) => Self::Scissor,
+    /// Whether `self` beats `other`.
+    fn beats(self, other: Shape) -> bool {
+        matches!(
+            (self, other),
+            (Self::Rock, Self::Scissor)
+                | (Self::Rock, Self::Lizard)
+                | (Self::Paper, Self::Rock)
+                | (Self::Paper, Self::Spock)
+                | (Self::Scissor, Self::Paper)
+                | (Self::Scissor, Self::Lizard)
+                | (Self::Lizard, Self::Paper)
+                | (Self::Lizard, Self::Spock)
+                | (Self::Spock, Self::Rock)
+                | (Self::Spock, Self::Scissor)
+        )
+    }
 
-            (Outcome::Win, Shape::Paper) => Self::Scissor,
-            (Outcome::Lose, Shape::Paper) => Self::Rock,
+    /// Takes the opponent's shape and returns the shape the player needs to play based on the
+    /// desierd outcome.
+    ///
+    /// Two shapes win (and lose) against each shape in the rpsls variant: the one worth the
+    /// most points is played.
+    fn from_outcome(opponent: Shape, outcome: Outcome, variant: &Variant) -> Self {
+        variant
+            .shapes()
+            .iter()
+            .copied()
+            .filter(|shape| Outcome::new(*shape, opponent) == outcome)
+            .max_by_key(|shape| usize::from(*shape))
+            .unwrap_or(opponent)
+    }
 
-            (Outcome::Win, Shape::Scissor) => Self::Rock,
-            (Outcome::Lose, Shape::Scissor) => Self::Paper,
-        }
+    fn opponent(o: char, variant: &Variant) -> Result<Self> {
+        Ok(match (o, variant) {
+            ('A', _) => Self::Rock,
+            ('B', _) => Self::Paper,
+            ('C', _) => Self::Scissor,
+            (c, Variant::Rpsls { lizard, .. }) if c == lizard.0 => Self::Lizard,
+            (c, Variant::Rpsls { spock, .. }) if c == spock.0 => Self::Spock,
+            (s, _) => anyhow::bail!("'{}' is not a valid sign for opponent", s),
+        })
     }
 
-    fn opponent(o: char) -> Result<Self> {
-        Ok(match o {
-            'A' => Self::Rock,
-            'B' => Self::Paper,
-            'C' => Self::Scissor,
-            s => anyhow::bail!("'{}' is not a valid sign for opponent", s),
+    fn player(p: char, variant: &Variant) -> Result<Self> {
+        Ok(match (p, variant) {
+            ('X', _) => Self::Rock,
+            ('Y', _) => Self::Paper,
+            ('Z', _) => Self::Scissor,
+            (c, Variant::Rpsls { lizard, .. }) if c == lizard.1 => Self::Lizard,
+            (c, Variant::Rpsls { spock, .. }) if c == spock.1 => Self::Spock,
+            (s, _) => anyhow::bail!("'{}' is not a valid sign for player", s),
         })
     }
 
-    fn player(p: char) -> Result<Self> {
-        Ok(match p {
-            'X' => Self::Rock,
-            'Y' => Self::Paper,
-            'Z' => Self::Scissor,
-            s => anyhow::bail!("'{}' is not a valid sign for player", s),
+    /// Parse shape typed by the player in the interactive game.
+    fn from_name(name: &str) -> Result<Self> {
+        Ok(match name.trim().to_lowercase().as_str() {
+            "r" | "rock" | "x" => Self::Rock,
+            "p" | "paper" | "y" => Self::Paper,
+            "s" | "scissor" | "scissors" | "z" => Self::Scissor,
+            s => anyhow::bail!("'{}' is not a valid shape", s),
         })
     }
+}
 
-    fn player_part_two(p: char, o: char) -> Result<Self> {
-        Ok(Self::from_outcome(
-            Self::opponent(o)?,
-            Outcome::try_from(p)?,
-        ))
+/// Parse the strategy guide of the given game variant.
+fn parse(input: &str, variant: &Variant) -> Result<Vec<Turn>> {
+    input.lines().map(|line| Turn::new(line, variant)).collect()
+}
+
+/// Score following the guide, with the second column being the player's shape.
+fn part1(turns: &[Turn], scoring: Scoring, mut tracer: Option<&mut Tracer>) -> Result<usize> {
+    let mut player = Player::new(scoring);
+    for (idx, turn) in turns.iter().enumerate() {
+        player.play_traced(
+            &turn.player,
+            &turn.opponent,
+            "part1",
+            idx + 1,
+            tracer.as_deref_mut(),
+        )?;
     }
+
+    Ok(player.score)
 }
 
-fn game(lines: &[String]) -> Result<Player> {
-    let turns: Result<Vec<Turn>> = lines
-        .iter()
-        .map(|line| -> Result<Turn> { Turn::try_from(line.as_str()) })
-        .collect();
+/// Score following the guide, with the second column being the desired outcome.
+fn part2(
+    turns: &[Turn],
+    variant: &Variant,
+    scoring: Scoring,
+    mut tracer: Option<&mut Tracer>,
+) -> Result<usize> {
+    let mut player = Player::new(scoring);
+    for (idx, turn) in turns.iter().enumerate() {
+        let outcome = turn
+            .outcome
+            .ok_or_else(|| anyhow::format_err!("{:?} is not a desired outcome", turn.player))?;
+        player.play_traced(
+            &Shape::from_outcome(turn.opponent, outcome, variant),
+            &turn.opponent,
+            "part2",
+            idx + 1,
+            tracer.as_deref_mut(),
+        )?;
+    }
 
-    let mut player = Player::new();
-    turns?
-        .iter()
-        .for_each(|turn| player.play(&turn.player, &turn.opponent));
+    Ok(player.score)
+}
 
-    Ok(player)
+/// Strategy guide analysis, based on the opponent column only.
+#[derive(Debug, PartialEq, Eq)]
+struct Analysis {
+    /// Maximum achievable score, playing the best shape every turn.
+    best: usize,
+    /// Minimum achievable score, playing the worst shape every turn.
+    worst: usize,
+    /// Score always playing for a draw.
+    always_draw: usize,
+    /// Score always playing for a win.
+    always_win: usize,
+}
+
+impl Analysis {
+    /// Analyse the opponent column of the strategy guide.
+    fn new(turns: &[Turn], variant: &Variant, scoring: Scoring) -> Self {
+        let score = |shape: &dyn Fn(Shape) -> Shape| {
+            let mut player = Player::new(scoring);
+            turns
+                .iter()
+                .for_each(|turn| player.play(&shape(turn.opponent), &turn.opponent));
+            player.score
+        };
+
+        Self {
+            best: score(&|opponent| {
+                variant
+                    .shapes()
+                    .iter()
+                    .copied()
+                    .max_by_key(|shape| scoring.points(*shape, opponent))
+                    .unwrap_or(opponent)
+            }),
+            worst: score(&|opponent| {
+                variant
+                    .shapes()
+                    .iter()
+                    .copied()
+                    .min_by_key(|shape| scoring.points(*shape, opponent))
+                    .unwrap_or(opponent)
+            }),
+            always_draw: score(&|opponent| Shape::from_outcome(opponent, Outcome::Draw, variant)),
+            always_win: score(&|opponent| Shape::from_outcome(opponent, Outcome::Win, variant)),
+        }
+    }
+
+    /// Points the given score falls short of the best achievable one.
+    fn shortfall(&self, score: usize) -> usize {
+        self.best.saturating_sub(score)
+    }
 }
 
-fn game_part_two(lines: &[String]) -> Result<Player> {
-    let turns: Result<Vec<Turn>> = lines
+/// Tournament standing of a strategy guide.
+#[derive(Debug, PartialEq, Eq)]
+struct Standing {
+    name: String,
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    /// Total score across all the matches.
+    score: usize,
+}
+
+/// Round-robin tournament between strategy guides, each playing its second column as the
+/// player's shapes (as in part 1): every guide plays a match against each other one, turn by
+/// turn until the shorter guide is over, the match going to the one scoring the most points.
+///
+/// Standings are ranked by wins, then draws, then total score.
+fn tournament(guides: &[(String, Vec<Turn>)], scoring: Scoring) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = guides
         .iter()
-        .map(|line| -> Result<Turn> { Turn::try_from_part_two(line.as_str()) })
+        .map(|(name, _)| Standing {
+            name: name.clone(),
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            score: 0,
+        })
         .collect();
 
-    let mut player = Player::new();
-    turns?
-        .iter()
-        .for_each(|turn| player.play(&turn.player, &turn.opponent));
+    for (i, (_, home)) in guides.iter().enumerate() {
+        for (j, (_, away)) in guides.iter().enumerate().skip(i + 1) {
+            let (mut home_player, mut away_player) = (Player::new(scoring), Player::new(scoring));
+            for (home_turn, away_turn) in home.iter().zip(away) {
+                home_player.play(&home_turn.player, &away_turn.player);
+                away_player.play(&away_turn.player, &home_turn.player);
+            }
 
-    Ok(player)
+            match home_player.score.cmp(&away_player.score) {
+                Ordering::Greater => (standings[i].wins += 1, standings[j].losses += 1),
+                Ordering::Less => (standings[i].losses += 1, standings[j].wins += 1),
+                Ordering::Equal => (standings[i].draws += 1, standings[j].draws += 1),
+            };
+            standings[i].score += home_player.score;
+            standings[j].score += away_player.score;
+        }
+    }
+
+    standings.sort_by(|a, b| {
+        (b.wins, b.draws, b.score)
+            .cmp(&(a.wins, a.draws, a.score))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    standings
 }
 
-fn read_input_lines<P>(path: P) -> Result<Vec<String>, io::Error>
+/// Strategy guide files, expanding directories into the files they contain (sorted by name).
+fn guide_paths(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut guides = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if path.is_dir() {
+            let mut files = fs::read_dir(&path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<PathBuf>>>()?;
+            files.retain(|file| file.is_file());
+            files.sort();
+            guides.extend(files);
+        } else {
+            guides.push(path);
+        }
+    }
+
+    Ok(guides)
+}
+
+/// Opponent in the interactive game.
+enum Opponent {
+    /// Plays the opponent column of the strategy guide, in order.
+    Guide { shapes: Vec<Shape>, round: usize },
+    /// Plays random shapes.
+    Random(Rng),
+    /// Plays the shape beating the one the player has played the most so far.
+    Ai { counts: [usize; 3], rng: Rng },
+}
+
+impl Opponent {
+    /// Construct a new instance of the given kind (`guide`, `random` or `ai`).
+    fn new(kind: &str, turns: &[Turn]) -> Result<Self> {
+        Ok(match kind {
+            "guide" => {
+                let shapes: Vec<Shape> = turns.iter().map(|turn| turn.opponent).collect();
+                if shapes.is_empty() {
+                    anyhow::bail!("strategy guide contains no turns");
+                }
+
+                Self::Guide { shapes, round: 0 }
+            }
+            "random" => Self::Random(Rng::from_time()),
+            "ai" => Self::Ai {
+                counts: [0; 3],
+                rng: Rng::from_time(),
+            },
+            k => anyhow::bail!("'{}' is not a valid opponent (guide, random, ai)", k),
+        })
+    }
+
+    /// Return the opponent's next shape.
+    fn next_shape(&mut self) -> Shape {
+        match self {
+            Self::Guide { shapes, round } => {
+                // Start over once the strategy guide is over.
+                let shape = shapes[*round % shapes.len()];
+                *round += 1;
+                shape
+            }
+            Self::Random(rng) => Shape::ALL[rng.below(3) as usize],
+            Self::Ai { counts, rng } => {
+                let max = counts.iter().max().copied().unwrap_or_default();
+                let favourites: Vec<Shape> = Shape::ALL
+                    .into_iter()
+                    .zip(counts.iter())
+                    .filter(|(_, count)| **count == max)
+                    .map(|(shape, _)| shape)
+                    .collect();
+                let favourite = *rng.choose(&favourites).unwrap_or(&Shape::Rock);
+
+                Shape::from_outcome(favourite, Outcome::Win, &Variant::Classic)
+            }
+        }
+    }
+
+    /// Let the opponent know what the player played.
+    fn observe(&mut self, player: Shape) {
+        if let Self::Ai { counts, .. } = self {
+            counts[Shape::ALL
+                .iter()
+                .position(|s| *s == player)
+                .unwrap_or_default()] += 1;
+        }
+    }
+}
+
+/// Interactive game: read the player's shapes from `input` until EOF or `q`, writing prompts and
+/// results of each turn to `output`.
+fn play<R, W>(mut opponent: Opponent, scoring: Scoring, input: R, mut output: W) -> Result<Player>
 where
-    P: AsRef<Path>,
+    R: BufRead,
+    W: Write,
 {
-    let input_file = File::open(path)?;
-    BufReader::new(input_file).lines().collect()
+    let mut player = Player::new(scoring);
+    let mut lines = input.lines();
+
+    for round in 1.. {
+        write!(
+            output,
+            "Round {round}: your shape ([r]ock, [p]aper, [s]cissors, [q]uit)? "
+        )?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        if line.trim() == "q" {
+            break;
+        }
+
+        let shape = match Shape::from_name(&line) {
+            Ok(shape) => shape,
+            Err(e) => {
+                writeln!(output, "{e}")?;
+                continue;
+            }
+        };
+
+        let opponent_shape = opponent.next_shape();
+        opponent.observe(shape);
+        let before = player.score;
+        player.play(&shape, &opponent_shape);
+        writeln!(
+            output,
+            "{:?} vs {:?}: {:?}, +{} points (total {})",
+            shape,
+            opponent_shape,
+            Outcome::new(shape, opponent_shape),
+            player.score - before,
+            player.score
+        )?;
+    }
+
+    writeln!(output, "\nFinal score: {}", player.score)?;
+
+    Ok(player)
 }
 
 fn run() -> Result<()> {
-    let lines = read_input_lines("./input/day2.dat")?;
+    let args = Args::from_env(
+        &[
+            "--opponent",
+            "--variant",
+            "--lizard",
+            "--spock",
+            "--scoring",
+            Tracer::OPTIONS[0],
+            Tracer::OPTIONS[1],
+            examples::OPTION,
+        ],
+        &[
+            "--play",
+            "--analysis",
+            "--tournament",
+            Tracer::TEXT_FLAG,
+            ParseMode::FLAG,
+        ],
+    )?;
+    let variant = Variant::new(
+        args.value("--variant").unwrap_or("classic"),
+        args.value("--lizard"),
+        args.value("--spock"),
+    )?;
+    let scoring = match args.value("--scoring") {
+        Some(path) => Scoring::parse(&read_input(path)?)?,
+        None => Scoring::default(),
+    };
+    let parse_mode = ParseMode::from_args(&args);
+
+    // Tournament between the strategy guides given as arguments.
+    if args.flag("--tournament") {
+        let guides = guide_paths(args.positional())?
+            .into_iter()
+            .map(|path| {
+                let input = read_input(&path)?;
+                let turns = parse(&parse_mode.normalize_tokens(&input), &variant)
+                    .map_err(|e| anyhow::format_err!("{}: {e}", path.display()))?;
+                Ok((path.display().to_string(), turns))
+            })
+            .collect::<Result<Vec<(String, Vec<Turn>)>>>()?;
+        if guides.len() < 2 {
+            anyhow::bail!("a tournament needs at least two strategy guides");
+        }
+
+        for (rank, standing) in tournament(&guides, scoring).iter().enumerate() {
+            println!(
+                "{}. {}: {}W {}D {}L, {} points",
+                rank + 1,
+                standing.name,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.score
+            );
+        }
+        return Ok(());
+    }
+
+    let mut tracer = Tracer::from_args(&args)?;
+    let input = examples::input(&args, 2)?;
+    let turns = parse(&parse_mode.normalize_tokens(&input), &variant)?;
+
+    // Interactive game.
+    if args.flag("--play") {
+        if variant != Variant::Classic {
+            anyhow::bail!("the interactive game is only played in the classic variant");
+        }
+        let opponent = Opponent::new(args.value("--opponent").unwrap_or("guide"), &turns)?;
+        play(opponent, scoring, io::stdin().lock(), io::stdout())?;
+        return Ok(());
+    }
 
     // Part 1
-    let player = game(&lines)?;
-    println!("Part 1: Player scored {} points", player.score);
+    println!(
+        "Part 1: Player scored {} points",
+        part1(&turns, scoring, tracer.as_mut())?
+    );
 
     // Part 2
-    // let player
-    let player = game_part_two(&lines)?;
-    println!("Part 2: Player scored {} points", player.score);
+    println!(
+        "Part 2: Player scored {} points",
+        part2(&turns, &variant, scoring, tracer.as_mut())?
+    );
+
+    // Flush the trace, reporting any error writing it.
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
+    }
+
+    // Strategy guide analysis.
+    if args.flag("--analysis") {
+        let analysis = Analysis::new(&turns, &variant, scoring);
+        println!("Maximum achievable score: {} points", analysis.best);
+        println!("Minimum achievable score: {} points", analysis.worst);
+        println!("Always draw: {} points", analysis.always_draw);
+        println!("Always win: {} points", analysis.always_win);
+        println!(
+            "Part 1 guide falls short by {} points, part 2 guide by {} points",
+            analysis.shortfall(part1(&turns, scoring, None)?),
+            analysis.shortfall(part2(&turns, &variant, scoring, None)?)
+        );
+    }
 
     Ok(())
 }
@@ -238,7 +807,11 @@ fn main() -> ExitCode {
 
 #[cfg(test)]
 mod test {
-    use super::{game, game_part_two, Outcome, Shape};
+    use super::{
+        parse, part1, part2, play, tournament, Analysis, Opponent, Outcome, Scoring, Shape, Tracer,
+        Variant,
+    };
+    use day1::examples;
 
     #[test]
     fn compare_shapes() {
@@ -261,17 +834,244 @@ mod test {
 
     #[test]
     fn test_example() {
-        let input = ["A Y", "B X", "C Z"];
+        let turns = parse("A Y\nB X\nC Z\n", &Variant::Classic).unwrap();
+        assert_eq!(part1(&turns, Scoring::default(), None).unwrap(), 15);
+        assert_eq!(
+            part2(&turns, &Variant::Classic, Scoring::default(), None).unwrap(),
+            12
+        );
+    }
 
-        let lines: Vec<String> = input
-                .iter()
-                .map(|line| line.to_string())
+    #[test]
+    fn trace() {
+        let turns = parse("A Y\nB X\n", &Variant::Classic).unwrap();
+        let path = std::env::temp_dir().join(format!("day2-trace-{}.jsonl", std::process::id()));
+        let mut tracer = Tracer::create(&path, 1).unwrap();
+        part1(&turns, Scoring::default(), Some(&mut tracer)).unwrap();
+        drop(tracer);
+
+        assert_eq!(
+            concat!(
+                r#"{"event":"part1","step":1,"player":"Paper","opponent":"Rock","outcome":"Win","#,
+                r#""points":8,"total":8}"#,
+                "\n",
+                r#"{"event":"part1","step":2,"player":"Rock","opponent":"Paper","outcome":"Lose","#,
+                r#""points":1,"total":9}"#,
+                "\n",
+            ),
+            std::fs::read_to_string(&path).unwrap()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn analysis() {
+        let turns = parse("A Y\nB X\nC Z\n", &Variant::Classic).unwrap();
+        let analysis = Analysis::new(&turns, &Variant::Classic, Scoring::default());
+        assert_eq!(
+            Analysis {
+                best: 24,
+                worst: 3 + 1 + 2,
+                always_draw: 15,
+                always_win: 24,
+            },
+            analysis
+        );
+        assert_eq!(
+            9,
+            analysis.shortfall(part1(&turns, Scoring::default(), None).unwrap())
+        );
+        assert_eq!(
+            12,
+            analysis.shortfall(part2(&turns, &Variant::Classic, Scoring::default(), None).unwrap())
+        );
+        let analysis = Analysis::new(&[], &Variant::Classic, Scoring::default());
+        assert_eq!((0, 0), (analysis.best, analysis.worst));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for line in ["", "A", "AY", "A  Y", "é ", "D X", "A W", "A Y "] {
+            assert!(parse(&format!("{line}\n"), &Variant::Classic).is_err());
+        }
+    }
+
+    #[test]
+    fn rpsls() {
+        // Each shape beats two shapes and loses against the other two.
+        for shape in Shape::ALL {
+            let outcomes: Vec<Outcome> = Shape::ALL
+                .into_iter()
+                .map(|other| Outcome::new(shape, other))
                 .collect();
+            assert_eq!(2, outcomes.iter().filter(|o| **o == Outcome::Win).count());
+            assert_eq!(2, outcomes.iter().filter(|o| **o == Outcome::Lose).count());
+        }
+        assert_eq!(Outcome::new(Shape::Lizard, Shape::Spock), Outcome::Win);
+        assert_eq!(Outcome::new(Shape::Spock, Shape::Paper), Outcome::Lose);
+
+        // Classic guides score the same in the extended variant.
+        let variant = Variant::new("rpsls", None, None).unwrap();
+        let turns = parse("A Y\nB X\nC Z\n", &variant).unwrap();
+        assert_eq!(15, part1(&turns, Scoring::default(), None).unwrap());
+
+        let turns = parse("D X\nE V\nA W\nC Z\n", &variant).unwrap();
+        // Rock vs lizard, lizard vs Spock, Spock vs rock, scissors vs scissors.
+        assert_eq!(
+            (1 + 6) + (4 + 6) + (5 + 6) + (3 + 3),
+            part1(&turns, Scoring::default(), None).unwrap()
+        );
+        assert!(part2(&turns, &variant, Scoring::default(), None).is_err());
 
-        let player = game(&lines).unwrap();
-        assert_eq!(player.score, 15);
+        // Spock beats scissors and loses against the lizard, worth more than rock and paper.
+        let turns = parse("C Z\nD X\n", &variant).unwrap();
+        assert_eq!(
+            (5 + 6) + 5,
+            part2(&turns, &variant, Scoring::default(), None).unwrap()
+        );
 
-        let player = game_part_two(&lines).unwrap();
-        assert_eq!(player.score, 12);
+        let variant = Variant::new("rpsls", Some("LK"), Some("SP")).unwrap();
+        assert_eq!(Shape::Spock, parse("S K", &variant).unwrap()[0].opponent);
+        assert!(parse("D V", &variant).is_err());
+        assert!(parse("D V", &Variant::Classic).is_err());
+
+        for (name, lizard, spock) in [
+            ("rps", None, None),
+            ("classic", Some("DV"), None),
+            ("rpsls", Some("D"), None),
+            ("rpsls", Some("AV"), None),
+            ("rpsls", Some("DV"), Some("EV")),
+        ] {
+            assert!(Variant::new(name, lizard, spock).is_err());
+        }
+    }
+
+    #[test]
+    fn scoring_config() {
+        assert_eq!(
+            Ok(Scoring::default()),
+            Scoring::parse("").map_err(|e| e.to_string())
+        );
+
+        // Draws worth more than wins, and every shape worth the same.
+        let scoring = Scoring::parse(concat!(
+            "# Alternative rules\n",
+            "[shapes]\n",
+            "rock = 0\n",
+            "paper = 0 # no points\n",
+            "scissors = 0\n",
+            "\n",
+            "[ outcomes ]\n",
+            "win = 1\n",
+            "draw = 2\n",
+        ))
+        .unwrap();
+        assert_eq!(0, scoring.points(Shape::Rock, Shape::Paper));
+        assert_eq!(2, scoring.points(Shape::Paper, Shape::Paper));
+        assert_eq!(4 + 1, scoring.points(Shape::Lizard, Shape::Spock));
+
+        let turns = parse("A Y\nB X\nC Z\n", &Variant::Classic).unwrap();
+        assert_eq!(3, part1(&turns, scoring, None).unwrap());
+        assert_eq!(
+            2 + 2 + 2,
+            Analysis::new(&turns, &Variant::Classic, scoring).best
+        );
+
+        for (input, line) in [
+            ("rock = 1", 1),
+            ("[shapes]\nrock 1", 2),
+            ("[shapes]\nrock = -1", 2),
+            ("[shapes]\nrock = one", 2),
+            ("[shapes]\nrock = 1\nrock = 2", 3),
+            ("[shapes]\nwin = 6", 2),
+            ("[outcome]\nwin = 6", 2),
+        ] {
+            let error = Scoring::parse(input).unwrap_err().to_string();
+            assert!(
+                error.starts_with(&format!("line {line}: ")),
+                "{input:?}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_robin_tournament() {
+        // Always rock, always paper, always scissors, and rock for a single turn.
+        let guides: Vec<(String, Vec<super::Turn>)> = [
+            ("rock", "A X\nB X"),
+            ("paper", "A Y\nC Y"),
+            ("scissors", "B Z\nB Z"),
+            ("short", "C X"),
+        ]
+        .into_iter()
+        .map(|(name, input)| (name.to_string(), parse(input, &Variant::Classic).unwrap()))
+        .collect();
+
+        let standings = tournament(&guides, Scoring::default());
+        let standings: Vec<(&str, usize, usize, usize, usize)> = standings
+            .iter()
+            .map(|s| (s.name.as_str(), s.wins, s.draws, s.losses, s.score))
+            .collect();
+        assert_eq!(
+            vec![
+                ("paper", 2, 0, 1, 16 + 4 + 8),
+                ("rock", 1, 1, 1, 2 + 14 + 4),
+                ("short", 1, 1, 1, 4 + 1 + 7),
+                ("scissors", 1, 0, 2, 18 + 6 + 3),
+            ],
+            standings
+        );
+
+        assert!(tournament(&[], Scoring::default()).is_empty());
+    }
+
+    #[test]
+    fn interactive_game() {
+        let turns = parse("A Y\nB X\nC Z\n", &Variant::Classic).unwrap();
+
+        // Same game as the example, plus an invalid shape.
+        let opponent = Opponent::new("guide", &turns).unwrap();
+        let mut output: Vec<u8> = vec![];
+        let player = play(
+            opponent,
+            Scoring::default(),
+            "paper\nlizard\nr\nS\nq\nrock\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(15, player.score);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Paper vs Rock: Win, +8 points (total 8)"));
+        assert!(output.contains("'lizard' is not a valid shape"));
+        assert!(output.ends_with("Final score: 15\n"));
+
+        // AI opponent beats a player always playing the same shape, once it learnt it.
+        let opponent = Opponent::new("ai", &turns).unwrap();
+        let mut output: Vec<u8> = vec![];
+        play(
+            opponent,
+            Scoring::default(),
+            "r\nr\nr\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+        // First round is a random guess.
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.matches("Rock vs Paper: Lose").count() >= 2);
+
+        assert!(Opponent::new("cheater", &turns).is_err());
+        assert!(Opponent::new("guide", &[]).is_err());
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(2, |input| {
+            let turns = parse(input, &Variant::Classic)?;
+            Ok((
+                part1(&turns, Scoring::default(), None)?,
+                part2(&turns, &Variant::Classic, Scoring::default(), None)?,
+            ))
+        });
     }
 }
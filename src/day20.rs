@@ -0,0 +1,168 @@
+use anyhow::Result;
+use day1::{cli::Args, examples, parse::ParseMode, span::SpanError};
+use std::process::ExitCode;
+
+/// Key the numbers are multiplied by in part 2.
+const DECRYPTION_KEY: i64 = 811_589_153;
+/// Mixing rounds in part 2.
+const ROUNDS: usize = 10;
+/// Positions after the `0` summed into the grove coordinates.
+const GROVE_OFFSETS: [usize; 3] = [1000, 2000, 3000];
+/// Bound of the absolute value of the numbers, keeping the decrypted grove coordinates from
+/// overflowing.
+const NUMBER_BOUND: i64 = 1 << 31;
+
+/// Parse the encrypted file, one number per line.
+fn parse(input: &str) -> Result<Vec<i64>> {
+    let numbers = input
+        .lines()
+        .map(|line| {
+            line.parse::<i64>()
+                .map_err(|_| anyhow::format_err!("invalid number '{line}'"))
+                .and_then(|n| match n.unsigned_abs() <= NUMBER_BOUND as u64 {
+                    true => Ok(n),
+                    false => anyhow::bail!("numbers must be within ±{NUMBER_BOUND}"),
+                })
+                .map_err(|e| SpanError::new(input, line, e).into())
+        })
+        .collect::<Result<Vec<i64>>>()?;
+
+    match numbers.iter().filter(|n| **n == 0).count() {
+        1 => Ok(numbers),
+        zeros => anyhow::bail!("expected exactly one 0 in the file, found {zeros}"),
+    }
+}
+
+/// Mix the numbers the given number of rounds: each number, in their original order, moves
+/// forward (or backward if negative) around the circular list by its value.
+fn mix(numbers: &[i64], rounds: usize) -> Vec<i64> {
+    // Numbers may be duplicated, so the list is made of indices into the original ones.
+    let mut order: Vec<usize> = (0..numbers.len()).collect();
+    // Moving a number around the rest of the list brings it back where it was.
+    let others = numbers.len() as i64 - 1;
+    if others <= 0 {
+        return numbers.to_vec();
+    }
+
+    for _ in 0..rounds {
+        for (idx, value) in numbers.iter().enumerate() {
+            let position = order.iter().position(|i| *i == idx).unwrap();
+            order.remove(position);
+            let position = (position as i64 + value).rem_euclid(others);
+            order.insert(position as usize, idx);
+        }
+    }
+
+    order.into_iter().map(|idx| numbers[idx]).collect()
+}
+
+/// Sum of the numbers 1000, 2000 and 3000 positions after the `0`.
+fn grove_coordinates(mixed: &[i64]) -> i64 {
+    let zero = mixed.iter().position(|n| *n == 0).unwrap();
+    GROVE_OFFSETS
+        .iter()
+        .map(|offset| mixed[(zero + offset) % mixed.len()])
+        .sum()
+}
+
+/// Grove coordinates after mixing the numbers once.
+fn part1(numbers: &[i64]) -> i64 {
+    grove_coordinates(&mix(numbers, 1))
+}
+
+/// Grove coordinates after decrypting the numbers and mixing them 10 times.
+fn part2(numbers: &[i64]) -> i64 {
+    let decrypted: Vec<i64> = numbers.iter().map(|n| n * DECRYPTION_KEY).collect();
+    grove_coordinates(&mix(&decrypted, ROUNDS))
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 20)?;
+    let numbers = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+
+    // Part 1
+    println!("Sum of the grove coordinates: {}", part1(&numbers));
+
+    // Part 2
+    println!(
+        "Sum of the decrypted grove coordinates: {}",
+        part2(&numbers)
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "1\n2\n-3\n3\n-2\n0\n4";
+
+    /// Circular list rotated to start from the `0`.
+    fn from_zero(mixed: &[i64]) -> Vec<i64> {
+        let zero = mixed.iter().position(|n| *n == 0).unwrap();
+        [&mixed[zero..], &mixed[..zero]].concat()
+    }
+
+    #[test]
+    fn example_test() {
+        let numbers = parse(INPUT).unwrap();
+        assert_eq!(vec![1, 2, -3, 3, -2, 0, 4], numbers);
+
+        assert_eq!(vec![0, 3, -2, 1, 2, -3, 4], from_zero(&mix(&numbers, 1)));
+        assert_eq!(3, part1(&numbers));
+        assert_eq!(1623178306, part2(&numbers));
+    }
+
+    #[test]
+    fn duplicates() {
+        // Each copy of a number moves on its own.
+        let numbers = parse("2\n2\n0\n-1\n2").unwrap();
+        assert_eq!(vec![0, 2, 2, -1, 2], from_zero(&mix(&numbers, 1)));
+
+        // Moving a number by a multiple of the list length minus one leaves it in place.
+        let numbers = parse("4\n0\n1").unwrap();
+        assert_eq!(vec![0, 4, 1], from_zero(&mix(&numbers, 1)));
+
+        let numbers = parse("0").unwrap();
+        assert_eq!(0, part1(&numbers));
+        assert_eq!(0, part2(&numbers));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "1\n2",
+            "0\n1\n0",
+            "1\n0\nx",
+            "1 \n0",
+            "0\n2147483649",
+            "0\n-9223372036854775808",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        let numbers = parse("0\n2147483648\n-2147483648").unwrap();
+        assert_eq!(0, part1(&numbers));
+        assert_eq!(0, part2(&numbers));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(20, |input| {
+            let numbers = parse(input)?;
+            Ok((part1(&numbers), part2(&numbers)))
+        });
+    }
+}
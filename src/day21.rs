@@ -0,0 +1,369 @@
+use anyhow::Result;
+use day1::{cli::Args, examples, parse::ParseMode, span::SpanError};
+use std::{
+    collections::{HashMap, HashSet},
+    process::ExitCode,
+};
+
+/// Monkey whose number is asked for.
+const ROOT: &str = "root";
+/// You, yelling a number instead of a monkey.
+const HUMAN: &str = "humn";
+
+/// Math operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl TryFrom<&str> for Operation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "+" => Self::Add,
+            "-" => Self::Sub,
+            "*" => Self::Mul,
+            "/" => Self::Div,
+            s => anyhow::bail!("invalid operation '{s}'"),
+        })
+    }
+}
+
+impl Operation {
+    /// Apply the operation, failing on overflow and division by zero.
+    fn apply(&self, lhs: i64, rhs: i64) -> Result<i64> {
+        let result = match self {
+            Self::Add => lhs.checked_add(rhs),
+            Self::Sub => lhs.checked_sub(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
+            Self::Div if rhs == 0 => anyhow::bail!("division by zero ({lhs} / 0)"),
+            Self::Div => lhs.checked_div(rhs),
+        };
+
+        result.ok_or_else(|| anyhow::format_err!("overflow applying {self:?} to {lhs} and {rhs}"))
+    }
+}
+
+/// Job of a monkey.
+#[derive(Debug, PartialEq, Eq)]
+enum Job<'a> {
+    /// Yell a number.
+    Number(i64),
+    /// Yell the result of the operation on the numbers of two other monkeys.
+    Operation(&'a str, Operation, &'a str),
+}
+
+/// Monkey with its job.
+#[derive(Debug, PartialEq, Eq)]
+struct Monkey<'a> {
+    name: &'a str,
+    job: Job<'a>,
+}
+
+impl<'a> TryFrom<&'a str> for Monkey<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let valid_name = |name: &'a str| match !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase())
+        {
+            true => Ok(name),
+            false => anyhow::bail!("invalid monkey name '{name}'"),
+        };
+
+        let (name, job) = value
+            .split_once(": ")
+            .ok_or_else(|| anyhow::format_err!("expected '<name>: <job>'"))?;
+        let job = match job.split(' ').collect::<Vec<&str>>()[..] {
+            [number] => Job::Number(
+                number
+                    .parse()
+                    .map_err(|_| anyhow::format_err!("invalid number '{number}'"))?,
+            ),
+            [lhs, operation, rhs] => Job::Operation(
+                valid_name(lhs)?,
+                Operation::try_from(operation)?,
+                valid_name(rhs)?,
+            ),
+            _ => anyhow::bail!("expected either '<number>' or '<name> <operation> <name>'"),
+        };
+
+        Ok(Self {
+            name: valid_name(name)?,
+            job,
+        })
+    }
+}
+
+/// Expression tree of the number yelled by a monkey.
+#[derive(Debug, PartialEq, Eq)]
+enum Expr {
+    Number(i64),
+    /// Number yelled by you (the one the monkeys expect in part 1).
+    Human(i64),
+    Operation(Box<Expr>, Operation, Box<Expr>),
+}
+
+impl Expr {
+    /// Build the expression of the number yelled by the monkey with the given name.
+    fn new<'a>(
+        name: &'a str,
+        jobs: &HashMap<&'a str, &Job<'a>>,
+        visited: &mut HashSet<&'a str>,
+    ) -> Result<Self> {
+        // Each monkey is listened to by a single other one, making the jobs a tree.
+        if !visited.insert(name) {
+            anyhow::bail!("monkey '{name}' is listened to more than once");
+        }
+
+        Ok(
+            match jobs
+                .get(name)
+                .ok_or_else(|| anyhow::format_err!("no such monkey '{name}'"))?
+            {
+                Job::Number(number) if name == HUMAN => Self::Human(*number),
+                Job::Number(number) => Self::Number(*number),
+                Job::Operation(lhs, operation, rhs) => Self::Operation(
+                    Box::new(Self::new(lhs, jobs, visited)?),
+                    *operation,
+                    Box::new(Self::new(rhs, jobs, visited)?),
+                ),
+            },
+        )
+    }
+
+    /// Evaluate the expression.
+    ///
+    /// # Note
+    /// Each monkey is listened to by a single other one (see [`Expr::new`]), so no
+    /// sub-expression is shared and there's nothing to memoize.
+    fn evaluate(&self) -> Result<i64> {
+        match self {
+            Self::Number(number) | Self::Human(number) => Ok(*number),
+            Self::Operation(lhs, operation, rhs) => {
+                operation.apply(lhs.evaluate()?, rhs.evaluate()?)
+            }
+        }
+    }
+
+    /// Whether the expression depends on the number you yell.
+    fn has_human(&self) -> bool {
+        match self {
+            Self::Number(_) => false,
+            Self::Human(_) => true,
+            Self::Operation(lhs, _, rhs) => lhs.has_human() || rhs.has_human(),
+        }
+    }
+
+    /// Number you have to yell for the expression to evaluate to `target`, inverting the
+    /// operations from the top of the tree down to you.
+    fn solve(&self, mut target: i64) -> Result<i64> {
+        let inexact = || anyhow::format_err!("no integer number to yell");
+        let mut expr = self;
+
+        loop {
+            let (lhs, operation, rhs) = match expr {
+                Self::Human(_) => return Ok(target),
+                Self::Number(_) => anyhow::bail!("the number you yell doesn't matter"),
+                Self::Operation(lhs, operation, rhs) => (lhs, operation, rhs),
+            };
+
+            expr = match (lhs.has_human(), rhs.has_human()) {
+                (true, true) => anyhow::bail!("the number you yell is needed more than once"),
+                (false, false) => anyhow::bail!("the number you yell doesn't matter"),
+                // Solve `x op rhs = target`.
+                (true, false) => {
+                    let rhs = rhs.evaluate()?;
+                    target = match operation {
+                        Operation::Add => Operation::Sub.apply(target, rhs)?,
+                        Operation::Sub => Operation::Add.apply(target, rhs)?,
+                        Operation::Mul if target.checked_rem(rhs) == Some(0) => target / rhs,
+                        Operation::Mul => return Err(inexact()),
+                        // Integer division truncates: the smallest solution is picked.
+                        Operation::Div if rhs != 0 => Operation::Mul.apply(target, rhs)?,
+                        Operation::Div => anyhow::bail!("division by zero"),
+                    };
+                    lhs
+                }
+                // Solve `lhs op x = target`.
+                (false, true) => {
+                    let lhs = lhs.evaluate()?;
+                    target = match operation {
+                        Operation::Add => Operation::Sub.apply(target, lhs)?,
+                        Operation::Sub => Operation::Sub.apply(lhs, target)?,
+                        Operation::Mul if target.checked_rem(lhs) == Some(0) => target / lhs,
+                        Operation::Mul => return Err(inexact()),
+                        Operation::Div if lhs.checked_rem(target) == Some(0) => lhs / target,
+                        Operation::Div => return Err(inexact()),
+                    };
+                    rhs
+                }
+            };
+        }
+    }
+}
+
+/// Parse the monkey jobs, one per line, into the expression of the number yelled by the root
+/// monkey.
+fn parse(input: &str) -> Result<Expr> {
+    let monkeys = input
+        .lines()
+        .map(|line| Monkey::try_from(line).map_err(|e| SpanError::new(input, line, e).into()))
+        .collect::<Result<Vec<Monkey>>>()?;
+
+    let mut jobs: HashMap<&str, &Job> = HashMap::with_capacity(monkeys.len());
+    for monkey in &monkeys {
+        if jobs.insert(monkey.name, &monkey.job).is_some() {
+            anyhow::bail!("duplicate monkey '{}'", monkey.name);
+        }
+    }
+
+    Expr::new(ROOT, &jobs, &mut HashSet::new())
+}
+
+/// Number yelled by the root monkey.
+fn part1(root: &Expr) -> Result<i64> {
+    root.evaluate()
+}
+
+/// Number to yell for the root monkey to find its two numbers equal.
+fn part2(root: &Expr) -> Result<i64> {
+    match root {
+        Expr::Operation(lhs, _, rhs) if rhs.has_human() => rhs.solve(lhs.evaluate()?),
+        Expr::Operation(lhs, _, rhs) => lhs.solve(rhs.evaluate()?),
+        _ => anyhow::bail!("monkey '{ROOT}' must compare two numbers"),
+    }
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 21)?;
+    let root = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!("Number yelled by the root monkey: {}", part1(&root)?);
+
+    // Part 2
+    println!(
+        "Number to yell to pass the equality test: {}",
+        part2(&root)?
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32"#;
+
+    #[test]
+    fn example_test() {
+        let root = parse(INPUT).unwrap();
+        assert_eq!(
+            Monkey {
+                name: "ptdq",
+                job: Job::Operation("humn", Operation::Sub, "dvpt"),
+            },
+            Monkey::try_from("ptdq: humn - dvpt").unwrap()
+        );
+        assert!(root.has_human());
+
+        assert_eq!(152, part1(&root).unwrap());
+        assert_eq!(301, part2(&root).unwrap());
+    }
+
+    #[test]
+    fn inverse_operations() {
+        // The solution satisfies the equality with you on either side of each operation.
+        for operation in ["+", "-", "*", "/"] {
+            for (lhs, rhs) in [("humn", "aaaa"), ("aaaa", "humn")] {
+                let input = format!(
+                    "root: bbbb + cccc\nbbbb: {lhs} {operation} {rhs}\naaaa: 6\ncccc: -6\nhumn: 1"
+                );
+                let root = parse(&input).unwrap();
+                let human = part2(&root).unwrap();
+
+                let input = input.replace("humn: 1", &format!("humn: {human}"));
+                let Expr::Operation(lhs, _, rhs) = parse(&input).unwrap() else {
+                    unreachable!()
+                };
+                assert_eq!(lhs.evaluate().unwrap(), rhs.evaluate().unwrap(), "{input}");
+            }
+        }
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "root 5",
+            "root: 5 5",
+            "root: a ^ b\na: 1\nb: 1",
+            "root: a + B\na: 1\nB: 1",
+            "root: a + b\na: 1",
+            "root: a + b\na: 1\nb: 1\na: 2",
+            "root: a + a\na: 1",
+            "root: a + b\na: root - b\nb: 1",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        for input in [
+            "root: a / b\na: 1\nb: 0",
+            "root: a * b\na: 9223372036854775807\nb: 2",
+        ] {
+            let root = parse(input);
+            assert!(root.is_err() || part1(&root.unwrap()).is_err(), "{input:?}");
+        }
+
+        for input in [
+            "root: 5",
+            "root: a + b\na: 1\nb: 2",
+            "root: a + b\na: humn * humn\nb: 4\nhumn: 1",
+            "root: a + b\na: humn * c\nb: 5\nc: 2\nhumn: 1",
+            "root: a + b\na: humn * c\nb: 5\nc: 0\nhumn: 1",
+            "root: a + b\na: c / humn\nb: 0\nc: 5\nhumn: 1",
+            "root: a + b\na: humn * c\nb: -9223372036854775808\nc: -1\nhumn: 1",
+        ] {
+            let root = parse(input);
+            assert!(root.is_err() || part2(&root.unwrap()).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(21, |input| {
+            let root = parse(input)?;
+            Ok((part1(&root)?, part2(&root)?))
+        });
+    }
+}
@@ -0,0 +1,498 @@
+use anyhow::Result;
+use day1::{
+    cli::Args, direction::Direction, examples, geometry::Vec3, grid::Grid, parse::ParseMode,
+};
+use std::{collections::HashMap, process::ExitCode};
+
+/// Tile of the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    /// Off the board.
+    Void,
+    Open,
+    Wall,
+}
+
+impl TryFrom<char> for Tile {
+    type Error = anyhow::Error;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            ' ' => Self::Void,
+            '.' => Self::Open,
+            '#' => Self::Wall,
+            c => anyhow::bail!("invalid tile '{c}'"),
+        })
+    }
+}
+
+/// Step of the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Forward(usize),
+    Left,
+    Right,
+}
+
+/// Position on the board, with the direction faced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    row: usize,
+    col: usize,
+    facing: Direction,
+}
+
+impl Position {
+    /// Final password for the position.
+    fn password(&self) -> usize {
+        let facing = match self.facing {
+            Direction::Right => 0,
+            Direction::Down => 1,
+            Direction::Left => 2,
+            Direction::Up => 3,
+        };
+
+        1000 * (self.row + 1) + 4 * (self.col + 1) + facing
+    }
+}
+
+/// Board and path notes.
+#[derive(Debug)]
+struct Notes {
+    board: Grid<Tile>,
+    path: Vec<Step>,
+    /// Leftmost open tile of the top row, facing right.
+    start: Position,
+}
+
+impl Notes {
+    /// Position one step ahead, or `None` if it's off the board.
+    fn ahead(&self, position: Position) -> Option<Position> {
+        let (dx, dy) = position.facing.unit_vector();
+        let row = position.row.checked_add_signed(dy)?;
+        let col = position.col.checked_add_signed(dx)?;
+
+        matches!(self.board.get(row, col), Some(Tile::Open | Tile::Wall)).then_some(Position {
+            row,
+            col,
+            ..position
+        })
+    }
+
+    /// Position one step ahead, wrapping around the board by walking back to its opposite edge.
+    fn flat_wrap(&self, position: Position) -> Position {
+        let mut back = Position {
+            facing: position.facing.opposite(),
+            ..position
+        };
+        while let Some(next) = self.ahead(back) {
+            back = next;
+        }
+
+        Position {
+            facing: position.facing,
+            ..back
+        }
+    }
+
+    /// Follow the path from the start, wrapping around the edges of the board with `wrap`, and
+    /// return the final position.
+    fn walk<F>(&self, wrap: F) -> Position
+    where
+        F: Fn(Position) -> Position,
+    {
+        let mut position = self.start;
+
+        for step in &self.path {
+            match step {
+                Step::Left => position.facing = position.facing.turn_left(),
+                Step::Right => position.facing = position.facing.turn_right(),
+                Step::Forward(steps) => {
+                    let (from, mut steps) = (position, *steps);
+                    let mut taken: usize = 0;
+                    while taken < steps {
+                        let next = self.ahead(position).unwrap_or_else(|| wrap(position));
+                        if self.board.get(next.row, next.col) == Some(&Tile::Wall) {
+                            break;
+                        }
+                        position = next;
+                        taken += 1;
+
+                        // Back where the step started: skip the full loops around the board.
+                        if position == from {
+                            steps %= taken;
+                            taken = 0;
+                        }
+                    }
+                }
+            }
+        }
+
+        position
+    }
+}
+
+/// Face of the cube, with the orientation it's folded in.
+#[derive(Debug)]
+struct Face {
+    /// Block of the board the face is on.
+    block: (usize, usize),
+    /// Outward unit vector.
+    normal: Vec3,
+    /// Unit vector pointing to the right on the board.
+    right: Vec3,
+    /// Unit vector pointing down on the board.
+    down: Vec3,
+}
+
+impl Face {
+    /// Unit vector of the given direction on the board.
+    fn vector(&self, direction: Direction) -> Vec3 {
+        let (dx, dy) = direction.unit_vector();
+        self.right * dx as i64 + self.down * dy as i64
+    }
+}
+
+/// Board folded into a cube.
+#[derive(Debug)]
+struct Cube {
+    /// Side of the faces.
+    size: usize,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    /// Fold the board into a cube, whatever the layout of its net.
+    fn new(board: &Grid<Tile>) -> Result<Self> {
+        let tiles = (0..board.rows())
+            .flat_map(|row| (0..board.cols()).map(move |col| (row, col)))
+            .filter(|(row, col)| board.get(*row, *col) != Some(&Tile::Void))
+            .count();
+        let size = ((tiles / 6) as f64).sqrt() as usize;
+        if size == 0 || 6 * size * size != tiles {
+            anyhow::bail!("the board isn't made of 6 square faces");
+        }
+
+        // Blocks of the board fully made of tiles.
+        let blocks: Vec<(usize, usize)> = (0..board.rows() / size)
+            .flat_map(|i| (0..board.cols() / size).map(move |j| (i, j)))
+            .filter(|(i, j)| {
+                (i * size..(i + 1) * size).all(|row| {
+                    (j * size..(j + 1) * size).all(|col| board.get(row, col) != Some(&Tile::Void))
+                })
+            })
+            .collect();
+        if blocks.len() != 6 {
+            anyhow::bail!("the board isn't made of 6 square faces");
+        }
+
+        // Fold the faces around the first one, walking the net: crossing an edge in a
+        // direction, the next face points that way, and going on turns into going down the
+        // normal of the previous face.
+        let mut folded: HashMap<(usize, usize), Face> = HashMap::with_capacity(6);
+        let mut stack = vec![Face {
+            block: blocks[0],
+            normal: Vec3::new(0, 0, -1),
+            right: Vec3::new(1, 0, 0),
+            down: Vec3::new(0, 1, 0),
+        }];
+        while let Some(face) = stack.pop() {
+            if folded.contains_key(&face.block) {
+                continue;
+            }
+            for direction in Direction::ALL {
+                let (dx, dy) = direction.unit_vector();
+                let Some(block) = face
+                    .block
+                    .0
+                    .checked_add_signed(dy)
+                    .zip(face.block.1.checked_add_signed(dx))
+                    .filter(|block| blocks.contains(block) && !folded.contains_key(block))
+                else {
+                    continue;
+                };
+                stack.push(Face {
+                    block,
+                    normal: face.vector(direction),
+                    right: match dx {
+                        0 => face.right,
+                        dx => -face.normal * dx as i64,
+                    },
+                    down: match dy {
+                        0 => face.down,
+                        dy => -face.normal * dy as i64,
+                    },
+                });
+            }
+            folded.insert(face.block, face);
+        }
+
+        let faces: Vec<Face> = folded.into_values().collect();
+        let all_sides = Vec3::UNITS
+            .iter()
+            .all(|normal| faces.iter().any(|face| face.normal == *normal));
+        if faces.len() != 6 || !all_sides {
+            anyhow::bail!("the board doesn't fold into a cube");
+        }
+
+        Ok(Self { size, faces })
+    }
+
+    /// Position one step ahead, over the edge of the face onto the adjacent face of the cube.
+    fn wrap(&self, position: Position) -> Position {
+        let block = (position.row / self.size, position.col / self.size);
+        let face = self.faces.iter().find(|face| face.block == block).unwrap();
+
+        // Center of the tile in 3D, with the cube spanning `-size..=size` on each axis.
+        let size = self.size as i64;
+        let center = face.normal * size
+            + face.right * (2 * (position.col % self.size) as i64 + 1 - size)
+            + face.down * (2 * (position.row % self.size) as i64 + 1 - size);
+
+        // The next face is the one ahead, going on down the current one.
+        let ahead = face.vector(position.facing);
+        let next = self.faces.iter().find(|face| face.normal == ahead).unwrap();
+        let center = center + ahead - face.normal;
+        let local = |axis: &Vec3| (center.dot(axis) + size - 1) as usize / 2;
+
+        Position {
+            row: next.block.0 * self.size + local(&next.down),
+            col: next.block.1 * self.size + local(&next.right),
+            facing: Direction::ALL
+                .into_iter()
+                .find(|direction| next.vector(*direction) == -face.normal)
+                .unwrap(),
+        }
+    }
+}
+
+/// Parse the path, alternating numbers of tiles to move forward and turns.
+fn parse_path(path: &str) -> Result<Vec<Step>> {
+    let mut steps: Vec<Step> = vec![];
+    let mut rest = path;
+
+    while let Some(c) = rest.chars().next() {
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (step, len) = match c {
+            'L' => (Step::Left, 1),
+            'R' => (Step::Right, 1),
+            _ if len > 0 => (
+                Step::Forward(
+                    rest[..len]
+                        .parse()
+                        .map_err(|_| anyhow::format_err!("invalid step '{}'", &rest[..len]))?,
+                ),
+                len,
+            ),
+            c => anyhow::bail!("invalid step '{c}'"),
+        };
+        steps.push(step);
+        rest = &rest[len..];
+    }
+
+    if steps.is_empty() {
+        anyhow::bail!("path must not be empty");
+    }
+
+    Ok(steps)
+}
+
+/// Parse the board map and the path, separated by a blank line.
+fn parse(input: &str) -> Result<Notes> {
+    let (board, path) = input.split_once("\n\n").ok_or_else(|| {
+        anyhow::format_err!("expected a blank line between the board and the path")
+    })?;
+
+    // Rows are padded with void tiles to the width of the board.
+    let width = board.lines().map(|line| line.chars().count()).max();
+    let board = board
+        .lines()
+        .map(|line| format!("{line:width$}", width = width.unwrap_or_default()))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let board = Grid::parse(&board, Tile::try_from)?;
+
+    let start = (0..board.cols())
+        .find(|col| board.get(0, *col) == Some(&Tile::Open))
+        .ok_or_else(|| anyhow::format_err!("no open tile on the top row of the board"))?;
+
+    Ok(Notes {
+        board,
+        path: parse_path(path.strip_suffix('\n').unwrap_or(path))?,
+        start: Position {
+            row: 0,
+            col: start,
+            facing: Direction::Right,
+        },
+    })
+}
+
+/// Final password, wrapping around the flat board.
+fn part1(notes: &Notes) -> usize {
+    notes.walk(|position| notes.flat_wrap(position)).password()
+}
+
+/// Final password, wrapping around the board folded into a cube.
+fn part2(notes: &Notes) -> Result<usize> {
+    let cube = Cube::new(&notes.board)?;
+    Ok(notes.walk(|position| cube.wrap(position)).password())
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 22)?;
+    let notes = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!("Final password on the board: {}", part1(&notes));
+
+    // Part 2
+    println!("Final password on the cube: {}", part2(&notes)?);
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5"#;
+
+    #[test]
+    fn example_test() {
+        let notes = parse(INPUT).unwrap();
+        assert_eq!((12, 16), (notes.board.rows(), notes.board.cols()));
+        assert_eq!(8, notes.start.col);
+        assert_eq!(
+            vec![Step::Forward(10), Step::Right, Step::Forward(5)],
+            notes.path[..3]
+        );
+
+        assert_eq!(6032, part1(&notes));
+        assert_eq!(5031, part2(&notes).unwrap());
+    }
+
+    #[test]
+    fn cube_nets() {
+        // Nets of open tiles: walking around the cube leads back to the start.
+        for net in [
+            [" #  ", "### ", " #  ", " #  "].as_slice(),
+            &[" ##", " # ", "## ", "#  "],
+            &["#   ", "####", "   #"],
+            &["##  ", " ###", "  # "],
+            &["  # ", "### ", "  ##"],
+            &["##  ", " ###", "   #"],
+            &["##  ", " ## ", "  ##"],
+        ] {
+            for size in [1, 3] {
+                let board: Vec<String> = net
+                    .iter()
+                    .flat_map(|row| {
+                        let row: String = row
+                            .chars()
+                            .flat_map(|c| {
+                                std::iter::repeat_n(if c == '#' { '.' } else { ' ' }, size)
+                            })
+                            .collect();
+                        std::iter::repeat_n(row, size)
+                    })
+                    .collect();
+                let notes = parse(&format!("{}\n\n1", board.join("\n"))).unwrap();
+                let cube = Cube::new(&notes.board).unwrap();
+
+                for row in 0..notes.board.rows() {
+                    for col in 0..notes.board.cols() {
+                        if notes.board.get(row, col) != Some(&Tile::Open) {
+                            continue;
+                        }
+                        for facing in Direction::ALL {
+                            let start = Position { row, col, facing };
+                            let step = |position: Position| {
+                                notes.ahead(position).unwrap_or_else(|| cube.wrap(position))
+                            };
+
+                            let mut position = start;
+                            for _ in 0..4 * size {
+                                position = step(position);
+                            }
+                            assert_eq!(start, position, "{net:?}");
+
+                            // Stepping back undoes the step.
+                            let mut back = step(start);
+                            back.facing = back.facing.opposite();
+                            let mut back = step(back);
+                            back.facing = back.facing.opposite();
+                            assert_eq!(start, back, "{net:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "...\n",
+            "...\n\n",
+            "...\n\n10X",
+            "...\n\n10 L",
+            "..x\n\n10",
+            "#\n\n10",
+            "...\n\n99999999999999999999999",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Boards that don't fold into a cube.
+        for board in [
+            "......",
+            "..\n..\n..",
+            "...\n...",
+            "...\n.\n..",
+            ".....\n .",
+        ] {
+            let notes = parse(&format!("{board}\n\n1")).unwrap();
+            assert!(part2(&notes).is_err(), "{board:?}");
+        }
+
+        // Walls everywhere but the start, and a long walk around the board.
+        let notes = parse(".#\n##\n\n5R5").unwrap();
+        assert_eq!(1000 + 4 + 1, part1(&notes));
+        let notes = parse(".....\n\n1000000000000000003").unwrap();
+        assert_eq!(1000 + 4 * 4, part1(&notes));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(22, |input| {
+            let notes = parse(input)?;
+            Ok((part1(&notes), part2(&notes)?))
+        });
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::Result;
+use day1::{
+    cli::Args,
+    direction::{Direction, Direction8},
+    examples,
+    grid::Grid,
+    parse::ParseMode,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    process::ExitCode,
+};
+
+/// Rounds simulated in part 1.
+const ROUNDS: usize = 10;
+
+/// Position of an elf.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Position {
+    x: isize,
+    y: isize,
+}
+
+impl Position {
+    /// Adjacent position in the given direction.
+    fn step(&self, direction: Direction8) -> Self {
+        let (dx, dy) = direction.unit_vector();
+        Self {
+            x: self.x + dx,
+            y: self.y + dy,
+        }
+    }
+}
+
+/// Elves spreading out over the grove.
+#[derive(Debug, Clone)]
+struct Grove {
+    /// Sparse set of the elves positions: the grove grows in every direction.
+    elves: HashSet<Position>,
+    /// Index of the first direction considered in the next round.
+    first: usize,
+}
+
+impl Grove {
+    /// Directions considered, in order, in the first round.
+    const DIRECTIONS: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Play a round, returning whether any elf moved.
+    fn round(&mut self) -> bool {
+        // Destination proposed by each elf (`None` if more than one elf proposed it).
+        let mut proposals: HashMap<Position, Option<Position>> = HashMap::new();
+
+        for elf in &self.elves {
+            let occupied = |direction: Direction8| self.elves.contains(&elf.step(direction));
+            if !Direction8::ALL.into_iter().any(occupied) {
+                continue;
+            }
+
+            let proposal = (0..Self::DIRECTIONS.len())
+                .map(|idx| Direction8::from(Self::DIRECTIONS[(self.first + idx) % 4]))
+                .find(|direction| {
+                    [direction.turn_left(), *direction, direction.turn_right()]
+                        .into_iter()
+                        .all(|direction| !occupied(direction))
+                });
+            if let Some(direction) = proposal {
+                match proposals.entry(elf.step(direction)) {
+                    Entry::Occupied(mut entry) => *entry.get_mut() = None,
+                    Entry::Vacant(entry) => {
+                        entry.insert(Some(*elf));
+                    }
+                }
+            }
+        }
+        self.first = (self.first + 1) % Self::DIRECTIONS.len();
+
+        let mut moved = false;
+        for (destination, elf) in proposals {
+            if let Some(elf) = elf {
+                self.elves.remove(&elf);
+                self.elves.insert(destination);
+                moved = true;
+            }
+        }
+
+        moved
+    }
+
+    /// Empty ground tiles in the smallest rectangle containing all the elves.
+    fn empty_tiles(&self) -> usize {
+        let (xs, ys) = (
+            self.elves.iter().map(|elf| elf.x),
+            self.elves.iter().map(|elf| elf.y),
+        );
+        let (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) =
+            (xs.clone().min(), xs.max(), ys.clone().min(), ys.max())
+        else {
+            return 0;
+        };
+
+        (max_x - min_x + 1) as usize * (max_y - min_y + 1) as usize - self.elves.len()
+    }
+}
+
+/// Parse the scan of the elves.
+fn parse(input: &str) -> Result<Grove> {
+    let scan = Grid::parse(input, |c| match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        c => anyhow::bail!("invalid tile '{c}'"),
+    })?;
+
+    let elves = scan
+        .iter_rows()
+        .zip(0..)
+        .flat_map(|(row, y)| {
+            row.iter()
+                .zip(0..)
+                .filter(|(elf, _)| **elf)
+                .map(move |(_, x)| Position { x, y })
+        })
+        .collect();
+
+    Ok(Grove { elves, first: 0 })
+}
+
+/// Empty ground tiles after 10 rounds.
+fn part1(grove: &Grove) -> usize {
+    let mut grove = grove.clone();
+    for _ in 0..ROUNDS {
+        grove.round();
+    }
+
+    grove.empty_tiles()
+}
+
+/// First round where no elf moves.
+fn part2(grove: &Grove) -> usize {
+    let mut grove = grove.clone();
+    (1..).find(|_| !grove.round()).unwrap_or_default()
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 23)?;
+    let grove = parse(&ParseMode::from_args(&args).normalize(&input))?;
+
+    // Part 1
+    println!(
+        "Empty ground tiles after {ROUNDS} rounds: {}",
+        part1(&grove)
+    );
+
+    // Part 2
+    println!("First round where no elf moves: {}", part2(&grove));
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#.."#;
+
+    #[test]
+    fn example_test() {
+        let grove = parse(INPUT).unwrap();
+        assert_eq!(22, grove.elves.len());
+
+        assert_eq!(110, part1(&grove));
+        assert_eq!(20, part2(&grove));
+    }
+
+    #[test]
+    fn small_example() {
+        let mut grove = parse(".....\n..##.\n..#..\n.....\n..##.\n.....").unwrap();
+        let positions = |grove: &Grove| {
+            let mut elves: Vec<(isize, isize)> =
+                grove.elves.iter().map(|elf| (elf.x, elf.y)).collect();
+            elves.sort_unstable();
+            elves
+        };
+
+        // The two elves proposing to move to (2, 3) stay put.
+        assert!(grove.round());
+        assert_eq!(
+            vec![(2, 0), (2, 2), (2, 4), (3, 0), (3, 3)],
+            positions(&grove)
+        );
+
+        assert!(grove.round());
+        assert!(grove.round());
+        assert_eq!(
+            vec![(0, 2), (2, 0), (2, 5), (4, 1), (4, 3)],
+            positions(&grove)
+        );
+        assert!(!grove.round());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in ["#.\n#", "#.x", "# "] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // No elves, and a lonely elf: nobody moves.
+        for input in ["", "...", "..#"] {
+            let grove = parse(input).unwrap();
+            assert_eq!(0, part1(&grove));
+            assert_eq!(1, part2(&grove));
+        }
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(23, |input| {
+            let grove = parse(input)?;
+            Ok((part1(&grove), part2(&grove)))
+        });
+    }
+}
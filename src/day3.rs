@@ -1,148 +1,241 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
 use anyhow::{Ok, Result};
-use day1::read_input_lines;
-use std::process::ExitCode;
+use day1::{cli::Args, examples, parse::ParseMode, priority::PriorityTable};
+use std::{
+    ops::{BitAnd, BitOr},
+    process::ExitCode,
+};
+
+/// Elves in each group, by default.
+const GROUP_SIZE: usize = 3;
 
 #[derive(Debug)]
 struct Group(Vec<Rucksack>);
 
 impl Group {
-    fn new(rucksacks: &[Rucksack]) -> Result<Self> {
-        if rucksacks.len() != 3 {
-            anyhow::bail!("group is not formed by 3 elves");
+    /// Construct a new instance, checking the group is formed by `size` elves.
+    fn new(rucksacks: &[Rucksack], size: usize) -> Result<Self> {
+        if size == 0 {
+            anyhow::bail!("group must be formed by at least one elf");
+        }
+        if rucksacks.len() != size {
+            anyhow::bail!("group is not formed by {size} elves");
         }
 
         Ok(Self(rucksacks.to_vec()))
     }
 
-    fn badge(&self) -> Result<Item> {
-        let rucksack_items: Vec<Vec<Item>> =
-            self.0.iter().map(|rucksack| rucksack.items()).collect();
+    /// Find the item carried by all the elves of the group and return its priority.
+    fn badge(&self) -> Result<usize> {
+        self.0
+            .iter()
+            .fold(ItemSet::ALL, |shared, rucksack| shared & rucksack.items())
+            .lowest()
+            .ok_or_else(|| anyhow::format_err!("badge not found"))
+    }
+}
+
+/// Set of items, one bit per priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct ItemSet(u128);
+
+impl ItemSet {
+    /// Set of all the items.
+    const ALL: Self = Self(u128::MAX);
 
-        for item in &rucksack_items[0] {
-            if rucksack_items[1].contains(item) && rucksack_items[2].contains(item) {
-                return Ok(*item);
-            }
+    /// Set with the item of the given priority added.
+    fn with(self, priority: usize) -> Result<Self> {
+        if priority >= u128::BITS as usize {
+            anyhow::bail!("item priority {priority} exceeds {}", u128::BITS - 1);
         }
 
-        anyhow::bail!("badge not found");
+        Ok(Self(self.0 | 1 << priority))
+    }
+
+    /// Priority of the item of the set with the lowest one (`None` if the set is empty).
+    fn lowest(self) -> Option<usize> {
+        (self.0 != 0).then(|| self.0.trailing_zeros() as usize)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Item(char);
-
-impl Item {
-    fn new(c: char) -> Item {
-        Self(c)
-    }
-
-    fn priority(&self) -> Result<usize> {
-        let priority: usize = match self.0.to_lowercase().collect::<Vec<char>>()[0] {
-            'a' => 1,
-            'b' => 2,
-            'c' => 3,
-            'd' => 4,
-            'e' => 5,
-            'f' => 6,
-            'g' => 7,
-            'h' => 8,
-            'i' => 9,
-            'j' => 10,
-            'k' => 11,
-            'l' => 12,
-            'm' => 13,
-            'n' => 14,
-            'o' => 15,
-            'p' => 16,
-            'q' => 17,
-            'r' => 18,
-            's' => 19,
-            't' => 20,
-            'u' => 21,
-            'v' => 22,
-            'w' => 23,
-            'x' => 24,
-            'y' => 25,
-            'z' => 26,
-            _ => anyhow::bail!("rucksack contains unexpected item"),
-        };
-
-        Ok(match self.0.is_uppercase() {
-            true => priority + 26,
-            false => priority,
-        })
+impl BitAnd for ItemSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
     }
 }
 
 #[derive(Debug, Clone)]
-struct Rucksack(Vec<Item>, Vec<Item>);
+struct Rucksack {
+    /// Items of each compartment.
+    compartments: Vec<ItemSet>,
+}
 
 impl Rucksack {
-    fn new(items: &str) -> Result<Self> {
+    /// Construct a new instance, splitting items into `k` compartments of equal size, each item
+    /// being scored according to `table`.
+    fn new(items: &str, k: usize, table: &PriorityTable) -> Result<Self> {
+        let items = items
+            .chars()
+            .map(|item| {
+                table.priority(item).ok_or_else(|| {
+                    anyhow::format_err!("rucksack contains unexpected item {item:?}")
+                })
+            })
+            .collect::<Result<Vec<usize>>>()?;
         let item_count = items.len();
 
-        if item_count % 2 != 0 {
-            anyhow::bail!("number of items in a rucksack must be even");
+        if k == 0 {
+            anyhow::bail!("rucksack must have at least one compartment");
+        }
+        if !item_count.is_multiple_of(k) {
+            anyhow::bail!("number of items in a rucksack ({item_count}) must be a multiple of {k}");
         }
 
-        let items: Vec<char> = items.chars().collect();
-
-        Ok(Rucksack(
-            items[..item_count / 2]
-                .iter()
-                .map(|c| Item::new(*c))
-                .collect(),
-            items[item_count / 2..]
-                .iter()
-                .map(|c| Item::new(*c))
-                .collect(),
-        ))
+        Ok(Rucksack {
+            compartments: match item_count {
+                0 => vec![ItemSet::default(); k],
+                _ => items
+                    .chunks(item_count / k)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .try_fold(ItemSet::default(), |set, priority| set.with(*priority))
+                    })
+                    .collect::<Result<Vec<ItemSet>>>()?,
+            },
+        })
     }
 
-    /// Find shared item in the two compartments and return its priority.
-    /// If Rucksack compartments have no items, return Err.
+    /// Find the item shared by all the compartments and return its priority (the lowest one if
+    /// more than one item is shared).
     fn find_shared_item(&self) -> Result<usize> {
-        for item in &self.0 {
-            if self.1.contains(item) {
-                return item.priority();
-            }
-        }
+        self.compartments
+            .iter()
+            .fold(ItemSet::ALL, |shared, compartment| shared & *compartment)
+            .lowest()
+            .ok_or_else(|| anyhow::format_err!("no item shared by all the compartments"))
+    }
+
+    fn items(&self) -> ItemSet {
+        self.compartments
+            .iter()
+            .fold(ItemSet::default(), |items, compartment| {
+                items | *compartment
+            })
+    }
+}
+
+/// Parse the rucksacks, one per line, each made of `k` compartments, scoring the items according
+/// to `table`.
+fn parse(input: &str, k: usize, table: &PriorityTable) -> Result<Vec<Rucksack>> {
+    input
+        .lines()
+        .map(|line| Rucksack::new(line, k, table))
+        .collect()
+}
+
+/// Sum of the priorities of the items shared by all the compartments of each rucksack.
+fn part1(rucksacks: &[Rucksack]) -> Result<usize> {
+    rucksacks
+        .iter()
+        .map(|rucksack| rucksack.find_shared_item())
+        .sum()
+}
 
-        anyhow::bail!("rucksack is empty")
+/// Sum of the priorities of the badges of each group of `size` elves.
+fn part2(rucksacks: &[Rucksack], size: usize) -> Result<usize> {
+    if size == 0 {
+        anyhow::bail!("group must be formed by at least one elf");
     }
+    if !rucksacks.len().is_multiple_of(size) {
+        anyhow::bail!(
+            "number of rucksacks ({}) must be a multiple of the group size ({size})",
+            rucksacks.len()
+        );
+    }
+
+    rucksacks
+        .chunks(size)
+        .map(|group| Group::new(group, size)?.badge())
+        .sum()
+}
+
+/// Detail of the item shared by the compartments of each rucksack and of the badge of each group
+/// of `size` elves, reporting errors in place.
+fn detail(rucksacks: &[Rucksack], size: usize, table: &PriorityTable) -> String {
+    let describe = |priority: Result<usize>| {
+        priority.map_or_else(
+            |e| e.to_string(),
+            |priority| {
+                let item = table.item(priority).unwrap_or('?');
+                format!("'{item}' (priority {priority})")
+            },
+        )
+    };
 
-    fn items(&self) -> Vec<Item> {
-        [self.0.clone(), self.1.clone()].concat()
+    let mut detail = String::new();
+    for (idx, rucksack) in rucksacks.iter().enumerate() {
+        detail.push_str(&format!(
+            "Rucksack {}: {}\n",
+            idx + 1,
+            describe(rucksack.find_shared_item())
+        ));
     }
+    for (idx, group) in rucksacks.chunks(size.max(1)).enumerate() {
+        detail.push_str(&format!(
+            "Group {}: {}\n",
+            idx + 1,
+            describe(Group::new(group, size).and_then(|group| group.badge()))
+        ));
+    }
+
+    detail
 }
 
 fn run() -> Result<()> {
-    let rucksacks: Result<Vec<Rucksack>> = read_input_lines("./input/day3.dat")?
-        .iter()
-        .map(|line| -> Result<Rucksack> { Rucksack::new(line) })
-        .collect();
+    let args = Args::from_env(
+        &[
+            "--compartments",
+            "--group-size",
+            "--priorities",
+            examples::OPTION,
+        ],
+        &["--detail", ParseMode::FLAG],
+    )?;
+    let table = match args.value("--priorities") {
+        Some(items) => PriorityTable::new(items)?,
+        None => PriorityTable::default(),
+    };
+    let input = examples::input(&args, 3)?;
+    let rucksacks = parse(
+        &ParseMode::from_args(&args).normalize_tokens(&input),
+        args.parse_value("--compartments")?.unwrap_or(2),
+        &table,
+    )?;
 
-    let rucksacks = rucksacks?;
+    let size = args.parse_value("--group-size")?.unwrap_or(GROUP_SIZE);
+
+    // Per rucksack and group detail.
+    if args.flag("--detail") {
+        print!("{}", detail(&rucksacks, size, &table));
+    }
 
     // Part 1
-    let priorities: Result<Vec<usize>> = rucksacks
-        .iter()
-        .map(|rucksack| rucksack.find_shared_item())
-        .collect();
-    println!(
-        "Total priorities are: {}",
-        priorities?.iter().sum::<usize>()
-    );
+    println!("Total priorities are: {}", part1(&rucksacks)?);
 
     // Part 2
-    let badges: Result<Vec<usize>> = rucksacks
-        .chunks(3)
-        .map(|group| -> Result<usize> { Group::new(group)?.badge()?.priority() })
-        .collect();
-    println!(
-        "Total badge priorities are: {}",
-        badges?.iter().sum::<usize>()
-    );
+    println!("Total badge priorities are: {}", part2(&rucksacks, size)?);
 
     Ok(())
 }
@@ -158,37 +251,163 @@ fn main() -> ExitCode {
 
 #[cfg(test)]
 mod test {
-    use super::{Group, Rucksack};
-    use anyhow::Result;
+    use super::{detail, parse, part1, part2, Group, PriorityTable, Rucksack, GROUP_SIZE};
+    use day1::examples;
 
     #[test]
     fn example_test_day3() {
-        let lines = [
-            "vJrwpWtwJgWrhcsFMMfFFhFp",
-            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
-            "PmmdzqPrVvPwwTWBwg",
-            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
-            "ttgJtRGJQctTZtZT",
-            "CrZsJsPPZsGzwwsLwLmpwMDw",
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+        let rucksacks = parse(input, 2, &PriorityTable::default()).unwrap();
+        assert_eq!(157, part1(&rucksacks).unwrap());
+        assert_eq!(70, part2(&rucksacks, GROUP_SIZE).unwrap());
+    }
+
+    #[test]
+    fn group_size() {
+        let rucksacks = parse("abad\nbcae\nfafg\nhaih", 2, &PriorityTable::default()).unwrap();
+        // 'a' is in every rucksack, 'h' only in the last one.
+        assert_eq!(1 + 1, part2(&rucksacks, 2).unwrap());
+        assert_eq!(1, part2(&rucksacks, 4).unwrap());
+        // Every rucksack is its own group: the badge is its lowest priority item.
+        assert_eq!(1 + 1 + 1 + 1, part2(&rucksacks, 1).unwrap());
+
+        let error = part2(&rucksacks, 3).unwrap_err();
+        assert_eq!(
+            "number of rucksacks (4) must be a multiple of the group size (3)",
+            error.to_string()
+        );
+        assert!(part2(&rucksacks, 0).is_err());
+        assert_eq!(0, part2(&[], GROUP_SIZE).unwrap());
+    }
+
+    #[test]
+    fn detail_output() {
+        let rucksacks = parse(
+            "vJrwpWtwJgWrhcsFMMfFFhFp\nabcd\nPmmdzqPrVvPwwTWBwg\nrr",
+            2,
+            &PriorityTable::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            concat!(
+                "Rucksack 1: 'p' (priority 16)\n",
+                "Rucksack 2: no item shared by all the compartments\n",
+                "Rucksack 3: 'P' (priority 42)\n",
+                "Rucksack 4: 'r' (priority 18)\n",
+                "Group 1: badge not found\n",
+                "Group 2: group is not formed by 3 elves\n",
+            ),
+            detail(&rucksacks, GROUP_SIZE, &PriorityTable::default())
+        );
+        assert_eq!("", detail(&[], GROUP_SIZE, &PriorityTable::default()));
+    }
+
+    #[test]
+    fn custom_priorities() {
+        let table = PriorityTable::new("abcdefghijklmnopqrstuvwxyz0123456789").unwrap();
+        let rucksacks = parse("a1b1\n1c1d\n1f2f", 2, &table).unwrap();
+        assert_eq!(28 + 28 + 6, part1(&rucksacks).unwrap());
+        assert_eq!(28, part2(&rucksacks, GROUP_SIZE).unwrap());
+        assert!(
+            detail(&rucksacks, GROUP_SIZE, &table).starts_with("Rucksack 1: '1' (priority 28)\n")
+        );
+
+        // Uppercase items have no priority.
+        assert!(parse("AA", 2, &table).is_err());
+
+        // Every printable ASCII character, well over 63 items.
+        let items: String = (b' '..=b'~').map(char::from).collect();
+        let table = PriorityTable::new(&items).unwrap();
+        assert_eq!(95, table.len());
+        let rucksacks = parse("~a~b\n}~c~\n ~d~", 2, &table).unwrap();
+        assert_eq!(95 * 3, part1(&rucksacks).unwrap());
+        assert_eq!(95, part2(&rucksacks, GROUP_SIZE).unwrap());
+        assert!(
+            detail(&rucksacks, GROUP_SIZE, &table).starts_with("Rucksack 1: '~' (priority 95)\n")
+        );
+    }
+
+    #[test]
+    fn k_compartments() {
+        // 'a' is the only item in all three compartments.
+        let rucksack = Rucksack::new("abcadcaec", 3, &PriorityTable::default()).unwrap();
+        assert_eq!(3, rucksack.compartments.len());
+        assert_eq!(1, rucksack.find_shared_item().unwrap());
+        // 'c' is in two compartments out of three.
+        assert!(Rucksack::new("cbxcdyefz", 3, &PriorityTable::default())
+            .unwrap()
+            .find_shared_item()
+            .is_err());
+        // A single compartment shares all its items with itself.
+        assert_eq!(
+            1,
+            Rucksack::new("ba", 1, &PriorityTable::default())
+                .unwrap()
+                .find_shared_item()
+                .unwrap()
+        );
+
+        let error = parse("abcd\nabcde", 2, &PriorityTable::default()).unwrap_err();
+        assert_eq!(
+            "number of items in a rucksack (5) must be a multiple of 2",
+            error.to_string()
+        );
+        assert!(Rucksack::new("abcd", 3, &PriorityTable::default()).is_err());
+        assert!(Rucksack::new("abcd", 0, &PriorityTable::default()).is_err());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        // Odd item count, non-ASCII items and digits (rejected while parsing), no shared item.
+        assert!(Rucksack::new("abc", 2, &PriorityTable::default()).is_err());
+        for items in ["éa", "ééaé", "1212", "abcd", ""] {
+            let shared = Rucksack::new(items, 2, &PriorityTable::default())
+                .and_then(|rucksack| rucksack.find_shared_item());
+            assert!(shared.is_err(), "{items:?}");
+        }
+        assert!(Rucksack::new("éa", 2, &PriorityTable::default()).is_err());
+
+        // Incomplete group and group without badge.
+        let rucksacks = [
+            Rucksack::new("aa", 2, &PriorityTable::default()).unwrap(),
+            Rucksack::new("bb", 2, &PriorityTable::default()).unwrap(),
+        ];
+        assert!(Group::new(&rucksacks, GROUP_SIZE).is_err());
+        let rucksacks = [
+            Rucksack::new("aa", 2, &PriorityTable::default()).unwrap(),
+            Rucksack::new("bb", 2, &PriorityTable::default()).unwrap(),
+            Rucksack::new("cc", 2, &PriorityTable::default()).unwrap(),
         ];
+        assert!(Group::new(&rucksacks, GROUP_SIZE).unwrap().badge().is_err());
+    }
 
-        let rucksacks: Result<Vec<Rucksack>> = lines
-            .iter()
-            .map(|line| -> Result<Rucksack> { Rucksack::new(line) })
-            .collect();
+    #[test]
+    fn registered_examples() {
+        examples::check(3, |input| {
+            let rucksacks = parse(input, 2, &PriorityTable::default())?;
+            Ok((part1(&rucksacks)?, part2(&rucksacks, GROUP_SIZE)?))
+        });
+    }
+}
 
-        let rucksacks = rucksacks.unwrap();
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
 
-        let priorities: Result<Vec<usize>> = rucksacks
-            .iter()
-            .map(|rucksack| rucksack.find_shared_item())
-            .collect();
-        assert_eq!(157, priorities.unwrap().iter().sum::<usize>());
-
-        let badges: Result<Vec<usize>> = rucksacks
-            .chunks(3)
-            .map(|group| -> Result<usize> { Group::new(group)?.badge()?.priority() })
-            .collect();
-        assert_eq!(70, badges.unwrap().iter().sum::<usize>());
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_rucksacks(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day3.dat")).unwrap();
+        b.iter(|| parse(&input, 2, &PriorityTable::default()).unwrap());
     }
 }
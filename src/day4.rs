@@ -1,12 +1,18 @@
-#![feature(is_sorted)]
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
 
 use anyhow::Result;
-use day1::read_input_lines;
-use std::process::ExitCode;
+use day1::{
+    cli::Args,
+    combinatorics::Combinatorics,
+    examples,
+    interval::{self, Interval},
+    parse::ParseMode,
+};
+use std::{fmt::Display, process::ExitCode};
 
 #[derive(Debug)]
-/// Pair of elves and their respective [`Range`]s.
-struct Pair(Range, Range);
+/// Pair of elves and their respective ranges of section IDs.
+struct Pair(Interval, Interval);
 
 impl TryFrom<&str> for Pair {
     type Error = anyhow::Error;
@@ -16,7 +22,7 @@ impl TryFrom<&str> for Pair {
             "unable to find two elves in this group"
         ))?;
 
-        Ok(Self(Range::new(pair.0)?, Range::new(pair.1)?))
+        Ok(Self(parse_range(pair.0)?, parse_range(pair.1)?))
     }
 }
 
@@ -24,77 +30,258 @@ impl Pair {
     /// Determine whether the range of one of the two elves in the [`Pair`] are fully contained
     /// in the range of the other elf.
     fn fully_contained(&self) -> bool {
-        let check_contained = |first: &Range, second: &Range| -> bool {
-            first.min <= second.min && second.max <= first.max
-        };
-
-        // Check if one is contained in the other or viceversa.
-        check_contained(&self.0, &self.1) || check_contained(&self.1, &self.0)
+        self.0.contains(&self.1) || self.1.contains(&self.0)
     }
 
-    /// Determine whether the two elves in the [`Group`] have overlapping range.
+    /// Determine whether the two elves in the [`Pair`] have overlapping range.
     fn overlap(&self) -> bool {
-        let check_overlap = |first: &Range, second: &Range| -> bool {
-            first.min <= second.min && second.min <= first.max
-        };
+        self.0.intersects(&self.1)
+    }
+}
 
-        // Check if one's minimum is contained in the other's range or viceversa.
-        check_overlap(&self.0, &self.1) || check_overlap(&self.1, &self.0)
+/// Parse a range of section IDs, expressed as `a-b`.
+fn parse_range(range: &str) -> Result<Interval> {
+    let bounds: Result<Vec<i64>> = range
+        .splitn(2, '-')
+        .map(|bound| -> Result<i64> {
+            bound
+                .parse::<u32>()
+                .map(i64::from)
+                .map_err(|_| anyhow::format_err!("invalid range format"))
+        })
+        .collect();
+
+    match bounds?[..] {
+        [min, max] => Interval::new(min, max).ok_or_else(|| {
+            anyhow::format_err!(
+                "range of IDs for each elf must be expressed as `a-b`, where a <= b"
+            )
+        }),
+        _ => anyhow::bail!("range of IDs for each elf must be expressed as `a-b`"),
     }
 }
 
-#[derive(Debug)]
-/// Range of sections IDs.
-struct Range {
-    min: usize,
-    max: usize,
-}
-
-impl Range {
-    /// Construct a new instance.
-    fn new(range: &str) -> Result<Self> {
-        let bounds: Result<Vec<usize>> = range
-            .splitn(2, '-')
-            .map(|bound| -> Result<usize> {
-                bound
-                    .parse::<usize>()
-                    .map_err(|_| anyhow::format_err!("invalid range format"))
-            })
+/// Kind of range adjustment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Adjustment {
+    /// Drop sections from one end of the range.
+    Shrink,
+    /// Move the whole range, keeping its length.
+    Shift,
+}
+
+/// Range adjustment removing the overlap of a [`Pair`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reassignment {
+    /// Index of the pair (from 1).
+    pair: usize,
+    /// Elf of the pair whose range is adjusted (1 or 2).
+    elf: usize,
+    kind: Adjustment,
+    /// Adjusted range.
+    range: Interval,
+    /// Number of sections dropped (shrink) or shifted by (shift).
+    cost: u64,
+}
+
+impl Pair {
+    /// Cheapest adjustment of one of the two ranges removing their overlap, or `None` if they
+    /// don't overlap. Ties are broken preferring shrinking over shifting, and the first elf
+    /// over the second.
+    fn cheapest_reassignment(&self, pair: usize) -> Option<Reassignment> {
+        if !self.overlap() {
+            return None;
+        }
+
+        let mut candidates: Vec<Reassignment> = vec![];
+        for (elf, (range, other)) in [(self.0, self.1), (self.1, self.0)].into_iter().enumerate() {
+            // Section IDs start from 0.
+            let mut candidate = |kind, min: i64, max: i64, cost| {
+                if let Some(range) = Interval::new(min, max).filter(|range| range.min >= 0) {
+                    candidates.push(Reassignment {
+                        pair,
+                        elf: elf + 1,
+                        kind,
+                        range,
+                        cost,
+                    });
+                }
+            };
+
+            // Drop the sections from the other range's min onwards, or up to its max.
+            let dropped = |kept: Option<Interval>| range.len() - kept.map_or(0, |kept| kept.len());
+            candidate(
+                Adjustment::Shrink,
+                range.min,
+                other.min - 1,
+                dropped(Interval::new(range.min, other.min - 1)),
+            );
+            candidate(
+                Adjustment::Shrink,
+                other.max + 1,
+                range.max,
+                dropped(Interval::new(other.max + 1, range.max)),
+            );
+
+            // Move before the other range's min, or past its max.
+            let shift = range.max + 1 - other.min;
+            candidate(
+                Adjustment::Shift,
+                range.min - shift,
+                other.min - 1,
+                shift.unsigned_abs(),
+            );
+            let shift = other.max + 1 - range.min;
+            candidate(
+                Adjustment::Shift,
+                other.max + 1,
+                range.max + shift,
+                shift.unsigned_abs(),
+            );
+        }
+
+        candidates.sort_by_key(|candidate| {
+            (
+                candidate.cost,
+                candidate.kind == Adjustment::Shift,
+                candidate.elf,
+            )
+        });
+        candidates.into_iter().next()
+    }
+}
+
+/// Plan of minimal range adjustments removing the overlap of every pair: one adjustment per
+/// overlapping pair, with the smallest total amount of sections dropped or shifted.
+fn plan(pairs: &[Pair]) -> Vec<Reassignment> {
+    pairs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, pair)| pair.cheapest_reassignment(idx + 1))
+        .collect()
+}
+
+/// Parse the pairs of elves, one per line.
+fn parse(input: &str) -> Result<Vec<Pair>> {
+    input.lines().map(Pair::try_from).collect()
+}
+
+/// Number of pairs where one range fully contains the other.
+fn part1(pairs: &[Pair]) -> usize {
+    pairs.iter().filter(|pair| pair.fully_contained()).count()
+}
+
+/// Number of pairs with overlapping ranges.
+fn part2(pairs: &[Pair]) -> usize {
+    pairs.iter().filter(|pair| pair.overlap()).count()
+}
+
+/// Total number of section IDs assigned to both elves of a pair, across all the pairs.
+fn overlap_sections(pairs: &[Pair]) -> u64 {
+    interval::overlap_len(pairs.iter().map(|pair| (pair.0, pair.1)))
+}
+
+/// Sections assigned to at least one elf.
+#[derive(Debug, PartialEq, Eq)]
+struct Coverage {
+    /// Sorted disjoint ranges of assigned sections.
+    ranges: Vec<Interval>,
+    /// Ranges of unassigned sections between the assigned ones.
+    gaps: Vec<Interval>,
+    /// Number of pairs of elves, across all the assignment pairs, sharing at least a section.
+    shared: usize,
+}
+
+impl Coverage {
+    /// Merge the ranges of all the elves, and compare every elf's range with all the others.
+    fn new(pairs: &[Pair]) -> Self {
+        let elves: Vec<Interval> = pairs.iter().flat_map(|pair| [pair.0, pair.1]).collect();
+        let ranges = interval::merge(elves.iter().copied());
+        let gaps = ranges
+            .windows(2)
+            .filter_map(|ranges| Interval::new(ranges[0].max + 1, ranges[1].min - 1))
             .collect();
+        let shared = elves.pairs().filter(|(a, b)| a.intersects(b)).count();
+
+        Self {
+            ranges,
+            gaps,
+            shared,
+        }
+    }
 
-        let bounds = bounds?;
+    /// Number of sections assigned to at least one elf.
+    fn covered(&self) -> u64 {
+        self.ranges.iter().map(Interval::len).sum()
+    }
+}
 
-        if !bounds.is_sorted() {
-            anyhow::bail!("range of IDs for each elf must be expressed as `a-b`, where a <= b")
+impl Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |ranges: &[Interval]| match ranges.is_empty() {
+            true => "none".to_string(),
+            false => ranges
+                .iter()
+                .map(|range| format!("{}-{}", range.min, range.max))
+                .collect::<Vec<String>>()
+                .join(", "),
         };
 
-        Ok(Self {
-            min: bounds[0],
-            max: bounds[1],
-        })
+        writeln!(f, "Covered ranges: {}", list(&self.ranges))?;
+        writeln!(f, "Gaps: {}", list(&self.gaps))?;
+        writeln!(
+            f,
+            "{} sections covered, {} in gaps",
+            self.covered(),
+            self.gaps.iter().map(Interval::len).sum::<u64>()
+        )?;
+        write!(f, "{} pairs of elves share sections", self.shared)
     }
 }
 
 fn run() -> Result<()> {
-    let lines = read_input_lines("./input/day4.dat")?;
+    let args = Args::from_env(
+        &[examples::OPTION],
+        &["--plan", "--coverage", ParseMode::FLAG],
+    )?;
+    let input = examples::input(&args, 4)?;
+    let elves_pairs = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
 
-    let elves_pairs: Result<Vec<Pair>> = lines
-        .iter()
-        .map(|line| -> Result<Pair> { Pair::try_from(line.as_str()) })
-        .collect();
+    // Minimal reassignment plan.
+    if args.flag("--plan") {
+        let plan = plan(&elves_pairs);
+        for r in &plan {
+            println!(
+                "Pair {}: {:?} elf {} range to {}-{} ({} sections)",
+                r.pair, r.kind, r.elf, r.range.min, r.range.max, r.cost
+            );
+        }
+        println!(
+            "{} adjustments, {} sections in total",
+            plan.len(),
+            plan.iter().map(|r| r.cost).sum::<u64>()
+        );
+        return Ok(());
+    }
 
-    let elves_pairs = elves_pairs?;
+    // Merged coverage of the ranges.
+    if args.flag("--coverage") {
+        println!("{}", Coverage::new(&elves_pairs));
+        return Ok(());
+    }
 
     // Part 1
-    let fully_contained_count = elves_pairs
-        .iter()
-        .filter(|pair| pair.fully_contained())
-        .count();
-    println!("Number of ranges fully contained by other elf's range: {fully_contained_count}");
+    println!(
+        "Number of ranges fully contained by other elf's range: {}",
+        part1(&elves_pairs)
+    );
 
     // Part 2
-    let overlap_count = elves_pairs.iter().filter(|pair| pair.overlap()).count();
-    println!("Number of overlapping ranges: {overlap_count}");
+    println!("Number of overlapping ranges: {}", part2(&elves_pairs));
+    println!(
+        "Number of overlapping sections: {}",
+        overlap_sections(&elves_pairs)
+    );
 
     Ok(())
 }
@@ -127,19 +314,114 @@ mod test {
         assert_eq!(5, Pair::try_from(lines[2]).unwrap().0.min);
         assert_eq!(9, Pair::try_from(lines[2]).unwrap().1.max);
 
-        let elves_pairs: Vec<Pair> = lines
-            .iter()
-            .map(|line| Pair::try_from(*line).unwrap())
-            .collect();
+        let elves_pairs = parse(&lines.join("\n")).unwrap();
+        assert_eq!(2, part1(&elves_pairs));
+        assert_eq!(4, part2(&elves_pairs));
+        assert_eq!(1 + 5 + 1 + 3, overlap_sections(&elves_pairs));
+    }
+
+    #[test]
+    fn reassignment_plan() {
+        let pairs = parse("2-4,6-8\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n1-9,1-9").unwrap();
+        let plan = plan(&pairs);
 
+        let adjustment =
+            |r: &Reassignment| (r.pair, r.elf, r.kind, r.range.min, r.range.max, r.cost);
         assert_eq!(
-            2,
-            elves_pairs
-                .iter()
-                .filter(|pair| pair.fully_contained())
-                .count()
+            vec![
+                (2, 1, Adjustment::Shrink, 5, 6, 1),
+                (3, 1, Adjustment::Shrink, 2, 2, 6),
+                (4, 2, Adjustment::Shrink, 4, 5, 1),
+                (5, 1, Adjustment::Shrink, 2, 3, 3),
+                (6, 1, Adjustment::Shift, 10, 18, 9),
+            ],
+            plan.iter().map(adjustment).collect::<Vec<_>>()
         );
 
-        assert_eq!(4, elves_pairs.iter().filter(|pair| pair.overlap()).count());
+        // Adjusted pairs no longer overlap.
+        for r in &plan {
+            let pair = &pairs[r.pair - 1];
+            let adjusted = match r.elf {
+                1 => Pair(r.range, pair.1),
+                _ => Pair(pair.0, r.range),
+            };
+            assert!(!adjusted.overlap(), "{r:?}");
+        }
+
+        // Nothing can be moved before section 0.
+        let r = Pair::try_from("0-0,0-3")
+            .unwrap()
+            .cheapest_reassignment(1)
+            .unwrap();
+        assert_eq!((1, 2, Adjustment::Shrink, 1, 3, 1), adjustment(&r));
+    }
+
+    #[test]
+    fn coverage() {
+        let pairs = parse("2-4,6-8\n5-7,7-9\n12-12,15-20\n16-17,13-13").unwrap();
+        let coverage = Coverage::new(&pairs);
+        assert_eq!(
+            vec![
+                Interval::new(2, 9).unwrap(),
+                Interval::new(12, 13).unwrap(),
+                Interval::new(15, 20).unwrap(),
+            ],
+            coverage.ranges
+        );
+        assert_eq!(8 + 2 + 6, coverage.covered());
+        // 6-8, 5-7 and 7-9 all overlap each other, and 16-17 lies within 15-20.
+        assert_eq!(3 + 1, coverage.shared);
+        assert_eq!(
+            concat!(
+                "Covered ranges: 2-9, 12-13, 15-20\nGaps: 10-11, 14-14\n",
+                "16 sections covered, 3 in gaps\n4 pairs of elves share sections"
+            ),
+            coverage.to_string()
+        );
+
+        assert_eq!(
+            "Covered ranges: none\nGaps: none\n0 sections covered, 0 in gaps\n0 pairs of elves share sections",
+            Coverage::new(&[]).to_string()
+        );
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for line in [
+            "",
+            "2-4",
+            "2-4,",
+            "2,6-8",
+            "2-4,6",
+            "4-2,6-8",
+            "a-b,6-8",
+            "2-4,6-8-9",
+            "-2-4,6-8",
+        ] {
+            assert!(Pair::try_from(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(4, |input| {
+            let elves_pairs = parse(input)?;
+            Ok((part1(&elves_pairs), part2(&elves_pairs)))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_pairs(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day4.dat")).unwrap();
+        b.iter(|| parse(&input).unwrap());
     }
 }
@@ -1,56 +1,188 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
 use anyhow::Result;
-use std::{fs, process::ExitCode};
+use day1::{
+    cli::Args,
+    examples,
+    parse::ParseMode,
+    span::SpanError,
+    trace::{TraceValue, Tracer},
+};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::{self, BufRead, Write},
+    process::ExitCode,
+    thread,
+    time::Duration,
+};
 
-/// CrateMover model.
-#[derive(Debug)]
-enum CrateMover {
-    CrateMover9000,
-    CrateMover9001,
+/// Delay between the frames of the animation, in milliseconds, by default.
+const ANIMATION_DELAY: u64 = 500;
+
+/// Capabilities of a crane model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CraneCapabilities {
+    /// Maximum number of crates lifted at once (`None` if unlimited).
+    max_lift: Option<usize>,
+    /// Whether the crates lifted at once keep their order once placed.
+    preserves_order: bool,
+    /// Maximum distance between the origin and destination stacks (`None` if unlimited).
+    max_reach: Option<usize>,
+}
+
+impl CraneCapabilities {
+    /// CrateMover 9000, moving one crate at a time.
+    const CRATE_MOVER_9000: Self = Self {
+        max_lift: Some(1),
+        preserves_order: true,
+        max_reach: None,
+    };
+    /// CrateMover 9001, moving all the crates at once.
+    const CRATE_MOVER_9001: Self = Self {
+        max_lift: None,
+        preserves_order: true,
+        max_reach: None,
+    };
+
+    /// Check that the crane reaches the destination stack of `m` from its origin one.
+    fn reach(&self, m: &Move) -> Result<()> {
+        match self.max_reach {
+            Some(reach) if m.origin.abs_diff(m.destination) > reach => anyhow::bail!(
+                "stack {} is out of reach from stack {}",
+                m.destination,
+                m.origin
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Arrange the `crates` (bottom to top) moved from a stack as they are placed on another one,
+    /// lifting them from the top, a lift at a time.
+    fn arrange<T>(&self, mut crates: Vec<T>) -> Vec<T> {
+        let lift = self.max_lift.unwrap_or(usize::MAX);
+        let mut arranged = Vec::with_capacity(crates.len());
+        while !crates.is_empty() {
+            let mut lifted = crates.split_off(crates.len().saturating_sub(lift));
+            if !self.preserves_order {
+                lifted.reverse();
+            }
+            arranged.append(&mut lifted);
+        }
+
+        arranged
+    }
+
+    /// Inverse of [`CraneCapabilities::arrange`].
+    fn unarrange<T>(&self, mut arranged: Vec<T>) -> Vec<T> {
+        let lift = self.max_lift.unwrap_or(usize::MAX);
+        let mut crates = Vec::with_capacity(arranged.len());
+        // The last lift, on top, held the bottom crates.
+        while !arranged.is_empty() {
+            let mut lifted = arranged.split_off((arranged.len() - 1) / lift * lift);
+            if !self.preserves_order {
+                lifted.reverse();
+            }
+            crates.append(&mut lifted);
+        }
+
+        crates
+    }
 }
 
-impl CrateMover {
-    /// Whether the [`CrateMover`] model can move multiple crates at the same time.
-    fn multiple_crates(&self) -> bool {
+impl TryFrom<&str> for CraneCapabilities {
+    type Error = anyhow::Error;
+
+    /// Parse either a known model (`9000` or `9001`) or a comma separated list of capabilities:
+    /// `lift=<n|all>`, `order=<keep|reverse>` and `reach=<n|any>` (`lift=all,order=keep,reach=any`
+    /// by default).
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "9000" => return Ok(Self::CRATE_MOVER_9000),
+            "9001" => return Ok(Self::CRATE_MOVER_9001),
+            _ => {}
+        }
+
+        let limit = |limit: &str, unlimited: &str| -> Result<Option<usize>> {
+            match limit {
+                l if l == unlimited => Ok(None),
+                l => match l.parse() {
+                    Ok(0) | Err(_) => anyhow::bail!("invalid crane limit `{l}`"),
+                    Ok(limit) => Ok(Some(limit)),
+                },
+            }
+        };
+
+        let mut capabilities = Self::CRATE_MOVER_9001;
+        for capability in value.split(',') {
+            match capability.split_once('=') {
+                Some(("lift", lift)) => capabilities.max_lift = limit(lift, "all")?,
+                Some(("order", "keep")) => capabilities.preserves_order = true,
+                Some(("order", "reverse")) => capabilities.preserves_order = false,
+                Some(("reach", reach)) => capabilities.max_reach = limit(reach, "any")?,
+                _ => anyhow::bail!("unknown CrateMover model or capability `{capability}`"),
+            }
+        }
+
+        Ok(capabilities)
+    }
+}
+
+impl Display for CraneCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limit = |limit: Option<usize>, unlimited: &'static str| {
+            limit.map_or(unlimited.to_string(), |limit| limit.to_string())
+        };
+
         match *self {
-            CrateMover::CrateMover9000 => true,
-            CrateMover::CrateMover9001 => false,
+            Self::CRATE_MOVER_9000 => write!(f, "CrateMover9000"),
+            Self::CRATE_MOVER_9001 => write!(f, "CrateMover9001"),
+            _ => write!(
+                f,
+                "lift={},order={},reach={}",
+                limit(self.max_lift, "all"),
+                if self.preserves_order {
+                    "keep"
+                } else {
+                    "reverse"
+                },
+                limit(self.max_reach, "any")
+            ),
         }
     }
 }
 
 /// Ship's cargo crane.
 #[derive(Debug)]
-struct Crane {
-    /// CrateMover model.
-    model: CrateMover,
+struct Crane<'a> {
+    /// Crane model.
+    model: CraneCapabilities,
     /// Storage configuration: list of stacks.
     storage: Storage,
     /// Crane's rearrangement procedure: sequence of moves.
-    procedure: Procedure,
+    procedure: &'a Procedure,
 }
 
-impl Crane {
+impl<'a> Crane<'a> {
     /// Construct a new instance.
-    fn new(model: CrateMover, storage: Storage, procedure: Procedure) -> Self {
-        Self { model, storage, procedure }
+    fn new(model: CraneCapabilities, storage: Storage, procedure: &'a Procedure) -> Self {
+        Self {
+            model,
+            storage,
+            procedure,
+        }
     }
 
     /// Consumes the crane object, applying the procedure and returning the new [`Storage`] state.
-    fn execute_procedure(mut self) -> Result<Storage> {
-        for m in self.procedure.moves {
-            let moved_crates = self
-                .storage
-                .get_stack(m.origin)
-                .ok_or(anyhow::format_err!("required origin stack does not exist"))?
-                .pop_crates(m.amount)
-                .ok_or(anyhow::format_err!("invalid instructions in procedure"))?;
-
-            self.storage
-                .get_stack(m.destination)
-                .ok_or(anyhow::format_err!(
-                    "required destination stack does not exist"
-                ))?
-                .append_stack(moved_crates, self.model.multiple_crates());
+    /// If a [`Tracer`] is given, the [`Storage`] state is recorded after each move.
+    fn execute_procedure(mut self, mut tracer: Option<&mut Tracer>) -> Result<Storage> {
+        let event = self.model.to_string();
+        for (idx, m) in self.procedure.moves.iter().enumerate() {
+            self.storage.apply(m, &self.model)?;
+
+            if let Some(tracer) = tracer.as_deref_mut() {
+                tracer.record(&event, idx + 1, &[("stacks", self.storage.trace_value())])?;
+            }
         }
 
         Ok(self.storage)
@@ -60,30 +192,80 @@ impl Crane {
 #[derive(Debug)]
 struct Procedure {
     moves: Vec<Move>,
+    /// Line number of each move (indexing from 1).
+    lines: Vec<usize>,
 }
 
 impl Procedure {
-    fn new(moves: Vec<Move>) -> Self {
-        Self { moves }
+    fn new(moves: Vec<Move>, lines: Vec<usize>) -> Self {
+        Self { moves, lines }
+    }
+
+    /// Shift the line numbers of the moves by `offset`.
+    fn offset(mut self, offset: usize) -> Self {
+        self.lines.iter_mut().for_each(|line| *line += offset);
+        self
+    }
+
+    /// Check the whole procedure can be performed by the given crane model on `storage`, without
+    /// moving any crate: only the stack heights are tracked. Return the first ill-formed move
+    /// with its line number.
+    fn validate(&self, storage: &Storage, model: &CraneCapabilities) -> Result<()> {
+        let mut heights: Vec<usize> = storage.stacks.iter().map(|s| s.items.len()).collect();
+
+        for (m, line) in self.moves.iter().zip(&self.lines) {
+            let check = |heights: &[usize]| -> Result<()> {
+                model.reach(m)?;
+                let origin = m
+                    .origin
+                    .checked_sub(1)
+                    .and_then(|idx| heights.get(idx))
+                    .ok_or_else(|| anyhow::format_err!("stack {} does not exist", m.origin))?;
+                if m.destination == 0 || m.destination > heights.len() {
+                    anyhow::bail!("stack {} does not exist", m.destination);
+                }
+                if *origin < m.amount {
+                    anyhow::bail!(
+                        "stack {} holds {origin} crates, {} required",
+                        m.origin,
+                        m.amount
+                    );
+                }
+                Ok(())
+            };
+            check(&heights).map_err(|e| anyhow::format_err!("line {line}: {e}"))?;
+
+            heights[m.origin - 1] -= m.amount;
+            heights[m.destination - 1] += m.amount;
+        }
+
+        Ok(())
     }
 }
 
 impl TryFrom<&str> for Procedure {
-    type Error = anyhow::Error;
+    type Error = SpanError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let moves: Result<Vec<Move>> = value
+        let (moves, lines): (Vec<Move>, Vec<usize>) = value
             .split('\n')
-            .filter(|line| !line.is_empty())
-            .map(|move_instruction| -> Result<Move> { Move::try_from(move_instruction) })
-            .collect();
+            .zip(1..)
+            .filter(|(line, _)| !line.is_empty())
+            .map(|(move_instruction, line)| {
+                Move::try_from(move_instruction)
+                    .map(|m| (m, line))
+                    .map_err(|e| e.within(value, move_instruction))
+            })
+            .collect::<Result<Vec<(Move, usize)>, SpanError>>()?
+            .into_iter()
+            .unzip();
 
-        Ok(Self::new(moves?))
+        Ok(Self::new(moves, lines))
     }
 }
 
 /// Crane move.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Move {
     /// Number of [`Crate`]s to move.
     amount: usize,
@@ -104,46 +286,113 @@ impl Move {
 }
 
 impl TryFrom<&str> for Move {
-    type Error = anyhow::Error;
+    type Error = SpanError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         // Example line to parse: 'move 6 from 5 to 7'
-        let mut keywords = ["move", "from", "to"].iter();
-        let values: Result<Vec<Option<usize>>> = value
-            .split_ascii_whitespace()
-            .enumerate()
-            .map(|(idx, val)| -> Result<Option<usize>> {
-                if idx % 2 == 0 {
-                    if &val
-                        != keywords
-                            .next()
-                            .ok_or(anyhow::format_err!("unexpected keyword `{}`", val))?
-                    {
-                        anyhow::bail!("badly formatted move instruction")
-                    } else {
-                        return Ok(None);
-                    }
-                }
+        let mut cursor = Cursor::new(value);
+        cursor.keyword("move")?;
+        let amount = cursor.number("the number of crates")?;
+        cursor.keyword("from")?;
+        let origin = cursor.number("the origin stack")?;
+        cursor.keyword("to")?;
+        let destination = cursor.number("the destination stack")?;
+        cursor.end()?;
 
-                Ok(Some(val.parse()?))
-            })
-            .collect();
+        Ok(Move::new(amount, origin, destination))
+    }
+}
 
-        let values: Vec<usize> = values?.iter().filter_map(|val| *val).collect();
+/// Cursor over a line, parsing its whitespace separated tokens one at a time. Errors point at
+/// the offending token, or at the end of the line if the token is missing.
+struct Cursor<'a> {
+    /// Line being parsed.
+    line: &'a str,
+    /// Part of the line yet to be parsed.
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str) -> Self {
+        Self { line, rest: line }
+    }
+
+    /// Next token (empty at the end of the line).
+    fn token(&mut self) -> &'a str {
+        let rest = self
+            .rest
+            .trim_start_matches(|c: char| c.is_ascii_whitespace());
+        let (token, rest) = rest.split_at(
+            rest.find(|c: char| c.is_ascii_whitespace())
+                .unwrap_or(rest.len()),
+        );
+        self.rest = rest;
+        token
+    }
+
+    /// Error pointing at `token`, which was expected to be `what`.
+    fn expected(&self, token: &str, what: &str) -> SpanError {
+        match token.is_empty() {
+            true => SpanError::new(self.line, token, format!("expected {what}")),
+            false => SpanError::new(
+                self.line,
+                token,
+                format!("expected {what}, found `{token}`"),
+            ),
+        }
+    }
+
+    /// Parse the given keyword.
+    fn keyword(&mut self, keyword: &str) -> Result<(), SpanError> {
+        match self.token() {
+            token if token == keyword => Ok(()),
+            token => Err(self.expected(token, &format!("`{keyword}`"))),
+        }
+    }
+
+    /// Parse a non negative number, described by `what` in errors.
+    fn number(&mut self, what: &str) -> Result<usize, SpanError> {
+        let token = self.token();
+        token.parse().map_err(|_| self.expected(token, what))
+    }
 
-        Ok(Move::new(values[0], values[1], values[2]))
+    /// Check there's nothing left to parse.
+    fn end(&mut self) -> Result<(), SpanError> {
+        match self.token() {
+            "" => Ok(()),
+            token => Err(self.expected(token, "the end of the line")),
+        }
     }
 }
 
 /// Storage configuration.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Storage {
     /// Stacks in the storage.
     stacks: Vec<Stack>,
 }
 
+/// Whitespace separated tokens of `line`, with the (inclusive) columns they span.
+fn tokens(line: &str) -> Vec<(usize, usize, &str)> {
+    let mut tokens = vec![];
+    let mut start: Option<(usize, usize)> = None;
+
+    for (column, (offset, c)) in line.char_indices().chain([(line.len(), ' ')]).enumerate() {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some((column, offset)),
+            (Some((start_column, start_offset)), true) => {
+                tokens.push((start_column, column - 1, &line[start_offset..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
 impl TryFrom<&str> for Storage {
-    type Error = anyhow::Error;
+    type Error = SpanError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         // Example:
@@ -151,41 +400,54 @@ impl TryFrom<&str> for Storage {
         // [N] [C]
         // [Z] [M] [P]
         //  1   2   3
-        let mut rev_lines: Vec<&str> = value.split('\n').rev().collect();
-        let size = rev_lines
-            .remove(0)
-            .split_ascii_whitespace()
-            .last()
-            .ok_or(anyhow::format_err!(
-                "storage must contain at least one stack"
-            ))?
-            .parse::<usize>()
-            .map_err(|_| anyhow::format_err!("unable to retrieve storage size"))?;
-
-        let mut stacks: Vec<Stack> = Vec::with_capacity(size);
+        //
+        // Each crate lies above the stack whose label shares at least a column with it, so
+        // stacks may be more than 9 and crate labels wider than a single character.
+        let mut rows: Vec<&str> = value.split('\n').collect();
+        let footer = tokens(rows.pop().unwrap_or_default());
+        if footer.is_empty() {
+            return Err(SpanError::at(
+                value,
+                value.len(),
+                0,
+                "expected the stack labels",
+            ));
+        }
+        for (idx, (_, _, label)) in footer.iter().enumerate() {
+            if label.parse::<usize>().ok() != Some(idx + 1) {
+                return Err(SpanError::new(
+                    value,
+                    label,
+                    format!("expected stack label {}, found `{label}`", idx + 1),
+                ));
+            }
+        }
 
-        for line in rev_lines {
-            let mut idx = 0;
-            let mut a = 0;
+        let mut stacks: Vec<Stack> = vec![Stack::default(); footer.len()];
+        for (height, row) in rows.iter().rev().enumerate() {
+            for (start, end, token) in tokens(row) {
+                let label = token
+                    .strip_prefix('[')
+                    .and_then(|token| token.strip_suffix(']'))
+                    .filter(|label| !label.is_empty() && !label.contains(['[', ']']))
+                    .ok_or_else(|| {
+                        let message = format!("expected a crate like `[A]`, found `{token}`");
+                        SpanError::new(value, token, message)
+                    })?;
+                let idx = footer
+                    .iter()
+                    .position(|(min, max, _)| *min <= end && start <= *max)
+                    .ok_or_else(|| SpanError::new(value, token, "crate is not above any stack"))?;
 
-            let mut line_chars = line.chars();
-            while let Some(c) = line_chars.nth(1) {
-                if a % 2 != 0 {
-                    a += 1;
-                    continue;
+                // The crate must lie right on top of the stack.
+                if stacks[idx].items.len() != height {
+                    return Err(SpanError::new(
+                        value,
+                        token,
+                        format!("crate is not on top of stack {}", idx + 1),
+                    ));
                 }
-
-                if c != ' ' {
-                    let c = Crate::new(c);
-                    if let Some(stack) = stacks.get_mut(idx) {
-                        stack.append_crate(c);
-                    } else {
-                        stacks.push(Stack::new(c));
-                    }
-                };
-
-                idx += 1;
-                a += 1;
+                stacks[idx].append_crate(Crate::new(label));
             }
         }
 
@@ -197,20 +459,115 @@ impl Storage {
     /// Return a mutable reference to `nth` [`Stack`] in the [`Storage`] or
     /// `None` if the index is out of bounds (indexing from 1).
     fn get_stack(&mut self, n: usize) -> Option<&mut Stack> {
-        self.stacks.get_mut(n - 1)
+        self.stacks.get_mut(n.checked_sub(1)?)
+    }
+
+    /// Move `amount` crates from the `origin` stack to the `destination` one, placing them as
+    /// arranged by `arrange`.
+    fn transfer<F>(
+        &mut self,
+        amount: usize,
+        origin: usize,
+        destination: usize,
+        arrange: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(Vec<Crate>) -> Vec<Crate>,
+    {
+        let moved_crates = self
+            .get_stack(origin)
+            .ok_or(anyhow::format_err!("required origin stack does not exist"))?
+            .pop_crates(amount)
+            .ok_or(anyhow::format_err!("invalid instructions in procedure"))?;
+
+        self.get_stack(destination)
+            .ok_or(anyhow::format_err!(
+                "required destination stack does not exist"
+            ))?
+            .append_stack(arrange(moved_crates));
+
+        Ok(())
+    }
+
+    /// Apply a single [`Move`] performed by the given crane model.
+    fn apply(&mut self, m: &Move, model: &CraneCapabilities) -> Result<()> {
+        model.reach(m)?;
+        self.transfer(m.amount, m.origin, m.destination, |crates| {
+            model.arrange(crates)
+        })
+    }
+
+    /// Undo the `procedure` performed by the given crane model, recovering the storage
+    /// configuration it started from.
+    fn undo(&mut self, procedure: &Procedure, model: &CraneCapabilities) -> Result<()> {
+        for (idx, m) in procedure.moves.iter().enumerate().rev() {
+            model
+                .reach(m)
+                .and_then(|_| {
+                    self.transfer(m.amount, m.destination, m.origin, |crates| {
+                        model.unarrange(crates)
+                    })
+                })
+                .map_err(|e| anyhow::format_err!("undoing move {}: {e}", idx + 1))?;
+        }
+
+        Ok(())
+    }
+
+    /// Return the stacks (bottom to top) as a [`TraceValue`].
+    fn trace_value(&self) -> TraceValue {
+        TraceValue::from(
+            self.stacks
+                .iter()
+                .map(|stack| stack.items.iter().map(|c| c.0.as_str()).collect::<String>())
+                .collect::<Vec<String>>(),
+        )
     }
 
     /// Return the sequence of the top crates of each stack.
     fn top_crates_sequence(&self) -> String {
         self.stacks
             .iter()
-            .map(|stack| stack.items.last().unwrap_or(&Crate::new(' ')).0)
+            .map(|stack| stack.items.last().map_or(" ", |c| c.0.as_str()))
             .collect()
     }
 }
 
+impl Display for Storage {
+    /// Render the storage as in the puzzle input, crates in bracketed columns above the stack
+    /// labels. Columns are widened to fit the widest crate label and stack label.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .stacks
+            .iter()
+            .flat_map(|stack| &stack.items)
+            .map(|c| c.0.chars().count() + 2)
+            .chain([self.stacks.len().to_string().len() + 1, 3])
+            .max()
+            .unwrap_or(3);
+        let height = self.stacks.iter().map(|s| s.items.len()).max().unwrap_or(0);
+
+        for row in (0..height).rev() {
+            let cells: Vec<String> = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.items.get(row) {
+                    Some(c) => format!("{:<width$}", format!("[{}]", c.0)),
+                    None => " ".repeat(width),
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        let labels: Vec<String> = (1..=self.stacks.len())
+            .map(|label| format!(" {label:<0$}", width - 1))
+            .collect();
+        write!(f, "{}", labels.join(" "))
+    }
+}
+
 /// Storage stack of [`Crate`]s.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 struct Stack {
     /// Crates collected in the stack.
     items: Vec<Crate>,
@@ -225,67 +582,433 @@ impl Stack {
         }
     }
 
-    /// Append [`Stack`] to the top of the stack.
-    fn append_stack(&mut self, mut crates: Vec<Crate>, reverse: bool) {
-        if reverse {
-            crates.reverse();
-        }
-
+    /// Append [`Crate`]s (bottom to top) to the top of the stack.
+    fn append_stack(&mut self, mut crates: Vec<Crate>) {
         self.items.append(&mut crates);
     }
 
-    /// Construct a new instance.
-    fn new(c: Crate) -> Self {
-        Self { items: vec![c] }
-    }
-
     /// Append [`Crate`] to Stack
     fn append_crate(&mut self, c: Crate) {
         self.items.push(c)
     }
 }
 
-/// Storage Crate.
-#[derive(Debug)]
-struct Crate(char);
+/// Storage Crate, with its label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Crate(String);
 
 impl Crate {
     /// Construct a new instance.
-    fn new(c: char) -> Self {
-        Self(c)
+    fn new(label: &str) -> Self {
+        Self(label.to_string())
     }
 }
 
+/// Interactive puzzle: read moves from `input` (`move N from A to B`, `undo` or `q`) and apply
+/// them to `storage`, reporting to `output` whether the `target` configuration is reproduced.
+///
+/// Return whether the target configuration was reached.
+fn play<R, W>(
+    model: CraneCapabilities,
+    mut storage: Storage,
+    target: &Storage,
+    input: R,
+    mut output: W,
+) -> Result<bool>
+where
+    R: BufRead,
+    W: Write,
+{
+    let mut history: Vec<Storage> = vec![];
+    let mut lines = input.lines();
+
+    loop {
+        for (idx, stack) in storage.stacks.iter().enumerate() {
+            let crates: Vec<String> = stack.items.iter().map(|c| format!("[{}]", c.0)).collect();
+            writeln!(output, "{} {}", idx + 1, crates.join(" "))?;
+        }
+
+        if storage == *target {
+            writeln!(
+                output,
+                "Final configuration reproduced in {} moves!",
+                history.len()
+            )?;
+            return Ok(true);
+        }
+
+        write!(output, "move N from A to B, [u]ndo, [q]uit> ")?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        match line.trim() {
+            "q" => break,
+            "u" | "undo" => match history.pop() {
+                Some(previous) => storage = previous,
+                None => writeln!(output, "nothing to undo")?,
+            },
+            instruction => {
+                let previous = storage.clone();
+                match Move::try_from(instruction)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|m| storage.apply(&m, &model))
+                {
+                    Ok(()) => history.push(previous),
+                    Err(e) => {
+                        storage = previous;
+                        writeln!(output, "{e}")?;
+                    }
+                }
+            }
+        }
+    }
+
+    writeln!(output, "Final configuration not reproduced")?;
+
+    Ok(false)
+}
+
+/// Animate the `procedure` performed by the given crane model on `storage`, drawing to `output`
+/// a frame each time crates are lifted and each time they are dropped, `delay` apart.
+///
+/// Return the storage the procedure ends with.
+fn animate<W: Write>(
+    model: &CraneCapabilities,
+    mut storage: Storage,
+    procedure: &Procedure,
+    delay: Duration,
+    mut output: W,
+) -> Result<Storage> {
+    let mut frame = |title: String, storage: &Storage| -> Result<()> {
+        // Clear the terminal and draw from its top left corner.
+        write!(output, "\x1b[2J\x1b[H{title}\n\n{storage}\n")?;
+        output.flush()?;
+        thread::sleep(delay);
+        Ok(())
+    };
+
+    let moves = procedure.moves.len();
+    frame(format!("{model}: starting storage"), &storage)?;
+    for (idx, m) in procedure.moves.iter().enumerate() {
+        let mut lifted = storage.clone();
+        storage
+            .apply(m, model)
+            .map_err(|e| anyhow::format_err!("move {}: {e}", idx + 1))?;
+
+        // The move was applied, so the crates can be lifted.
+        let crates: Vec<String> = lifted.stacks[m.origin - 1]
+            .pop_crates(m.amount)
+            .unwrap_or_default()
+            .iter()
+            .map(|c| format!("[{}]", c.0))
+            .collect();
+        frame(
+            format!(
+                "{model}: move {}/{moves}, lifting {} from stack {}",
+                idx + 1,
+                crates.join(" "),
+                m.origin
+            ),
+            &lifted,
+        )?;
+        frame(
+            format!(
+                "{model}: move {}/{moves}, dropped on stack {}",
+                idx + 1,
+                m.destination
+            ),
+            &storage,
+        )?;
+    }
+
+    Ok(storage)
+}
+
+/// Conflict between two cranes touching the same stack in the same tick.
+#[derive(Debug, PartialEq, Eq)]
+struct Conflict {
+    /// Tick of the conflict (from 1).
+    tick: usize,
+    /// Crane forced to wait (from 1).
+    crane: usize,
+    /// Crane which moved first (from 1).
+    other: usize,
+    /// Contended stack (from 1).
+    stack: usize,
+}
+
+/// Outcome of the concurrent simulation.
+#[derive(Debug)]
+struct Simulation {
+    /// Final storage configuration.
+    storage: Storage,
+    /// Number of ticks elapsed.
+    ticks: usize,
+    /// Conflicts, in order.
+    conflicts: Vec<Conflict>,
+}
+
+/// Execute the procedures of multiple cranes of the same model interleaved against the same
+/// storage: at every tick each crane with moves left attempts its next move, by ascending crane
+/// index. A crane whose move touches (as origin or destination) a stack already touched during
+/// the tick by another crane waits for the next tick, and the conflict is recorded.
+fn simulate(
+    model: &CraneCapabilities,
+    mut storage: Storage,
+    cranes: &[Procedure],
+) -> Result<Simulation> {
+    let mut next: Vec<usize> = vec![0; cranes.len()];
+    let mut conflicts: Vec<Conflict> = vec![];
+    let mut ticks: usize = 0;
+
+    while cranes
+        .iter()
+        .zip(&next)
+        .any(|(procedure, next)| *next < procedure.moves.len())
+    {
+        ticks += 1;
+        // Stacks touched during the tick, with the crane touching them.
+        let mut touched: Vec<(usize, usize)> = vec![];
+
+        for (crane, procedure) in cranes.iter().enumerate() {
+            let Some(m) = procedure.moves.get(next[crane]) else {
+                continue;
+            };
+
+            if let Some((stack, other)) = touched
+                .iter()
+                .find(|(stack, _)| *stack == m.origin || *stack == m.destination)
+            {
+                conflicts.push(Conflict {
+                    tick: ticks,
+                    crane: crane + 1,
+                    other: other + 1,
+                    stack: *stack,
+                });
+                continue;
+            }
+
+            storage.apply(m, model).map_err(|e| {
+                anyhow::format_err!("crane {}, move {}: {e}", crane + 1, next[crane] + 1)
+            })?;
+            touched.extend([(m.origin, crane), (m.destination, crane)]);
+            next[crane] += 1;
+        }
+    }
+
+    Ok(Simulation {
+        storage,
+        ticks,
+        conflicts,
+    })
+}
+
+/// Puzzle input.
+#[derive(Debug)]
+struct Input {
+    /// Starting storage configuration.
+    storage: Storage,
+    /// Rearrangement procedure.
+    procedure: Procedure,
+    /// Procedures of each crane for the concurrent simulation: blank line separated blocks of
+    /// the rearrangement procedure.
+    cranes: Vec<Procedure>,
+}
+
+/// Parse the storage configuration and the procedure, separated by a blank line.
+fn parse(input: &str, mode: ParseMode) -> Result<Input> {
+    let (storage_configuration, procedure_instructions) =
+        input.split_once("\n\n").ok_or_else(|| {
+            SpanError::at(
+                input,
+                input.len(),
+                0,
+                "expected a blank line after the storage configuration",
+            )
+        })?;
+
+    // Errors point at the input as parsed, with the procedure normalized in lenient mode.
+    let input = match mode.normalize_tokens(procedure_instructions) {
+        Cow::Borrowed(_) => Cow::Borrowed(input),
+        Cow::Owned(procedure) => Cow::Owned(format!("{storage_configuration}\n\n{procedure}")),
+    };
+    let (storage_configuration, procedure_instructions) =
+        input.split_at(storage_configuration.len());
+    let procedure_instructions = &procedure_instructions[2..];
+
+    // Line numbers are counted from the start of the input.
+    let mut offset = storage_configuration.matches('\n').count() + 2;
+    let mut cranes = vec![];
+    for block in procedure_instructions.split("\n\n") {
+        if !block.trim().is_empty() {
+            let procedure = Procedure::try_from(block).map_err(|e| e.within(&input, block))?;
+            cranes.push(procedure.offset(offset));
+        }
+        offset += block.matches('\n').count() + 2;
+    }
+
+    Ok(Input {
+        storage: Storage::try_from(storage_configuration)
+            .map_err(|e| e.within(&input, storage_configuration))?,
+        procedure: Procedure::new(
+            cranes
+                .iter()
+                .flat_map(|procedure| procedure.moves.iter().cloned())
+                .collect(),
+            cranes
+                .iter()
+                .flat_map(|procedure| procedure.lines.iter().copied())
+                .collect(),
+        ),
+        cranes,
+    })
+}
+
+/// Storage after the given crane model applied the procedure.
+fn rearrange(
+    input: &Input,
+    model: CraneCapabilities,
+    tracer: Option<&mut Tracer>,
+) -> Result<Storage> {
+    Crane::new(model, input.storage.clone(), &input.procedure).execute_procedure(tracer)
+}
+
+/// Top crates of each stack after the CrateMover 9000 applied the procedure.
+fn part1(input: &Input, tracer: Option<&mut Tracer>) -> Result<String> {
+    Ok(rearrange(input, CraneCapabilities::CRATE_MOVER_9000, tracer)?.top_crates_sequence())
+}
+
+/// Top crates of each stack after the CrateMover 9001 applied the procedure.
+fn part2(input: &Input, tracer: Option<&mut Tracer>) -> Result<String> {
+    Ok(rearrange(input, CraneCapabilities::CRATE_MOVER_9001, tracer)?.top_crates_sequence())
+}
+
 fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day5.dat")?;
+    let args = Args::from_env(
+        &[
+            Tracer::OPTIONS[0],
+            Tracer::OPTIONS[1],
+            "--model",
+            "--delay",
+            examples::OPTION,
+        ],
+        &[
+            "--play",
+            "--animate",
+            "--cranes",
+            "--reverse",
+            "--validate",
+            "--draw",
+            ParseMode::FLAG,
+        ],
+    )?;
+    let mut tracer = Tracer::from_args(&args)?;
+    let mode = ParseMode::from_args(&args);
+    let input = examples::input(&args, 5)?;
+    let input = parse(&mode.normalize(&input), mode)?;
+    let model = || CraneCapabilities::try_from(args.value("--model").unwrap_or("9000"));
 
-    let (storage_configuration, procedure_instructions) = input
-        .split_once("\n\n")
-        .ok_or(anyhow::format_err!("invalid input format"))?;
+    // Dry run of the procedure.
+    if args.flag("--validate") {
+        let model = model()?;
+        input.procedure.validate(&input.storage, &model)?;
+        println!(
+            "{} moves of the procedure can be performed by {model}",
+            input.procedure.moves.len()
+        );
+        return Ok(());
+    }
+
+    // Concurrent cranes, one per block of the procedure.
+    if args.flag("--cranes") {
+        let simulation = simulate(&model()?, input.storage, &input.cranes)?;
+        for conflict in &simulation.conflicts {
+            println!(
+                "Tick {}: crane {} waits for crane {} on stack {}",
+                conflict.tick, conflict.crane, conflict.other, conflict.stack
+            );
+        }
+        println!(
+            "{} cranes completed in {} ticks with {} conflicts, top crates: {}",
+            input.cranes.len(),
+            simulation.ticks,
+            simulation.conflicts.len(),
+            simulation.storage.top_crates_sequence()
+        );
+        return Ok(());
+    }
+
+    // Starting configuration of the storage, given as the final one.
+    if args.flag("--reverse") {
+        let mut storage = input.storage;
+        storage.undo(&input.procedure, &model()?)?;
+        println!("{storage}");
+        return Ok(());
+    }
+
+    // Interactive puzzle.
+    if args.flag("--play") {
+        let target = Crane::new(model()?, input.storage.clone(), &input.procedure)
+            .execute_procedure(None)?;
+        play(
+            model()?,
+            input.storage,
+            &target,
+            io::stdin().lock(),
+            io::stdout(),
+        )?;
+        return Ok(());
+    }
+
+    // Animation of the procedure.
+    if args.flag("--animate") {
+        let model = model()?;
+        let delay = args.parse_value("--delay")?.unwrap_or(ANIMATION_DELAY);
+        let storage = animate(
+            &model,
+            input.storage,
+            &input.procedure,
+            Duration::from_millis(delay),
+            io::stdout(),
+        )?;
+        println!(
+            "The sequence of the top crates of each stack for {model} is: {}",
+            storage.top_crates_sequence()
+        );
+        return Ok(());
+    }
 
     // Part 1
-    let storage = Crane::new(
-        CrateMover::CrateMover9000,
-        Storage::try_from(storage_configuration)?,
-        Procedure::try_from(procedure_instructions)?,
-    )
-    .execute_procedure()?;
     println!(
         "The sequence of the top crates of each stack for CrateMover9000 is: {}",
-        storage.top_crates_sequence()
+        part1(&input, tracer.as_mut())?
     );
+    if args.flag("--draw") {
+        println!(
+            "{}",
+            rearrange(&input, CraneCapabilities::CRATE_MOVER_9000, None)?
+        );
+    }
 
     // Part 2
-    let storage = Crane::new(
-        CrateMover::CrateMover9001,
-        Storage::try_from(storage_configuration)?,
-        Procedure::try_from(procedure_instructions)?,
-    )
-    .execute_procedure()?;
     println!(
         "The sequence of the top crates of each stack for CrateMover9001 is: {}",
-        storage.top_crates_sequence()
+        part2(&input, tracer.as_mut())?
     );
+    if args.flag("--draw") {
+        println!(
+            "{}",
+            rearrange(&input, CraneCapabilities::CRATE_MOVER_9001, None)?
+        );
+    }
+
+    // Flush the trace, reporting any error writing it.
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
+    }
 
     Ok(())
 }
@@ -305,30 +1028,553 @@ mod test {
 
     #[test]
     fn example_test() {
-        let procedure_instructions = r#"move 1 from 2 to 1
+        let input = r#"    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
 move 3 from 1 to 3
 move 2 from 2 to 1
-move 1 from 1 to 2"#;
+move 1 from 1 to 2
+"#;
+
+        let input = parse(input, ParseMode::Strict).unwrap();
+        assert_eq!("CMZ", part1(&input, None).unwrap());
+        assert_eq!("MCD", part2(&input, None).unwrap());
+    }
 
-        let storage_configuration = r#"    [D]    
+    #[test]
+    fn wide_storage() {
+        let storage_configuration = concat!(
+            "                                    [J]     \n",
+            "[A]                                 [K]     \n",
+            "[B] [C] [D] [E] [F] [G] [H] [I]     [L] [M] \n",
+            " 1   2   3   4   5   6   7   8   9  10  11  ",
+        );
+        let mut storage = Storage::try_from(storage_configuration).unwrap();
+        assert_eq!(11, storage.stacks.len());
+        assert!(storage.stacks[8].items.is_empty());
+        assert_eq!("ACDEFGHI JM", storage.top_crates_sequence());
+
+        let procedure = Procedure::try_from("move 2 from 10 to 9\nmove 1 from 11 to 10").unwrap();
+        for m in &procedure.moves {
+            storage
+                .apply(m, &CraneCapabilities::CRATE_MOVER_9001)
+                .unwrap();
+        }
+        assert_eq!("ACDEFGHIJM ", storage.top_crates_sequence());
+
+        // Multi-character labels.
+        let storage = Storage::try_from("[AB]\n[CD] [E]\n  1    2 ").unwrap();
+        assert_eq!(
+            vec![Crate::new("CD"), Crate::new("AB")],
+            storage.stacks[0].items
+        );
+        assert_eq!("ABE", storage.top_crates_sequence());
+
+        for storage_configuration in [
+            " 1   3 ",
+            " 2   1 ",
+            "[A] [B] [C]\n 1   2 ",
+            "[A]\n    [B]\n 1   2 ",
+            "[A][B]\n 1   2 ",
+            "[]\n 1 ",
+            "[A]\n\n 1 ",
+        ] {
+            assert!(
+                Storage::try_from(storage_configuration).is_err(),
+                "{storage_configuration:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn crane_capabilities() {
+        let crates = || vec![1, 2, 3, 4, 5];
+        let cranes = [
+            CraneCapabilities::CRATE_MOVER_9000,
+            CraneCapabilities::CRATE_MOVER_9001,
+            CraneCapabilities::try_from("lift=2").unwrap(),
+            CraneCapabilities::try_from("lift=2,order=reverse,reach=1").unwrap(),
+        ];
+        assert_eq!(
+            vec![
+                vec![5, 4, 3, 2, 1],
+                vec![1, 2, 3, 4, 5],
+                vec![4, 5, 2, 3, 1],
+                vec![5, 4, 3, 2, 1],
+            ],
+            cranes.map(|crane| crane.arrange(crates()))
+        );
+        assert_eq!(
+            vec![3, 2, 1],
+            CraneCapabilities::try_from("order=reverse")
+                .unwrap()
+                .arrange(vec![1, 2, 3])
+        );
+
+        for lift in ["1", "2", "3", "all"] {
+            for order in ["keep", "reverse"] {
+                let crane =
+                    CraneCapabilities::try_from(format!("lift={lift},order={order}").as_str())
+                        .unwrap();
+                for n in 0..8 {
+                    let crates: Vec<usize> = (0..n).collect();
+                    assert_eq!(
+                        crates,
+                        crane.unarrange(crane.arrange(crates.clone())),
+                        "{crane}"
+                    );
+                }
+            }
+        }
+
+        // The reach limits the distance between the stacks.
+        let mut storage = Storage::try_from("[A] [B] [C]\n 1   2   3 ").unwrap();
+        let crane = CraneCapabilities::try_from("reach=1").unwrap();
+        assert!(storage.apply(&Move::new(1, 1, 2), &crane).is_ok());
+        let error = storage.apply(&Move::new(1, 3, 1), &crane).unwrap_err();
+        assert_eq!("stack 1 is out of reach from stack 3", error.to_string());
+
+        assert_eq!(
+            "CrateMover9000",
+            CraneCapabilities::CRATE_MOVER_9000.to_string()
+        );
+        assert_eq!("lift=2,order=reverse,reach=1", cranes[3].to_string());
+        for model in [
+            "9002",
+            "",
+            "lift=0",
+            "lift=x",
+            "order=shuffle",
+            "reach",
+            "reach=any,",
+        ] {
+            assert!(CraneCapabilities::try_from(model).is_err(), "{model}");
+        }
+    }
+
+    #[test]
+    fn dry_run() {
+        let input = "[A]\n[B] [C]\n 1   2 \n\nmove 1 from 1 to 2\n\nmove 2 from 2 to 1\nmove 4 from 1 to 2\n";
+        let input = parse(input, ParseMode::Strict).unwrap();
+        assert_eq!(vec![5, 7, 8], input.procedure.lines);
+        let storage = input.storage.clone();
+
+        let error = input
+            .procedure
+            .validate(&input.storage, &CraneCapabilities::CRATE_MOVER_9000)
+            .unwrap_err();
+        assert_eq!(
+            "line 8: stack 1 holds 3 crates, 4 required",
+            error.to_string()
+        );
+        assert_eq!(storage, input.storage);
+
+        for (procedure, error) in [
+            (
+                "move 1 from 1 to 2\nmove 1 from 3 to 1",
+                "line 2: stack 3 does not exist",
+            ),
+            ("\nmove 1 from 1 to 0", "line 2: stack 0 does not exist"),
+            ("move 2 from 1 to 2\nmove 3 from 2 to 1", ""),
+        ] {
+            let result = Procedure::try_from(procedure)
+                .unwrap()
+                .validate(&storage, &CraneCapabilities::CRATE_MOVER_9001);
+            assert_eq!(
+                error,
+                result.err().map(|e| e.to_string()).unwrap_or_default()
+            );
+        }
+
+        let storage = Storage::try_from("[A] [B] [C]\n 1   2   3 ").unwrap();
+        let error = Procedure::try_from("move 1 from 3 to 1")
+            .unwrap()
+            .validate(&storage, &CraneCapabilities::try_from("reach=1").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            "line 1: stack 1 is out of reach from stack 3",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn reverse_procedure() {
+        let input = parse(
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n",
+            ParseMode::Strict,
+        )
+        .unwrap();
+
+        for model in [
+            CraneCapabilities::CRATE_MOVER_9000,
+            CraneCapabilities::CRATE_MOVER_9001,
+            CraneCapabilities::try_from("lift=2,order=reverse").unwrap(),
+        ] {
+            let mut storage = input.storage.clone();
+            for m in &input.procedure.moves {
+                storage.apply(m, &model).unwrap();
+            }
+            assert_ne!(input.storage, storage);
+
+            storage.undo(&input.procedure, &model).unwrap();
+            assert_eq!(input.storage, storage, "{model:?}");
+        }
+
+        // The starting configuration would need crates the final one lacks.
+        let mut storage = Storage::try_from("[A] [B]\n 1   2 ").unwrap();
+        let procedure = Procedure::try_from("move 2 from 1 to 2\nmove 1 from 1 to 2").unwrap();
+        let error = storage
+            .undo(&procedure, &CraneCapabilities::CRATE_MOVER_9000)
+            .unwrap_err();
+        assert_eq!(
+            "undoing move 1: invalid instructions in procedure",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn storage_drawing() {
+        let storage_configuration = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        let storage = Storage::try_from(storage_configuration).unwrap();
+        assert_eq!(storage_configuration, storage.to_string());
+
+        // Columns widen to fit the labels, and the drawing parses back.
+        let storage = Storage::try_from("[Sun]\n[Moon] [E]\n   1   2").unwrap();
+        assert_eq!(
+            "[Sun]        \n[Moon] [E]   \n 1      2    ",
+            storage.to_string()
+        );
+        assert_eq!(
+            storage,
+            Storage::try_from(storage.to_string().as_str()).unwrap()
+        );
+
+        // Stack labels of two digits widen the columns as well.
+        let footer: Vec<String> = (1..=10).map(|label| format!(" {label}")).collect();
+        let mut storage = Storage::try_from(footer.join("  ").as_str()).unwrap();
+        storage.stacks[9].append_crate(Crate::new("A"));
+        assert_eq!(
+            concat!(
+                "                                    [A]\n",
+                " 1   2   3   4   5   6   7   8   9   10"
+            ),
+            storage.to_string()
+        );
+        assert_eq!(
+            storage,
+            Storage::try_from(storage.to_string().as_str()).unwrap()
+        );
+    }
+
+    #[test]
+    fn concurrent_cranes() {
+        let input = r#"    [D]    
 [N] [C]    
 [Z] [M] [P]
- 1   2   3 "#;
+ 1   2   3 
+
+move 1 from 2 to 1
+move 1 from 1 to 3
+
+move 1 from 3 to 2
+move 1 from 2 to 1
+"#;
+
+        let input = parse(input, ParseMode::Strict).unwrap();
+        assert_eq!(2, input.cranes.len());
+        assert_eq!(4, input.procedure.moves.len());
+
+        // Crane 2 waits on stack 2 at tick 1, then on stack 3 at tick 2.
+        let simulation = simulate(
+            &CraneCapabilities::CRATE_MOVER_9000,
+            input.storage.clone(),
+            &input.cranes,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                Conflict {
+                    tick: 1,
+                    crane: 2,
+                    other: 1,
+                    stack: 2,
+                },
+                Conflict {
+                    tick: 2,
+                    crane: 2,
+                    other: 1,
+                    stack: 3,
+                },
+            ],
+            simulation.conflicts
+        );
+        assert_eq!(4, simulation.ticks);
+        assert_eq!("DCP", simulation.storage.top_crates_sequence());
+
+        // A single crane runs the whole procedure sequentially.
+        let simulation = simulate(
+            &CraneCapabilities::CRATE_MOVER_9000,
+            input.storage.clone(),
+            std::slice::from_ref(&input.procedure),
+        )
+        .unwrap();
+        assert!(simulation.conflicts.is_empty());
+        assert_eq!(4, simulation.ticks);
+
+        let procedures = [Procedure::try_from("move 5 from 1 to 2").unwrap()];
+        let error = simulate(
+            &CraneCapabilities::CRATE_MOVER_9000,
+            input.storage,
+            &procedures,
+        )
+        .unwrap_err();
+        assert_eq!(
+            "crane 1, move 1: invalid instructions in procedure",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn adversarial_input() {
+        let storage_configuration = "[A] [B]\n 1   2 ";
 
-        let storage = Crane::new(
-            CrateMover::CrateMover9000,
-            Storage::try_from(storage_configuration).unwrap(),
-            Procedure::try_from(procedure_instructions).unwrap(),
+        for instruction in [
+            "move 1",
+            "move 1 from 2",
+            "move 1 from 2 to",
+            "move 1 from 2 to 1 and",
+            "move a from 2 to 1",
+            "move -1 from 2 to 1",
+            "go 1 from 2 to 1",
+        ] {
+            assert!(Procedure::try_from(instruction).is_err(), "{instruction}");
+        }
+
+        for instruction in [
+            "move 1 from 0 to 1",
+            "move 1 from 1 to 3",
+            "move 2 from 1 to 2",
+        ] {
+            let procedure = Procedure::try_from(instruction).unwrap();
+            let crane = Crane::new(
+                CraneCapabilities::CRATE_MOVER_9000,
+                Storage::try_from(storage_configuration).unwrap(),
+                &procedure,
+            );
+            assert!(crane.execute_procedure(None).is_err(), "{instruction}");
+        }
+
+        for storage_configuration in ["", "[A]", "[A]\n a "] {
+            assert!(Storage::try_from(storage_configuration).is_err());
+        }
+    }
+
+    #[test]
+    fn error_spans() {
+        let storage_configuration = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        for (input, line, column, message) in [
+            ("\n\nmove 1 from 2 to 1", 1, 1, "expected the stack labels"),
+            (
+                "[A]\n 2 \n\nmove 1 from 1 to 1",
+                2,
+                2,
+                "expected stack label 1, found `2`",
+            ),
+            (
+                "[A}\n 1 \n\nmove 1 from 1 to 1",
+                1,
+                1,
+                "expected a crate like `[A]`, found `[A}`",
+            ),
+            (
+                "[A]\n   \n 1 \n\nmove 1 from 1 to 1",
+                1,
+                1,
+                "crate is not on top of stack 1",
+            ),
+            (
+                "[A]     [B]\n 1 \n\nmove 1 from 1 to 1",
+                1,
+                9,
+                "crate is not above any stack",
+            ),
+            (
+                "[A]\n 1 ",
+                2,
+                4,
+                "expected a blank line after the storage configuration",
+            ),
+        ] {
+            let error = parse(input, ParseMode::Strict).unwrap_err();
+            let error = error.downcast_ref::<SpanError>().unwrap();
+            assert_eq!((line, column), (error.line(), error.column()), "{input:?}");
+            assert!(error.to_string().contains(message), "{error}");
+        }
+
+        for (procedure, line, column, message) in [
+            ("mvoe 1 from 2 to 1", 6, 1, "expected `move`, found `mvoe`"),
+            (
+                "move 1 from 2 to 1\nmove x from 2 to 1",
+                7,
+                6,
+                "expected the number of crates",
+            ),
+            (
+                "move 1 from -2 to 1",
+                6,
+                13,
+                "expected the origin stack, found `-2`",
+            ),
+            ("move 1 from 2 into 1", 6, 15, "expected `to`, found `into`"),
+            ("move 1 from 2 to", 6, 17, "expected the destination stack"),
+            (
+                "move 1 from 2 to 1\n\nmove 1 from 2 to 1 now",
+                8,
+                20,
+                "found `now`",
+            ),
+        ] {
+            let input = format!("{storage_configuration}\n\n{procedure}");
+            let error = parse(&input, ParseMode::Strict).unwrap_err();
+            let error = error.downcast_ref::<SpanError>().unwrap();
+            assert_eq!(
+                (line, column),
+                (error.line(), error.column()),
+                "{procedure:?}"
+            );
+            assert!(error.to_string().contains(message), "{error}");
+        }
+
+        // In lenient mode errors point at the normalized procedure.
+        let input = format!("{storage_configuration}\n\n  move  1 from 2 to x");
+        let error = parse(&input, ParseMode::Lenient).unwrap_err();
+        assert_eq!(
+            concat!(
+                "line 6, column 18: expected the destination stack, found `x`\n",
+                "  |\n",
+                "6 | move 1 from 2 to x\n",
+                "  |                  ^"
+            ),
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn animation() {
+        let storage = Storage::try_from("[A]\n[B] [C]\n 1   2 ").unwrap();
+        let procedure = Procedure::try_from("move 2 from 1 to 2").unwrap();
+        let mut output: Vec<u8> = vec![];
+        let storage = animate(
+            &CraneCapabilities::CRATE_MOVER_9001,
+            storage,
+            &procedure,
+            Duration::ZERO,
+            &mut output,
         )
-        .execute_procedure().unwrap();
-        assert_eq!("CMZ", storage.top_crates_sequence());
+        .unwrap();
+        assert_eq!(" A", storage.top_crates_sequence());
+
+        let output = String::from_utf8(output).unwrap();
+        let frames: Vec<&str> = output.split("\x1b[2J\x1b[H").skip(1).collect();
+        assert_eq!(
+            vec![
+                "CrateMover9001: starting storage\n\n[A]    \n[B] [C]\n 1   2 \n",
+                "CrateMover9001: move 1/1, lifting [B] [A] from stack 1\n\n    [C]\n 1   2 \n",
+                "CrateMover9001: move 1/1, dropped on stack 2\n\n    [A]\n    [B]\n    [C]\n 1   2 \n",
+            ],
+            frames
+        );
 
-        let storage = Crane::new(
-            CrateMover::CrateMover9001,
-            Storage::try_from(storage_configuration).unwrap(),
-            Procedure::try_from(procedure_instructions).unwrap(),
+        let procedure = Procedure::try_from("move 3 from 1 to 2").unwrap();
+        let storage = Storage::try_from("[A]\n 1   2 ").unwrap();
+        let error = animate(
+            &CraneCapabilities::CRATE_MOVER_9001,
+            storage,
+            &procedure,
+            Duration::ZERO,
+            io::sink(),
         )
-        .execute_procedure().unwrap();
-        assert_eq!("MCD", storage.top_crates_sequence());
+        .unwrap_err();
+        assert_eq!(
+            "move 1: invalid instructions in procedure",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn interactive_puzzle() {
+        let storage_configuration = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        let storage = Storage::try_from(storage_configuration).unwrap();
+        let target = Crane::new(
+            CraneCapabilities::CRATE_MOVER_9001,
+            storage.clone(),
+            &Procedure::try_from("move 1 from 2 to 1\nmove 3 from 1 to 3").unwrap(),
+        )
+        .execute_procedure(None)
+        .unwrap();
+
+        // Wrong move, undo, invalid moves, then the right procedure.
+        let input = "move 2 from 2 to 1\nundo\nmove 9 from 1 to 2\njump\nmove 1 from 2 to 1\nmove 3 from 1 to 3\n";
+        let mut output: Vec<u8> = vec![];
+        assert!(play(
+            CraneCapabilities::CRATE_MOVER_9001,
+            storage.clone(),
+            &target,
+            input.as_bytes(),
+            &mut output
+        )
+        .unwrap());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("invalid instructions in procedure"));
+        assert!(output.contains("line 1, column 1: expected `move`, found `jump`"));
+        assert!(output.ends_with("Final configuration reproduced in 2 moves!\n"));
+
+        let input = "move 1 from 2 to 1\nu\nu\nq\n";
+        let mut output: Vec<u8> = vec![];
+        assert!(!play(
+            CraneCapabilities::CRATE_MOVER_9001,
+            storage,
+            &target,
+            input.as_bytes(),
+            &mut output
+        )
+        .unwrap());
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("nothing to undo"));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(5, |input| {
+            let input = parse(input, ParseMode::Strict)?;
+            Ok((part1(&input, None)?, part2(&input, None)?))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_storage(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day5.dat")).unwrap();
+        let (storage, _) = input.split_once("\n\n").unwrap();
+        b.iter(|| Storage::try_from(storage).unwrap());
+    }
+
+    #[bench]
+    fn parse_procedure(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day5.dat")).unwrap();
+        let (_, procedure) = input.split_once("\n\n").unwrap();
+        b.iter(|| Procedure::try_from(procedure).unwrap());
     }
 }
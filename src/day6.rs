@@ -1,5 +1,15 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
 use anyhow::Result;
-use std::{fs, process::ExitCode};
+use day1::{cli::Args, counter::Counter, examples, parse::ParseMode};
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    process::ExitCode,
+};
+
+/// Bytes read at once from a streamed datastream.
+const CHUNK_SIZE: usize = 8192;
 
 /// Sequence type.
 #[derive(Clone, Copy)]
@@ -17,72 +27,367 @@ impl From<Sequence> for usize {
     }
 }
 
+impl Sequence {
+    /// Name of the marker of this sequence type.
+    fn name(&self) -> &'static str {
+        match self {
+            Sequence::Packet => "packet",
+            Sequence::Message => "message",
+        }
+    }
+}
+
+/// Rolling window over a datastream fed one symbol (character or byte) at a time, detecting
+/// markers without keeping the whole stream.
+#[derive(Debug)]
+struct MarkerDetector {
+    /// Marker length.
+    len: usize,
+    /// Last `len` symbols.
+    window: VecDeque<u32>,
+    /// Count of each symbol below 256 (any byte, or ASCII and Latin-1 character) in the window.
+    low: [usize; 256],
+    /// Count of each other symbol in the window.
+    others: Counter<u32>,
+    /// Distinct symbols in the window.
+    distinct: usize,
+    /// Symbols processed so far.
+    processed: usize,
+}
+
+impl MarkerDetector {
+    /// Construct a new instance detecting markers of `len` distinct symbols.
+    fn new(len: usize) -> Self {
+        Self {
+            len,
+            window: VecDeque::with_capacity(len + 1),
+            low: [0; 256],
+            others: Counter::new(),
+            distinct: 0,
+            processed: 0,
+        }
+    }
+
+    /// Feed the next symbol, returning the number of symbols processed if the last `len` ones
+    /// are all different.
+    ///
+    /// # Note
+    /// The window counts are updated as it slides by one symbol, so each symbol is processed in
+    /// constant time whatever the marker length.
+    fn push(&mut self, c: u32) -> Option<usize> {
+        self.processed += 1;
+        self.window.push_back(c);
+        self.count(c, true);
+        if self.window.len() > self.len {
+            if let Some(oldest) = self.window.pop_front() {
+                self.count(oldest, false);
+            }
+        }
+
+        (self.distinct == self.len).then_some(self.processed)
+    }
+
+    /// Count `c` entering (or leaving) the window, keeping track of the distinct symbols.
+    fn count(&mut self, c: u32, enters: bool) {
+        let count = match self.low.get_mut(c as usize) {
+            Some(count) => {
+                match enters {
+                    true => *count += 1,
+                    false => *count -= 1,
+                }
+                *count
+            }
+            None => {
+                match enters {
+                    true => self.others.add(c),
+                    false => {
+                        self.others.remove(&c);
+                    }
+                }
+                self.others.count(&c)
+            }
+        };
+
+        match (enters, count) {
+            (true, 1) => self.distinct += 1,
+            (false, 0) => self.distinct -= 1,
+            _ => {}
+        }
+    }
+}
+
 /// DataStream Buffer
 #[derive(Debug)]
 struct StreamBuffer {
-    chars: Vec<char>,
+    /// Symbols of the stream: characters of text, or bytes of binary data.
+    symbols: Vec<u32>,
+    /// Whether the stream is binary data, scanned byte by byte.
+    binary: bool,
 }
 
 impl From<&str> for StreamBuffer {
     fn from(value: &str) -> Self {
         Self {
-            chars: value.chars().collect(),
+            symbols: value.chars().map(u32::from).collect(),
+            binary: false,
+        }
+    }
+}
+
+impl From<&[u8]> for StreamBuffer {
+    /// Scan valid UTF-8 data as text, character by character, and anything else byte by byte.
+    fn from(value: &[u8]) -> Self {
+        match std::str::from_utf8(value) {
+            Ok(text) => Self::from(text),
+            Err(_) => Self::bytes(value),
         }
     }
 }
 
 impl StreamBuffer {
-    /// Check if sequence is a valid start-of-{packet,message} marker.
+    /// Construct a new instance scanning `data` byte by byte, even if it is text.
+    fn bytes(data: &[u8]) -> Self {
+        Self {
+            symbols: data.iter().map(|b| u32::from(*b)).collect(),
+            binary: true,
+        }
+    }
+
+    /// Name of the symbols the stream is scanned by.
+    fn unit(&self) -> &'static str {
+        match self.binary {
+            true => "byte",
+            false => "character",
+        }
+    }
+
+    /// Return the number of characters to be processed before encountering the first
+    /// [`Sequence`] marker (start-of-packet | start-of-message).
     ///
     /// # Note
     /// In order to be a valid start-of-{packet,message} marker the {4,14} chars sequence must
-    /// not to have a duplicate character.
-    fn check_marker(sequence: &[char]) -> bool {
-        for (idx, val) in sequence.iter().enumerate() {
-            if sequence[idx + 1..].contains(val) {
-                return false;
-            }
+    /// not to have a duplicate character: the window of the last {4,14} chars is counted while
+    /// sliding over the buffer.
+    fn chars_before(&self, sequence: Sequence) -> Option<usize> {
+        self.all_markers(sequence.into()).next()
+    }
+
+    /// Return, in increasing order, the number of characters processed at each position where
+    /// the last `len` characters are all different.
+    fn all_markers(&self, len: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut detector = MarkerDetector::new(len);
+        self.symbols.iter().filter_map(move |c| detector.push(*c))
+    }
+}
+
+/// Parse the datastream buffer.
+fn parse(input: &str) -> StreamBuffer {
+    StreamBuffer::from(input)
+}
+
+/// Characters processed before the first start-of-packet marker.
+fn part1(stream: &StreamBuffer) -> Option<usize> {
+    stream.chars_before(Sequence::Packet)
+}
+
+/// Characters processed before the first start-of-message marker.
+fn part2(stream: &StreamBuffer) -> Option<usize> {
+    stream.chars_before(Sequence::Message)
+}
+
+/// Markers found in a batch of datastreams, one per line.
+#[derive(Debug, PartialEq, Eq)]
+struct Batch {
+    /// Start-of-packet and start-of-message markers of each stream.
+    markers: Vec<(Option<usize>, Option<usize>)>,
+}
+
+impl Batch {
+    /// Detect the markers of each stream.
+    fn new(streams: &[StreamBuffer]) -> Self {
+        Self {
+            markers: streams
+                .iter()
+                .map(|stream| (part1(stream), part2(stream)))
+                .collect(),
         }
+    }
 
-        true
+    /// Number of streams containing a start-of-packet and a start-of-message marker.
+    fn found(&self) -> (usize, usize) {
+        (
+            self.markers.iter().filter(|(p, _)| p.is_some()).count(),
+            self.markers.iter().filter(|(_, m)| m.is_some()).count(),
+        )
     }
 
-    /// Return sequence of 4 characters starting at index.
-    fn get_seq(&self, idx: usize, n: usize) -> Option<&[char]> {
-        self.chars.get(idx..idx + n)
+    /// Earliest and latest offset among the found markers of the given kind.
+    fn range(&self, sequence: Sequence) -> Option<(usize, usize)> {
+        let offsets = self.markers.iter().filter_map(|(p, m)| match sequence {
+            Sequence::Packet => *p,
+            Sequence::Message => *m,
+        });
+
+        Some((offsets.clone().min()?, offsets.max()?))
     }
+}
 
-    /// Return the number of characters to be processed before encountering the first
-    /// [`Sequence`] marker (start-of-packet | start-of-message).
-    fn chars_before(&self, sequence: Sequence) -> Option<usize> {
-        let sequence_len = sequence.into();
-        let mut idx: usize = 0;
-        while let Some(slice) = self.get_seq(idx, sequence_len) {
-            if Self::check_marker(slice) {
-                return Some(idx + sequence_len);
+/// Detect the first packet and message markers of the datastream read from `reader` in chunks,
+/// reporting each one to `output` as soon as it is found: only the last symbols of the stream
+/// are kept, and reading stops once both markers are found.
+///
+/// The stream is scanned by characters, or by bytes if `binary`.
+fn scan<R, W>(mut reader: R, binary: bool, mut output: W) -> Result<[Option<usize>; 2]>
+where
+    R: Read,
+    W: Write,
+{
+    let sequences = [Sequence::Packet, Sequence::Message];
+    let mut detectors = sequences.map(|sequence| MarkerDetector::new(sequence.into()));
+    let mut markers = [None; 2];
+    let mut chunk = [0; CHUNK_SIZE];
+    // Bytes of a character split across chunks, carried over to the next one.
+    let mut pending = 0;
+    let mut offset = 0;
+    let unit = match binary {
+        true => "byte",
+        false => "character",
+    };
+
+    while markers.iter().any(Option::is_none) {
+        let read = match reader.read(&mut chunk[pending..]) {
+            Ok(0) if pending > 0 => anyhow::bail!("stream ends with an incomplete character"),
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let bytes = &chunk[..pending + read];
+        let (symbols, valid): (Vec<u32>, usize) = match std::str::from_utf8(bytes) {
+            _ if binary => (bytes.iter().map(|b| u32::from(*b)).collect(), bytes.len()),
+            Ok(valid) => (valid.chars().map(u32::from).collect(), valid.len()),
+            // Incomplete character at the end of the chunk.
+            Err(e) if e.error_len().is_none() => {
+                let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default();
+                (valid.chars().map(u32::from).collect(), valid.len())
+            }
+            Err(e) => anyhow::bail!(
+                "invalid UTF-8 data at byte {}, scan binary streams with `--bytes`",
+                offset + e.valid_up_to()
+            ),
+        };
+
+        for c in symbols {
+            for ((detector, marker), sequence) in
+                detectors.iter_mut().zip(&mut markers).zip(sequences)
+            {
+                if marker.is_some() {
+                    continue;
+                }
+                *marker = detector.push(c);
+                if let Some(chars_num) = marker {
+                    writeln!(
+                        output,
+                        "First {} marker found after {unit}: {chars_num}",
+                        sequence.name()
+                    )?;
+                    output.flush()?;
+                }
             }
-            idx += 1;
         }
 
-        None
+        offset += valid;
+        pending = bytes.len() - valid;
+        chunk.copy_within(valid..valid + pending, 0);
     }
+
+    for (marker, sequence) in markers.iter().zip(sequences) {
+        if marker.is_none() {
+            writeln!(output, "No {} marker found", sequence.name())?;
+        }
+    }
+
+    Ok(markers)
+}
+
+/// Parse one datastream per line.
+fn parse_batch(input: &str) -> Vec<StreamBuffer> {
+    input.lines().map(parse).collect()
 }
 
 fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day6.dat")?;
+    let args = Args::from_env(
+        &[examples::OPTION],
+        &["--batch", "--all", "--stdin", "--bytes", ParseMode::FLAG],
+    )?;
+    let mode = ParseMode::from_args(&args);
+
+    // Datastream read from the standard input as it comes.
+    if args.flag("--stdin") {
+        scan(io::stdin().lock(), args.flag("--bytes"), io::stdout())?;
+        return Ok(());
+    }
+
+    // One datastream per line.
+    if args.flag("--batch") {
+        let input = examples::input(&args, 6)?;
+        let input = mode.normalize(&input);
+        let batch = Batch::new(&parse_batch(&input));
+        let offset = |marker: Option<usize>| marker.map_or("-".to_string(), |m| m.to_string());
+        println!("stream\tpacket\tmessage");
+        for (idx, (packet, message)) in batch.markers.iter().enumerate() {
+            println!("{}\t{}\t{}", idx + 1, offset(*packet), offset(*message));
+        }
+
+        let (packets, messages) = batch.found();
+        println!(
+            "{} streams, {packets} with packet marker, {messages} with message marker",
+            batch.markers.len()
+        );
+        for (name, sequence) in [("Packet", Sequence::Packet), ("Message", Sequence::Message)] {
+            if let Some((min, max)) = batch.range(sequence) {
+                println!("{name} markers found after characters {min} to {max}");
+            }
+        }
+        return Ok(());
+    }
+
+    // Binary data is scanned byte by byte, as it is not normalized.
+    let data = examples::input_bytes(&args, 6)?;
+    let stream = match std::str::from_utf8(&data) {
+        Ok(_) if args.flag("--bytes") => StreamBuffer::bytes(&data),
+        Ok(input) => parse(&mode.normalize(input)),
+        Err(_) => StreamBuffer::from(&data[..]),
+    };
+    let unit = stream.unit();
 
-    let stream = StreamBuffer::try_from(input.as_str())?;
+    // Every marker, not just the first one.
+    if args.flag("--all") {
+        for (name, sequence) in [("packet", Sequence::Packet), ("message", Sequence::Message)] {
+            let markers: Vec<String> = stream
+                .all_markers(sequence.into())
+                .map(|offset| offset.to_string())
+                .collect();
+            println!(
+                "{} {name} markers found after {unit}s: {}",
+                markers.len(),
+                markers.join(", ")
+            );
+        }
+        return Ok(());
+    }
 
     // Part 1
-    if let Some(chars_num) = stream.chars_before(Sequence::Packet) {
-        println!("First packet marker found after character: {chars_num}");
+    if let Some(chars_num) = part1(&stream) {
+        println!("First packet marker found after {unit}: {chars_num}");
     } else {
         println!("No packet marker found");
     }
 
     // Part 2
-    if let Some(chars_num) = stream.chars_before(Sequence::Message) {
-        println!("First message marker found after character: {chars_num}");
+    if let Some(chars_num) = part2(&stream) {
+        println!("First message marker found after {unit}: {chars_num}");
     } else {
         println!("No message marker found");
     }
@@ -113,11 +418,190 @@ mod test {
             ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
         ];
 
-
         for (stream, packet, message) in TEST_INPUT {
-            let stream = StreamBuffer::try_from(stream).unwrap();
-            assert_eq!(stream.chars_before(Sequence::Packet).unwrap(), packet);
-            assert_eq!(stream.chars_before(Sequence::Message).unwrap(), message);
+            let stream = parse(stream);
+            assert_eq!(part1(&stream).unwrap(), packet);
+            assert_eq!(part2(&stream).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let batch = Batch::new(&parse_batch(
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb\nabcabcabc\nnznrnfrfntjfmvfwmzdfjlvtqnbhcprsg\n",
+        ));
+        assert_eq!(
+            vec![(Some(7), Some(19)), (None, None), (Some(10), Some(29))],
+            batch.markers
+        );
+        assert_eq!((2, 2), batch.found());
+        assert_eq!(Some((7, 10)), batch.range(Sequence::Packet));
+        assert_eq!(Some((19, 29)), batch.range(Sequence::Message));
+
+        let batch = Batch::new(&parse_batch("aaaa"));
+        assert_eq!((0, 0), batch.found());
+        assert_eq!(None, batch.range(Sequence::Packet));
+    }
+
+    #[test]
+    fn all_markers() {
+        let stream = parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb");
+        assert_eq!(
+            vec![7, 8, 9, 10, 11, 12, 13, 14],
+            stream
+                .all_markers(Sequence::Packet.into())
+                .take(8)
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            vec![19, 25, 26, 27, 28, 29, 30],
+            stream
+                .all_markers(Sequence::Message.into())
+                .collect::<Vec<usize>>()
+        );
+
+        let stream = parse("abcabcaab");
+        assert_eq!(
+            vec![3, 4, 5, 6, 7],
+            stream.all_markers(3).collect::<Vec<usize>>()
+        );
+        assert_eq!(0, stream.all_markers(4).count());
+
+        // Long markers, mixing ASCII and other characters.
+        let chars: String = (0..1000)
+            .filter_map(|code| char::from_u32(0x20 + code))
+            .collect();
+        let stream = parse(&format!("{chars}{chars}"));
+        assert_eq!(
+            (1000..=2000).collect::<Vec<usize>>(),
+            stream.all_markers(1000).collect::<Vec<usize>>()
+        );
+        assert_eq!(0, stream.all_markers(1001).count());
+    }
+
+    #[test]
+    fn streaming() {
+        for (stream, packet, message) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", Some(7), Some(19)),
+            ("abcabcabc", None, None),
+            ("éàèùaaaa", Some(4), None),
+        ] {
+            let mut output: Vec<u8> = vec![];
+            assert_eq!(
+                [packet, message],
+                scan(stream.as_bytes(), false, &mut output).unwrap()
+            );
+            assert_eq!(
+                packet.is_some(),
+                String::from_utf8(output)
+                    .unwrap()
+                    .starts_with("First packet marker")
+            );
+        }
+
+        // Multi-byte characters split across chunks, and a marker far into the stream.
+        let stream = format!("{}{}", "é".repeat(CHUNK_SIZE), "abcdefghijklmnopqrstuvwxyz");
+        let markers = scan(&stream.as_bytes()[1..], false, io::sink());
+        assert!(markers.is_err());
+        let markers = scan(stream.as_bytes(), false, io::sink()).unwrap();
+        assert_eq!([Some(CHUNK_SIZE + 3), Some(CHUNK_SIZE + 13)], markers);
+
+        // Reading stops once both markers are found.
+        let mut reader = "abcdefghijklmn".as_bytes().chain([0xff_u8].as_slice());
+        assert_eq!(
+            [Some(4), Some(14)],
+            scan(&mut reader, false, io::sink()).unwrap()
+        );
+        assert!(scan(
+            "abcd\u{e9}".as_bytes().split_last().unwrap().1,
+            false,
+            io::sink()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn binary_data() {
+        // Text is scanned by characters, anything else by bytes.
+        let stream = StreamBuffer::from("éàèù".as_bytes());
+        assert_eq!(("character", Some(4)), (stream.unit(), part1(&stream)));
+        // Each character is encoded as two bytes, the first one shared.
+        let stream = StreamBuffer::bytes("éàèù".as_bytes());
+        assert_eq!(("byte", None), (stream.unit(), part1(&stream)));
+
+        let data: Vec<u8> = [0xff, 0xff, 0x00, 0xfe]
+            .into_iter()
+            .chain(0x80..0x8d)
+            .collect();
+        let stream = StreamBuffer::from(&data[..]);
+        assert_eq!(("byte", Some(5)), (stream.unit(), part1(&stream)));
+        assert_eq!(Some(15), part2(&stream));
+
+        let mut output: Vec<u8> = vec![];
+        assert_eq!(
+            [Some(5), Some(15)],
+            scan(&data[..], true, &mut output).unwrap()
+        );
+        assert_eq!(
+            "First packet marker found after byte: 5\nFirst message marker found after byte: 15\n",
+            String::from_utf8(output).unwrap()
+        );
+        assert!(scan(&data[..], false, io::sink()).is_err());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for stream in [
+            "",
+            "abc",
+            "aaaaaaaaaaaaaaaaaaaa",
+            "abcabcabcabcabcabc",
+            "ééééé",
+        ] {
+            let stream = StreamBuffer::from(stream);
+            assert_eq!(None, stream.chars_before(Sequence::Message));
         }
+
+        assert_eq!(
+            None,
+            StreamBuffer::from("abcabcabc").chars_before(Sequence::Packet)
+        );
+        assert_eq!(
+            Some(4),
+            StreamBuffer::from("éàèù").chars_before(Sequence::Packet)
+        );
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(6, |input| {
+            let stream = parse(input);
+            Ok((
+                part1(&stream).ok_or_else(|| anyhow::format_err!("no packet marker found"))?,
+                part2(&stream).ok_or_else(|| anyhow::format_err!("no message marker found"))?,
+            ))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_stream(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day6.dat")).unwrap();
+        b.iter(|| parse(&input));
+    }
+
+    #[bench]
+    fn message_marker(b: &mut Bencher) {
+        let stream =
+            parse(&read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day6.dat")).unwrap());
+        b.iter(|| part2(&stream));
     }
 }
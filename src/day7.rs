@@ -0,0 +1,289 @@
+use anyhow::Result;
+use day1::{cli::Args, examples, parse::ParseMode, span::SpanError};
+use std::{collections::HashMap, process::ExitCode};
+
+/// Total disk space.
+const DISK_SIZE: usize = 70_000_000;
+/// Free space needed to run the update.
+const UPDATE_SIZE: usize = 30_000_000;
+/// Maximum size of the directories summed up in part 1.
+const SMALL_DIR_SIZE: usize = 100_000;
+
+/// Directory of the filesystem.
+#[derive(Debug, Default)]
+struct Dir {
+    /// Index of the parent directory (`None` for the root).
+    parent: Option<usize>,
+    /// Subdirectories indices, by name.
+    dirs: HashMap<String, usize>,
+    /// File sizes, by name.
+    files: HashMap<String, usize>,
+}
+
+/// Terminal session line.
+#[derive(Debug, PartialEq, Eq)]
+enum Line<'a> {
+    /// `$ cd <dir>`
+    Cd(&'a str),
+    /// `$ ls`
+    Ls,
+    /// `dir <name>`
+    Dir(&'a str),
+    /// `<size> <name>`
+    File(usize, &'a str),
+}
+
+impl<'a> TryFrom<&'a str> for Line<'a> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let valid_name = |name: &'a str| match name.is_empty() || name.contains(['/', ' ']) {
+            true => anyhow::bail!("invalid name '{name}'"),
+            false => Ok(name),
+        };
+
+        Ok(match value.split_once(' ') {
+            Some(("$", "ls")) => Self::Ls,
+            Some(("$", command)) => match command.split_once(' ') {
+                Some(("cd", dir @ ("/" | ".."))) => Self::Cd(dir),
+                Some(("cd", dir)) => Self::Cd(valid_name(dir)?),
+                _ => anyhow::bail!("unknown command '{command}'"),
+            },
+            Some(("dir", name)) => Self::Dir(valid_name(name)?),
+            Some((size, name)) => Self::File(
+                size.parse()
+                    .map_err(|_| anyhow::format_err!("invalid file size '{size}'"))?,
+                valid_name(name)?,
+            ),
+            None => anyhow::bail!("expected command or directory listing"),
+        })
+    }
+}
+
+/// Filesystem tree explored by a terminal session.
+#[derive(Debug)]
+struct Filesystem {
+    /// Directories, the root first (parents always come before their subdirectories).
+    dirs: Vec<Dir>,
+}
+
+impl Filesystem {
+    /// Index of the root directory.
+    const ROOT: usize = 0;
+
+    /// Replay the terminal session lines, building the directory tree.
+    fn new(lines: &[Line]) -> Result<Self> {
+        let mut dirs: Vec<Dir> = vec![Dir::default()];
+        let mut cwd: usize = Self::ROOT;
+        // Whether the last command was `ls`, so its output can be listed.
+        let mut listing = false;
+
+        for line in lines {
+            match line {
+                Line::Cd("/") => cwd = Self::ROOT,
+                Line::Cd("..") => {
+                    cwd = dirs[cwd]
+                        .parent
+                        .ok_or_else(|| anyhow::format_err!("root directory has no parent"))?
+                }
+                Line::Cd(name) => {
+                    cwd = *dirs[cwd]
+                        .dirs
+                        .get(*name)
+                        .ok_or_else(|| anyhow::format_err!("no such directory '{name}'"))?
+                }
+                Line::Ls => {}
+                Line::Dir(_) | Line::File(..) if !listing => {
+                    anyhow::bail!("directory listing outside of 'ls' output")
+                }
+                Line::Dir(name) => {
+                    if !dirs[cwd].dirs.contains_key(*name) {
+                        let idx = dirs.len();
+                        dirs.push(Dir {
+                            parent: Some(cwd),
+                            ..Default::default()
+                        });
+                        dirs[cwd].dirs.insert(name.to_string(), idx);
+                    }
+                }
+                Line::File(size, name) => {
+                    // Listing the same directory twice doesn't count its files twice.
+                    dirs[cwd].files.insert(name.to_string(), *size);
+                }
+            }
+            listing = matches!(line, Line::Ls | Line::Dir(_) | Line::File(..));
+        }
+
+        // Directory sizes can't overflow if the total doesn't.
+        dirs.iter()
+            .flat_map(|dir| dir.files.values())
+            .try_fold(0usize, |total, size| total.checked_add(*size))
+            .ok_or_else(|| anyhow::format_err!("total size of the files overflows"))?;
+
+        Ok(Self { dirs })
+    }
+
+    /// Total size of each directory, including its subdirectories (by directory index).
+    fn sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = self
+            .dirs
+            .iter()
+            .map(|dir| dir.files.values().sum())
+            .collect();
+
+        // Subdirectories come after their parent: add them up bottom-up.
+        for (idx, dir) in self.dirs.iter().enumerate().rev() {
+            if let Some(parent) = dir.parent {
+                sizes[parent] += sizes[idx];
+            }
+        }
+
+        sizes
+    }
+}
+
+/// Parse the terminal session into the filesystem tree.
+fn parse(input: &str) -> Result<Filesystem> {
+    let lines = input
+        .lines()
+        .map(|line| Line::try_from(line).map_err(|e| SpanError::new(input, line, e).into()))
+        .collect::<Result<Vec<Line>>>()?;
+
+    Filesystem::new(&lines)
+}
+
+/// Sum of the total sizes of the directories of at most 100000.
+fn part1(fs: &Filesystem) -> usize {
+    fs.sizes()
+        .into_iter()
+        .filter(|size| *size <= SMALL_DIR_SIZE)
+        .sum()
+}
+
+/// Total size of the smallest directory that, if deleted, frees up enough space for the update.
+fn part2(fs: &Filesystem) -> usize {
+    let sizes = fs.sizes();
+    let needed = (sizes[Filesystem::ROOT] + UPDATE_SIZE).saturating_sub(DISK_SIZE);
+
+    // Deleting the root always frees up enough space.
+    sizes
+        .into_iter()
+        .filter(|size| *size >= needed)
+        .min()
+        .unwrap_or_default()
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&[examples::OPTION], &[ParseMode::FLAG])?;
+    let input = examples::input(&args, 7)?;
+    let fs = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+
+    // Part 1
+    println!(
+        "Sum of the total sizes of directories of at most {SMALL_DIR_SIZE}: {}",
+        part1(&fs)
+    );
+
+    // Part 2
+    println!(
+        "Total size of the smallest directory to delete: {}",
+        part2(&fs)
+    );
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn example_test() {
+        const INPUT: &str = r#"$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k"#;
+
+        let fs = parse(INPUT).unwrap();
+        let sizes = fs.sizes();
+        assert_eq!(4, sizes.len());
+        assert_eq!(48381165, sizes[Filesystem::ROOT]);
+        let size = |path: &[&str]| {
+            sizes[path
+                .iter()
+                .fold(Filesystem::ROOT, |idx, name| fs.dirs[idx].dirs[*name])]
+        };
+        assert_eq!(94853, size(&["a"]));
+        assert_eq!(584, size(&["a", "e"]));
+        assert_eq!(24933642, size(&["d"]));
+
+        assert_eq!(95437, part1(&fs));
+        assert_eq!(24933642, part2(&fs));
+
+        // Listing a directory again doesn't change its size.
+        let fs = parse(&format!("{INPUT}\n$ cd /\n$ ls\ndir a\n14848514 b.txt")).unwrap();
+        assert_eq!(48381165, fs.sizes()[Filesystem::ROOT]);
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "$ cd ..",
+            "$ cd a",
+            "$ ls\ndir a\n$ cd b",
+            "$ rm -rf /",
+            "$ ls\n12 a b",
+            "$ ls\n-12 a",
+            "$ ls\ndir",
+            "$ ls\ndir a/b",
+            "dir a",
+            "$ cd /\n12 a",
+            "$ ls\n\n12 a",
+            "$ ls\n18446744073709551616 a",
+            "$ ls\n18446744073709551615 a\ndir b\n$ cd b\n$ ls\n1 c",
+        ] {
+            assert!(parse(input).is_err(), "{input:?}");
+        }
+
+        // Empty sessions see an empty root only.
+        let fs = parse("").unwrap();
+        assert_eq!(0, part1(&fs));
+        assert_eq!(0, part2(&fs));
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(7, |input| {
+            let fs = parse(input)?;
+            Ok((part1(&fs), part2(&fs)))
+        });
+    }
+}
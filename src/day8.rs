@@ -1,176 +1,43 @@
-use anyhow::Result;
-use std::{fs, ops::Range, process::ExitCode};
-
-#[derive(Debug)]
-struct Forest {
-    rows: usize,
-    cols: usize,
-    matrix: Vec<usize>,
-}
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
 
-impl From<&str> for Forest {
-    fn from(s: &str) -> Self {
-        let mut i: usize = 1;
-
-        let matrix: Vec<usize> = s
-            .trim_end()
-            .chars()
-            .filter_map(|c| {
-                if c == '\n' {
-                    i += 1;
-                }
-
-                c.to_digit(10).map(|d| d as usize)
-            })
-            .collect();
-
-        Self {
-            rows: i,
-            cols: matrix.len() / i,
-            matrix,
-        }
+use anyhow::Result;
+use day1::{cli::Args, examples, forest::Forest, parse::ParseMode};
+use std::process::ExitCode;
+
+/// Parse the forest map and build its line-of-sight tables, optionally including the diagonal
+/// ones.
+fn parse(input: &str, diagonals: bool) -> Result<Forest> {
+    let mut forest = Forest::try_from(input)?;
+    if diagonals {
+        forest.include_diagonals();
     }
+    forest.preprocess();
+    Ok(forest)
 }
 
-impl Forest {
-    /// Get forest's matrix element value (returns None if indices are out of bounds).
-    fn element(&self, i: usize, j: usize) -> Option<usize> {
-        if i >= self.rows || j >= self.cols {
-            return None;
-        }
-
-        Some(self.matrix[i * self.cols + j])
-    }
-
-    /// Check wheter the tree at given position is at the edge of the forest or not.
-    fn is_edge(&self, i: usize, j: usize) -> bool {
-        i == 0 || i == self.cols - 1 || j == 0 || j == self.rows - 1
-    }
-
-    /// Check wheter the tree at given position is visible from at least one side.
-    fn is_visible(&self, i: usize, j: usize) -> bool {
-        // Edges are always visible.
-        if self.is_edge(i, j) {
-            return true;
-        }
-
-        let horizontal = |range: Range<usize>| -> bool {
-            let mut visible = true;
-            for k in range {
-                if self.element(i, k) >= self.element(i, j) {
-                    visible = false;
-                    break;
-                }
-            }
-
-            visible
-        };
-
-        let vertical = |range: Range<usize>| -> bool {
-            let mut visible = true;
-            for k in range {
-                if self.element(k, j) >= self.element(i, j) {
-                    visible = false;
-                    break;
-                }
-            }
-
-            visible
-        };
-
-        // Look left.
-        if horizontal(0..j) {
-            return true;
-        }
-
-        // Look right.
-        if horizontal(j + 1..self.cols) {
-            return true;
-        }
-
-        // Look up.
-        if vertical(0..i) {
-            return true;
-        }
-
-        // Look down.
-        if vertical(i + 1..self.rows) {
-            return true;
-        }
-
-        false
-    }
-
-    /// Count the number of visible trees (including edges).
-    fn count_visible_trees(&self) -> usize {
-        // Edges are always visible: save on iteration loops.
-        let mut count: usize = self.rows * 2 + (self.cols - 2) * 2;
-
-        // Loop only on inner trees.
-        for i in 1..self.rows - 1 {
-            for j in 1..self.cols - 1 {
-                if self.is_visible(i, j) {
-                    count += 1;
-                }
-            }
-        }
-
-        count
-    }
-
-    /// Calculate the tree's scenic score.
-    fn scenic_score(&self, i: usize, j: usize) -> usize {
-        let horizontal = |range: &[usize]| -> usize {
-            let mut score: usize = 0;
-            for k in range {
-                score += 1;
-                if self.element(i, *k) >= self.element(i, j) {
-                    break;
-                }
-            }
-            score
-        };
-
-        let vertical = |range: &[usize]| -> usize {
-            let mut score: usize = 0;
-            for k in range {
-                score += 1;
-                if self.element(*k, j) >= self.element(i, j) {
-                    break;
-                }
-            }
-            score
-        };
-
-        horizontal(&(0..j).rev().collect::<Vec<usize>>())
-            * horizontal(&(j + 1..self.cols).collect::<Vec<usize>>())
-            * vertical(&(0..i).rev().collect::<Vec<usize>>())
-            * vertical(&(i + 1..self.rows).collect::<Vec<usize>>())
-    }
-
-    /// Find the highest scenic score possible for any tree.
-    fn highest_score(&self) -> usize {
-        let mut scores: Vec<usize> = vec![];
-        for i in 0..self.rows {
-            for j in 0..self.cols {
-                scores.push(self.scenic_score(i, j));
-            }
-        }
+/// Number of trees visible from outside the forest.
+fn part1(forest: &Forest) -> usize {
+    forest.count_visible_trees()
+}
 
-        *scores.iter().max().unwrap()
-    }
+/// Highest scenic score of any tree.
+fn part2(forest: &Forest) -> usize {
+    forest.highest_score()
 }
 
 fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day8.dat")?;
-
-    let forest = Forest::from(input.as_str());
+    let args = Args::from_env(&[examples::OPTION], &["--diagonal", ParseMode::FLAG])?;
+    let input = examples::input(&args, 8)?;
+    let forest = parse(
+        &ParseMode::from_args(&args).normalize(&input),
+        args.flag("--diagonal"),
+    )?;
 
     // Part 1
-    println!("Number of visible trees: {}", forest.count_visible_trees());
+    println!("Number of visible trees: {}", part1(&forest));
 
     // Part 2
-    println!("Highest scenic score for any tree is: {}", forest.highest_score());
+    println!("Highest scenic score for any tree is: {}", part2(&forest));
 
     Ok(())
 }
@@ -196,26 +63,48 @@ mod test {
 33549
 35390"#;
 
-        let forest = Forest::from(INPUT);
-
-        assert_eq!(Some(5), forest.element(2, 1));
-        assert_eq!(Some(3), forest.element(2, 2));
-        assert_eq!(Some(4), forest.element(3, 3));
-        assert_eq!(Some(0), forest.element(4, 4));
-        assert_eq!(None, forest.element(6, 0));
-        assert_eq!(None, forest.element(1, 6));
-
-        assert!(forest.is_edge(0, 0));
-        assert!(forest.is_edge(forest.rows - 1, forest.cols - 1));
-        assert!(forest.is_edge(2, forest.cols - 1));
-        assert!(forest.is_edge(forest.rows - 1, 2));
-        assert!(!forest.is_edge(2, 2));
-        assert!(!forest.is_edge(3, 3));
-        assert!(!forest.is_edge(1, 2));
-
-        assert_eq!(21, forest.count_visible_trees());
-        assert_eq!(4, forest.scenic_score(1, 2));
-        assert_eq!(8, forest.scenic_score(3, 2));
-        assert_eq!(8, forest.highest_score());
+        let forest = parse(INPUT, false).unwrap();
+        assert_eq!(21, part1(&forest));
+        assert_eq!(8, part2(&forest));
+
+        let forest = parse(INPUT, true).unwrap();
+        assert_eq!(22, part1(&forest));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in ["", "12a\n456", "123\n45"] {
+            assert!(parse(input, false).is_err(), "{input:?}");
+        }
+
+        // Degenerate forests are made of edges only.
+        for (input, visible) in [("5", 1), ("12345", 5), ("1\n2\n3", 3), ("12\n34", 4)] {
+            let forest = parse(input, false).unwrap();
+            assert_eq!(visible, part1(&forest));
+            assert_eq!(0, part2(&forest));
+        }
+    }
+
+    #[test]
+    fn registered_examples() {
+        examples::check(8, |input| {
+            let forest = parse(input, false)?;
+            Ok((part1(&forest), part2(&forest)))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
+
+    #[bench]
+    fn parse_forest(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day8.dat")).unwrap();
+        b.iter(|| parse(&input, false).unwrap());
     }
 }
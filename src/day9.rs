@@ -1,5 +1,16 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
 use anyhow::Result;
-use std::{cmp::Ordering, fs, process::ExitCode};
+use day1::{
+    cli::Args,
+    direction::Direction,
+    examples,
+    geometry::Vec3,
+    parse::ParseMode,
+    span::SpanError,
+    trace::{TraceValue, Tracer},
+};
+use std::{cmp::Ordering, collections::HashSet, process::ExitCode};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Position {
@@ -12,13 +23,15 @@ impl Position {
         Self { x, y }
     }
 
+    /// Return the position as a `[x, y]` [`TraceValue`].
+    fn trace_value(&self) -> TraceValue {
+        TraceValue::from(vec![self.x, self.y])
+    }
+
     fn update_position(&mut self, direction: &Direction) {
-        match direction {
-            Direction::Up => self.y += 1,
-            Direction::Down => self.y -= 1,
-            Direction::Left => self.x -= 1,
-            Direction::Right => self.x += 1,
-        }
+        let (x, y) = direction.unit_vector();
+        self.x += x as i64;
+        self.y += y as i64;
     }
 }
 
@@ -75,9 +88,9 @@ impl NewRope {
         } else if self.same_col(i, i - 1) {
             // Tail moves on the same col to catch up or stays in place if head and tail overlap.
             match self.knots[i - 1].y.cmp(&self.knots[i].y) {
-                Ordering::Less => self.knots[i].update_position(&Direction::Down),
+                Ordering::Less => self.knots[i].update_position(&Direction::Up),
                 Ordering::Equal => {} // Head and tail overlap.
-                Ordering::Greater => self.knots[i].update_position(&Direction::Up),
+                Ordering::Greater => self.knots[i].update_position(&Direction::Down),
             }
         } else {
             // Tail moves diagonally to catch up.
@@ -87,15 +100,16 @@ impl NewRope {
                 Ordering::Equal => unreachable!(), // Head and tail can't overlap at this point.
             }
             match self.knots[i - 1].y.cmp(&self.knots[i].y) {
-                Ordering::Less => self.knots[i].update_position(&Direction::Down),
-                Ordering::Greater => self.knots[i].update_position(&Direction::Up),
+                Ordering::Less => self.knots[i].update_position(&Direction::Up),
+                Ordering::Greater => self.knots[i].update_position(&Direction::Down),
                 Ordering::Equal => unreachable!(), // Head and tail can't overlap at this point.
             }
         }
     }
 
-    /// Start movements.
-    fn start(&mut self, movements: &[Movement]) {
+    /// Start movements. If a [`Tracer`] is given, knots positions are recorded after each step.
+    fn start(&mut self, movements: &[Movement], mut tracer: Option<&mut Tracer>) -> Result<()> {
+        let mut step: usize = 0;
         for movement in movements {
             for _ in 0..movement.amount {
                 // Move head.
@@ -110,8 +124,16 @@ impl NewRope {
                         }
                     }
                 }
+
+                step += 1;
+                if let Some(tracer) = tracer.as_deref_mut() {
+                    let knots = self.knots.iter().map(Position::trace_value).collect();
+                    tracer.record("new_rope", step, &[("knots", TraceValue::List(knots))])?;
+                }
             }
         }
+
+        Ok(())
     }
 
     /// Count unique tail visited positions.
@@ -175,9 +197,9 @@ impl Rope {
         } else if self.head_tail_same_col() {
             // Tail moves on the same col to catch up or stays in place if head and tail overlap.
             match self.head.y.cmp(&self.tail.y) {
-                Ordering::Less => self.tail.update_position(&Direction::Down),
+                Ordering::Less => self.tail.update_position(&Direction::Up),
                 Ordering::Equal => {} // Head and tail overlap.
-                Ordering::Greater => self.tail.update_position(&Direction::Up),
+                Ordering::Greater => self.tail.update_position(&Direction::Down),
             }
         } else {
             // Tail moves diagonally to catch up.
@@ -187,15 +209,16 @@ impl Rope {
                 Ordering::Equal => unreachable!(), // Head and tail can't overlap at this point.
             }
             match self.head.y.cmp(&self.tail.y) {
-                Ordering::Less => self.tail.update_position(&Direction::Down),
-                Ordering::Greater => self.tail.update_position(&Direction::Up),
+                Ordering::Less => self.tail.update_position(&Direction::Up),
+                Ordering::Greater => self.tail.update_position(&Direction::Down),
                 Ordering::Equal => unreachable!(), // Head and tail can't overlap at this point.
             }
         }
     }
 
-    /// Start movements.
-    fn start(&mut self, movements: &[Movement]) {
+    /// Start movements. If a [`Tracer`] is given, knots positions are recorded after each step.
+    fn start(&mut self, movements: &[Movement], mut tracer: Option<&mut Tracer>) -> Result<()> {
+        let mut step: usize = 0;
         for movement in movements {
             for _ in 0..movement.amount {
                 // Move head.
@@ -206,8 +229,16 @@ impl Rope {
                     // Update tail position history.
                     self.tail_history.push(self.tail);
                 }
+
+                step += 1;
+                if let Some(tracer) = tracer.as_deref_mut() {
+                    let knots = vec![self.head.trace_value(), self.tail.trace_value()];
+                    tracer.record("rope", step, &[("knots", TraceValue::List(knots))])?;
+                }
             }
         }
+
+        Ok(())
     }
 
     /// Count unique tail visited positions.
@@ -223,77 +254,190 @@ impl Rope {
     }
 }
 
-/// Direction of the movement.
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// Head movement.
+#[derive(Debug, Clone)]
+struct Movement {
+    amount: usize,
+    direction: Direction,
 }
 
-impl TryFrom<&str> for Direction {
-    type Error = anyhow::Error;
+/// Split a movement line into its direction and amount tokens, parsing the amount. Errors point
+/// at the offending token of `value`.
+fn movement_tokens(value: &str) -> Result<(&str, usize), SpanError> {
+    let (direction, amount) = value
+        .split_once(' ')
+        .ok_or_else(|| SpanError::new(value, value, "expected direction and amount"))?;
+    let amount = amount
+        .parse()
+        .map_err(|_| SpanError::new(value, amount, format!("invalid amount '{amount}'")))?;
+
+    Ok((direction, amount))
+}
+
+impl TryFrom<&str> for Movement {
+    type Error = SpanError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Ok(match value {
-            "U" => Self::Up,
-            "D" => Self::Down,
-            "L" => Self::Left,
-            "R" => Self::Right,
-            d => anyhow::bail!("invalid direction '{d}'"),
+        let (direction, amount) = movement_tokens(value)?;
+
+        Ok(Self {
+            amount,
+            direction: Direction::try_from(direction)
+                .map_err(|e| SpanError::new(value, direction, e))?,
         })
     }
 }
 
-/// Head movement.
+/// Head movement in 3D space.
 #[derive(Debug, Clone)]
-struct Movement {
+struct Movement3 {
     amount: usize,
-    direction: Direction,
+    /// Unit vector of the direction.
+    direction: Vec3,
 }
 
-impl TryFrom<&str> for Movement {
-    type Error = anyhow::Error;
+impl TryFrom<&str> for Movement3 {
+    type Error = SpanError;
 
-    fn try_from(value: &str) -> Result<Self> {
-        let (direction, amount) = value
-            .split_once(' ')
-            .ok_or(anyhow::format_err!("badly formatted input"))?;
+    /// Parse `U`/`D`/`L`/`R` movements, plus `F`/`B` (forward/back) along the z axis.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (direction, amount) = movement_tokens(value)?;
 
         Ok(Self {
-            amount: amount
-                .parse()
-                .map_err(|_| anyhow::format_err!("invalid amount '{amount}'"))?,
-            direction: Direction::try_from(direction)?,
+            amount,
+            direction: match direction {
+                "F" => Vec3::new(0, 0, 1),
+                "B" => Vec3::new(0, 0, -1),
+                d => {
+                    let (x, y) = Direction::try_from(d)
+                        .map_err(|e| SpanError::new(value, d, e))?
+                        .unit_vector();
+                    Vec3::new(x as i64, y as i64, 0)
+                }
+            },
         })
     }
 }
 
-fn run() -> Result<()> {
-    let input = fs::read_to_string("./input/day9.dat")?;
-    let movements: Result<Vec<Movement>> = input
+/// Rope of any number of knots moving in 3D space (experimental).
+#[derive(Debug, Clone)]
+struct Rope3 {
+    knots: Vec<Vec3>,
+    /// Positions visited by the tail.
+    visited: HashSet<Vec3>,
+}
+
+impl Rope3 {
+    /// Construct a new instance with the given number of knots (at least one).
+    fn new(knots: usize) -> Result<Self> {
+        if knots == 0 {
+            anyhow::bail!("rope must have at least one knot");
+        }
+
+        Ok(Self {
+            knots: vec![Vec3::ZERO; knots],
+            visited: HashSet::from([Vec3::ZERO]), // Starting position is visited.
+        })
+    }
+
+    /// Start movements: each knot no longer touching (Chebyshev distance greater than 1) the
+    /// previous one steps towards it along every axis they differ on.
+    fn start(&mut self, movements: &[Movement3]) {
+        for movement in movements {
+            for _ in 0..movement.amount {
+                self.knots[0] += movement.direction;
+                for i in 1..self.knots.len() {
+                    if self.knots[i].chebyshev(&self.knots[i - 1]) > 1 {
+                        let step = (self.knots[i - 1] - self.knots[i]).signum();
+                        self.knots[i] += step;
+                    }
+                }
+                self.visited.insert(self.knots[self.knots.len() - 1]);
+            }
+        }
+    }
+}
+
+/// Parse the series of 3D head movements.
+fn parse3(input: &str) -> Result<Vec<Movement3>> {
+    input
         .lines()
-        .map(|line| -> Result<Movement> { Movement::try_from(line) })
-        .collect();
-    let movements = movements?;
+        .map(|line| -> Result<Movement3> {
+            Movement3::try_from(line).map_err(|e| e.within(input, line).into())
+        })
+        .collect()
+}
 
-    // Part 1
+/// Positions visited at least once by the tail of the rope of the given number of knots, in 3D
+/// space.
+fn visited3(movements: &[Movement3], knots: usize) -> Result<usize> {
+    let mut rope = Rope3::new(knots)?;
+    rope.start(movements);
+    Ok(rope.visited.len())
+}
+
+/// Parse the series of head movements.
+fn parse(input: &str) -> Result<Vec<Movement>> {
+    input
+        .lines()
+        .map(|line| -> Result<Movement> {
+            Movement::try_from(line).map_err(|e| e.within(input, line).into())
+        })
+        .collect()
+}
+
+/// Positions visited at least once by the tail of the two knots rope.
+fn part1(movements: &[Movement], tracer: Option<&mut Tracer>) -> Result<usize> {
     let mut rope = Rope::new();
-    rope.start(&movements);
+    rope.start(movements, tracer)?;
+    Ok(rope.unique_visited_positions())
+}
+
+/// Positions visited at least once by the tail of the ten knots rope.
+fn part2(movements: &[Movement], tracer: Option<&mut Tracer>) -> Result<usize> {
+    let mut new_rope = NewRope::new();
+    new_rope.start(movements, tracer)?;
+    Ok(new_rope.unique_visited_positions())
+}
+
+fn run() -> Result<()> {
+    let args = Args::from_env(
+        &[Tracer::OPTIONS[0], Tracer::OPTIONS[1], examples::OPTION],
+        &["--3d", ParseMode::FLAG],
+    )?;
+    let mut tracer = Tracer::from_args(&args)?;
+    let input = examples::input(&args, 9)?;
+
+    // Experimental 3D ropes.
+    if args.flag("--3d") {
+        let movements = parse3(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+        for knots in [2, KNOTS_NUM] {
+            println!(
+                "Unique tail visited positions in 3D ({knots} knots rope) are: {}",
+                visited3(&movements, knots)?
+            );
+        }
+        return Ok(());
+    }
+    let movements = parse(&ParseMode::from_args(&args).normalize_tokens(&input))?;
+
+    // Part 1
     println!(
         "Unique tail visited positions are: {}",
-        rope.unique_visited_positions()
+        part1(&movements, tracer.as_mut())?
     );
 
     // Part 2
-    let mut new_rope = NewRope::new();
-    new_rope.start(&movements);
     println!(
         "Unique tail visited position (10 knots rope) are: {}",
-        new_rope.unique_visited_positions()
+        part2(&movements, tracer.as_mut())?
     );
 
+    // Flush the trace, reporting any error writing it.
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
+    }
+
     Ok(())
 }
 
@@ -322,16 +466,8 @@ D 1
 L 5
 R 2"#;
 
-        let movements: Result<Vec<Movement>> = INPUT
-            .lines()
-            .map(|line| -> Result<Movement> { Movement::try_from(line) })
-            .collect();
-        let movements = movements.unwrap();
-
-        let mut rope = Rope::new();
-        rope.start(&movements);
-
-        assert_eq!(13, rope.unique_visited_positions());
+        let movements = parse(INPUT).unwrap();
+        assert_eq!(13, part1(&movements, None).unwrap());
 
         // Part 2
         const NEW_INPUT: &str = r#"R 5
@@ -343,15 +479,69 @@ D 10
 L 25
 U 20"#;
 
-        let movements: Result<Vec<Movement>> = NEW_INPUT
-            .lines()
-            .map(|line| -> Result<Movement> { Movement::try_from(line) })
-            .collect();
-        let movements = movements.unwrap();
+        let movements = parse(NEW_INPUT).unwrap();
+        assert_eq!(36, part2(&movements, None).unwrap());
+    }
+
+    #[test]
+    fn rope_3d() {
+        // Planar movements match the 2D ropes.
+        let movements = parse3("R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2").unwrap();
+        assert_eq!(13, visited3(&movements, 2).unwrap());
+        let movements = parse3("R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20").unwrap();
+        assert_eq!(36, visited3(&movements, KNOTS_NUM).unwrap());
+
+        // The tail catches up diagonally across the three axes.
+        let movements = parse3("R 1\nU 1\nF 2").unwrap();
+        let mut rope = Rope3::new(2).unwrap();
+        rope.start(&movements);
+        assert_eq!(Vec3::new(1, -1, 1), rope.knots[1]);
+        assert_eq!(2, rope.visited.len());
+
+        assert_eq!(5, visited3(&movements, 1).unwrap());
+        assert!(Rope3::new(0).is_err());
+        for line in ["F", "X 1", "F -1", "B a"] {
+            assert!(parse3(line).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for line in ["", "R", "R4", "R  4", "X 4", "RR 4", "R -4", "R a", "R 4 4"] {
+            assert!(Movement::try_from(line).is_err(), "{line}");
+        }
+
+        // Errors point at the offending token.
+        for (line, column, len) in [("X 4", 1, 1), ("RR 4", 1, 2), ("R a", 3, 1)] {
+            let error = Movement::try_from(line).unwrap_err();
+            assert_eq!(column, error.column(), "{line}");
+            assert!(error.to_string().ends_with(&"^".repeat(len)), "{line}");
+        }
+        let error = parse3("F 1\nB -1").unwrap_err();
+        let error = error.downcast_ref::<SpanError>().unwrap();
+        assert_eq!((2, 3), (error.line(), error.column()));
+    }
 
-        let mut new_rope = NewRope::new();
-        new_rope.start(&movements);
+    #[test]
+    fn registered_examples() {
+        examples::check(9, |input| {
+            let movements = parse(input)?;
+            Ok((part1(&movements, None)?, part2(&movements, None)?))
+        });
+    }
+}
+
+#[cfg(all(test, feature = "bench"))]
+mod bench {
+    extern crate test;
+
+    use super::*;
+    use day1::read_input;
+    use test::Bencher;
 
-        assert_eq!(36, new_rope.unique_visited_positions());
+    #[bench]
+    fn parse_movements(b: &mut Bencher) {
+        let input = read_input(concat!(env!("CARGO_MANIFEST_DIR"), "/input/day9.dat")).unwrap();
+        b.iter(|| parse(&input).unwrap());
     }
 }
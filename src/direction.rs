@@ -0,0 +1,220 @@
+//! Grid directions.
+//!
+//! Unit vectors follow screen coordinates: `x` grows to the right and `y` grows downwards.
+
+/// Cardinal direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All cardinal directions, clockwise starting from [`Direction::Up`].
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// Return the direction obtained turning 90° counterclockwise.
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Right => Self::Up,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+        }
+    }
+
+    /// Return the direction obtained turning 90° clockwise.
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    /// Return the opposite direction.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+        }
+    }
+
+    /// Return the `(x, y)` unit vector.
+    pub fn unit_vector(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        match value {
+            Direction::Up => Self::Up,
+            Direction::Right => Self::Right,
+            Direction::Down => Self::Down,
+            Direction::Left => Self::Left,
+        }
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    /// Parse both `U`/`R`/`D`/`L` and compass `N`/`E`/`S`/`W` notations.
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'U' | 'N' | '^' => Self::Up,
+            'R' | 'E' | '>' => Self::Right,
+            'D' | 'S' | 'v' => Self::Down,
+            'L' | 'W' | '<' => Self::Left,
+            d => anyhow::bail!("invalid direction '{d}'"),
+        })
+    }
+}
+
+impl TryFrom<&str> for Direction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Self::try_from(c),
+            _ => anyhow::bail!("invalid direction '{value}'"),
+        }
+    }
+}
+
+/// Cardinal or diagonal direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// All directions, clockwise starting from [`Direction8::Up`].
+    pub const ALL: [Direction8; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// Index of `self` in [`Direction8::ALL`].
+    fn idx(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|d| *d == self)
+            .unwrap_or_default()
+    }
+
+    /// Return the direction obtained turning 45° counterclockwise.
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self.idx() + 7) % 8]
+    }
+
+    /// Return the direction obtained turning 45° clockwise.
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self.idx() + 1) % 8]
+    }
+
+    /// Return the opposite direction.
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self.idx() + 4) % 8]
+    }
+
+    /// Return the `(x, y)` unit vector.
+    pub fn unit_vector(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::UpRight => (1, -1),
+            Self::Right => (1, 0),
+            Self::DownRight => (1, 1),
+            Self::Down => (0, 1),
+            Self::DownLeft => (-1, 1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, -1),
+        }
+    }
+}
+
+impl TryFrom<&str> for Direction8 {
+    type Error = anyhow::Error;
+
+    /// Parse compass notation (`N`, `NE`, `E`, ...).
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "N" => Self::Up,
+            "NE" => Self::UpRight,
+            "E" => Self::Right,
+            "SE" => Self::DownRight,
+            "S" => Self::Down,
+            "SW" => Self::DownLeft,
+            "W" => Self::Left,
+            "NW" => Self::UpLeft,
+            d => anyhow::bail!("invalid direction '{d}'"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rotations() {
+        for direction in Direction::ALL {
+            assert_eq!(direction, direction.turn_left().turn_right());
+            assert_eq!(direction.opposite(), direction.turn_right().turn_right());
+            assert_eq!(direction, direction.opposite().opposite());
+
+            let (x, y) = direction.unit_vector();
+            assert_eq!((-x, -y), direction.opposite().unit_vector());
+            assert_eq!(
+                direction.unit_vector(),
+                Direction8::from(direction).unit_vector()
+            );
+        }
+
+        for direction in Direction8::ALL {
+            assert_eq!(direction, direction.turn_left().turn_right());
+            assert_eq!(
+                direction.opposite(),
+                (0..4).fold(direction, |d, _| d.turn_right())
+            );
+        }
+
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+        assert_eq!(Direction8::UpRight, Direction8::Up.turn_right());
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Direction::Up, Direction::try_from("U").unwrap());
+        assert_eq!(Direction::Left, Direction::try_from('W').unwrap());
+        assert!(Direction::try_from("UU").is_err());
+        assert!(Direction::try_from("").is_err());
+        assert_eq!(Direction8::DownLeft, Direction8::try_from("SW").unwrap());
+        assert!(Direction8::try_from("X").is_err());
+    }
+}
@@ -0,0 +1,712 @@
+//! Puzzle examples, registered by day with their expected answers.
+//!
+//! Examples are selected with the `--example NAME` option of the day binaries, in place of the
+//! puzzle input, and every day tests all of its examples.
+
+use crate::{cli::Args, open_input, read_input, read_input_bytes};
+use anyhow::Result;
+use std::{fmt::Display, io::BufRead};
+
+/// Command line option selecting an example.
+pub const OPTION: &str = "--example";
+
+/// Puzzle example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub name: &'static str,
+    pub input: &'static str,
+    /// Expected answer of part 1, as printed.
+    pub part1: &'static str,
+    /// Expected answer of part 2, as printed.
+    pub part2: &'static str,
+}
+
+impl Example {
+    const fn new(
+        name: &'static str,
+        input: &'static str,
+        part1: &'static str,
+        part2: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            input,
+            part1,
+            part2,
+        }
+    }
+}
+
+const DAY1: &[Example] = &[
+    Example::new(
+        "small",
+        "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n",
+        "24000",
+        "45000",
+    ),
+    // Last Elf not followed by a blank line.
+    Example::new("edgecase", "100\n\n200\n300\n\n50", "500", "650"),
+];
+
+const DAY2: &[Example] = &[
+    Example::new("small", "A Y\nB X\nC Z\n", "15", "12"),
+    Example::new("draws", "A X\nB Y\nC Z\n", "15", "15"),
+];
+
+const DAY3: &[Example] = &[
+    Example::new(
+        "small",
+        r#"vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+"#,
+        "157",
+        "70",
+    ),
+    // Highest priority item everywhere.
+    Example::new("uppercase", "ZaZb\nZcZd\nZeZf\n", "156", "52"),
+];
+
+const DAY4: &[Example] = &[
+    Example::new(
+        "small",
+        "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n",
+        "2",
+        "4",
+    ),
+    // Identical and touching ranges.
+    Example::new("edgecase", "1-1,1-1\n1-2,3-4\n3-4,4-5\n", "1", "2"),
+];
+
+const DAY5: &[Example] = &[
+    Example::new(
+        "small",
+        concat!(
+            "    [D]    \n",
+            "[N] [C]    \n",
+            "[Z] [M] [P]\n",
+            " 1   2   3 \n",
+            "\n",
+            "move 1 from 2 to 1\n",
+            "move 3 from 1 to 3\n",
+            "move 2 from 2 to 1\n",
+            "move 1 from 1 to 2\n",
+        ),
+        "CMZ",
+        "MCD",
+    ),
+    // Crates moved back and forth between two stacks.
+    Example::new(
+        "edgecase",
+        concat!(
+            "[A]    \n",
+            "[B] [C]\n",
+            " 1   2 \n",
+            "\n",
+            "move 2 from 1 to 2\n",
+            "move 1 from 2 to 1\n",
+        ),
+        "BA",
+        "AB",
+    ),
+];
+
+const DAY6: &[Example] = &[
+    Example::new("small", "mjqjpqmgbljsphdztnvjfqwrcgsmlb", "7", "19"),
+    Example::new("stream2", "bvwbjplbgvbhsrlpgdmjqwftvncz", "5", "23"),
+    Example::new("stream3", "nppdvjthqldpwncqszvftbrmjlhg", "6", "23"),
+    Example::new("stream4", "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", "10", "29"),
+    Example::new("stream5", "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", "11", "26"),
+];
+
+const DAY7: &[Example] = &[
+    Example::new(
+        "small",
+        r#"$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k"#,
+        "95437",
+        "24933642",
+    ),
+    // Single small file, the smallest directory is deleted even if the space isn't needed.
+    Example::new("edgecase", "$ cd /\n$ ls\n100 a\n", "100", "100"),
+];
+
+const DAY8: &[Example] = &[
+    Example::new("small", "30373\n25512\n65332\n33549\n35390\n", "21", "8"),
+    // Single tree, on every edge.
+    Example::new("edgecase", "5\n", "1", "0"),
+];
+
+const DAY9: &[Example] = &[
+    Example::new(
+        "small",
+        "R 4\nU 4\nL 3\nD 1\nR 4\nD 1\nL 5\nR 2\n",
+        "13",
+        "1",
+    ),
+    Example::new(
+        "larger",
+        "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20\n",
+        "88",
+        "36",
+    ),
+];
+
+const DAY10: &[Example] = &[
+    Example::new(
+        "small",
+        r#"addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop"#,
+        "13140",
+        concat!(
+            "\n##..##..##..##..##..##..##..##..##..##..",
+            "\n###...###...###...###...###...###...###.",
+            "\n####....####....####....####....####....",
+            "\n#####.....#####.....#####.....#####.....",
+            "\n######......######......######......####",
+            "\n#######.......#######.......#######.....",
+        ),
+    ),
+    // Program over before the first signal strength is measured.
+    Example::new(
+        "short",
+        "addx 5\naddx -3\nnoop\n",
+        "0",
+        concat!(
+            "\n##..#...................................",
+            "\n........................................",
+            "\n........................................",
+            "\n........................................",
+            "\n........................................",
+            "\n........................................",
+        ),
+    ),
+];
+
+const DAY13: &[Example] = &[
+    Example::new(
+        "small",
+        r#"[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]
+"#,
+        "13",
+        "140",
+    ),
+    // Empty lists and a packet equal to a divider.
+    Example::new("edgecase", "[[]]\n[]\n\n[1]\n[[2]]\n", "2", "24"),
+];
+
+const DAY14: &[Example] = &[
+    Example::new(
+        "small",
+        "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n",
+        "24",
+        "93",
+    ),
+    // Sand piling up on a shelf under the source.
+    Example::new("shelf", "499,2 -> 501,2\n", "1", "12"),
+];
+
+/// Solved with `--row 10 --max 20`, the defaults with an example.
+const DAY15: &[Example] = &[
+    Example::new(
+        "small",
+        r#"Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3"#,
+        "26",
+        "56000011",
+    ),
+    // Distress beacon in a corner of the search area.
+    Example::new(
+        "corner",
+        r#"Sensor at x=10, y=10: closest beacon is at x=10, y=29
+Sensor at x=0, y=0: closest beacon is at x=0, y=1
+Sensor at x=20, y=0: closest beacon is at x=20, y=1
+Sensor at x=0, y=20: closest beacon is at x=0, y=19"#,
+        "39",
+        "80000020",
+    ),
+];
+
+const DAY16: &[Example] = &[
+    Example::new(
+        "small",
+        r#"Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II"#,
+        "1651",
+        "1707",
+    ),
+    // The elephant opens the valve you don't.
+    Example::new(
+        "twovalves",
+        r#"Valve AA has flow rate=0; tunnels lead to valves BB, CC
+Valve BB has flow rate=10; tunnel leads to valve AA
+Valve CC has flow rate=10; tunnel leads to valve AA"#,
+        "530",
+        "480",
+    ),
+];
+
+const DAY17: &[Example] = &[
+    Example::new(
+        "small",
+        ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>\n",
+        "3068",
+        "1514285714288",
+    ),
+    // Every rock pushed against the left wall.
+    Example::new("leftwall", "<\n", "4448", "2200000000000"),
+];
+
+const DAY19: &[Example] = &[
+    Example::new(
+        "small",
+        concat!(
+            "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. ",
+            "Each obsidian robot costs 3 ore and 14 clay. ",
+            "Each geode robot costs 2 ore and 7 obsidian.\n",
+            "Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. ",
+            "Each obsidian robot costs 3 ore and 8 clay. ",
+            "Each geode robot costs 3 ore and 12 obsidian.\n",
+        ),
+        "33",
+        "3472",
+    ),
+    // Fewer blueprints than the elephants left uneaten.
+    Example::new(
+        "single",
+        concat!(
+            "Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. ",
+            "Each obsidian robot costs 3 ore and 14 clay. ",
+            "Each geode robot costs 2 ore and 7 obsidian.\n",
+        ),
+        "9",
+        "56",
+    ),
+];
+
+const DAY20: &[Example] = &[
+    Example::new("small", "1\n2\n-3\n3\n-2\n0\n4\n", "3", "1623178306"),
+    Example::new("zero", "0\n", "0", "0"),
+];
+
+const DAY21: &[Example] = &[
+    Example::new(
+        "small",
+        r#"root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32"#,
+        "152",
+        "301",
+    ),
+    // You yell the number compared to yours.
+    Example::new("direct", "root: humn + abcd\nhumn: 1\nabcd: 5\n", "6", "5"),
+];
+
+const DAY22: &[Example] = &[
+    Example::new(
+        "small",
+        r#"        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5"#,
+        "6032",
+        "5031",
+    ),
+    // Walking around a cube of single tiles leads back to the start.
+    Example::new("tiny", " .\n...\n .\n .\n\n4R1\n", "2009", "2009"),
+];
+
+const DAY23: &[Example] = &[
+    Example::new(
+        "small",
+        r#"....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#.."#,
+        "110",
+        "20",
+    ),
+    Example::new(
+        "smaller",
+        ".....\n..##.\n..#..\n.....\n..##.\n.....\n",
+        "25",
+        "4",
+    ),
+];
+
+/// Examples registered for `day`.
+pub fn examples(day: u32) -> &'static [Example] {
+    match day {
+        1 => DAY1,
+        2 => DAY2,
+        3 => DAY3,
+        4 => DAY4,
+        5 => DAY5,
+        6 => DAY6,
+        7 => DAY7,
+        8 => DAY8,
+        9 => DAY9,
+        10 => DAY10,
+        13 => DAY13,
+        14 => DAY14,
+        15 => DAY15,
+        16 => DAY16,
+        17 => DAY17,
+        19 => DAY19,
+        20 => DAY20,
+        21 => DAY21,
+        22 => DAY22,
+        23 => DAY23,
+        _ => &[],
+    }
+}
+
+/// Example of `day` with the given name.
+pub fn example(day: u32, name: &str) -> Result<&'static Example> {
+    let examples = examples(day);
+    examples
+        .iter()
+        .find(|example| example.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = examples.iter().map(|example| example.name).collect();
+            anyhow::format_err!(
+                "no example '{name}' for day {day} (available: {})",
+                names.join(", ")
+            )
+        })
+}
+
+/// Example of `day` selected with the `--example NAME` option, or `None` if not given.
+pub fn from_args(args: &Args, day: u32) -> Result<Option<&'static Example>> {
+    args.value(OPTION)
+        .map(|name| example(day, name))
+        .transpose()
+}
+
+/// Input of the example of `day` selected with `--example NAME`, or else of the puzzle input
+/// of `day` (see [`read_input`]).
+pub fn input(args: &Args, day: u32) -> Result<String> {
+    Ok(match from_args(args, day)? {
+        Some(example) => example.input.to_string(),
+        None => read_input(format!("./input/day{day}.dat"))?,
+    })
+}
+
+/// Like [`input`], for inputs which may not be valid UTF-8 (see [`read_input_bytes`]).
+pub fn input_bytes(args: &Args, day: u32) -> Result<Vec<u8>> {
+    Ok(match from_args(args, day)? {
+        Some(example) => example.input.as_bytes().to_vec(),
+        None => read_input_bytes(format!("./input/day{day}.dat"))?,
+    })
+}
+
+/// Like [`input`], for buffered reading (see [`open_input`]).
+pub fn open(args: &Args, day: u32) -> Result<Box<dyn BufRead>> {
+    Ok(match from_args(args, day)? {
+        Some(example) => Box::new(example.input.as_bytes()),
+        None => open_input(format!("./input/day{day}.dat"))?,
+    })
+}
+
+/// Check the answers of `solve` against every example of `day`, panicking on the first mismatch.
+///
+/// Shared by the tests of the day binaries, `solve` returns the answers of both parts.
+pub fn check<P1: Display, P2: Display>(day: u32, solve: impl Fn(&str) -> Result<(P1, P2)>) {
+    for example in examples(day) {
+        let (part1, part2) = solve(example.input)
+            .unwrap_or_else(|e| panic!("day {day} example '{}': {e}", example.name));
+        assert_eq!(
+            (example.part1, example.part2),
+            (part1.to_string().as_str(), part2.to_string().as_str()),
+            "day {day} example '{}'",
+            example.name
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        for day in 1..=25 {
+            let examples = examples(day);
+            for (idx, registered) in examples.iter().enumerate() {
+                assert!(!registered.name.is_empty(), "day {day}");
+                assert!(
+                    examples[idx + 1..]
+                        .iter()
+                        .all(|other| other.name != registered.name),
+                    "day {day}: duplicate example '{}'",
+                    registered.name
+                );
+                assert_eq!(Some(registered), example(day, registered.name).ok());
+            }
+        }
+
+        assert_eq!("24000", example(1, "small").unwrap().part1);
+        assert_eq!(
+            "no example 'huge' for day 1 (available: small, edgecase)",
+            example(1, "huge").unwrap_err().to_string()
+        );
+        assert!(example(11, "small").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "day 1 example 'small'")]
+    fn check_mismatch() {
+        check(1, |input| Ok((input.lines().count(), 0)));
+    }
+
+    #[test]
+    fn selection() {
+        let args = |args: &[&str]| {
+            Args::parse(args.iter().map(|arg| arg.to_string()), &[OPTION], &[]).unwrap()
+        };
+        assert_eq!(
+            "A Y\nB X\nC Z\n",
+            input(&args(&["--example", "small"]), 2).unwrap()
+        );
+        assert_eq!(None, from_args(&args(&[]), 2).unwrap());
+        assert!(input(&args(&["--example=huge"]), 2).is_err());
+
+        let mut lines = open(&args(&["--example", "edgecase"]), 1).unwrap().lines();
+        assert_eq!("100", lines.next().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,375 @@
+//! Tree heights of the forest (day 8).
+
+use crate::direction::Direction8;
+use anyhow::Result;
+
+/// Map of the tree heights, answering visibility and scenic score queries about its trees.
+#[derive(Debug)]
+pub struct Forest {
+    rows: usize,
+    cols: usize,
+    /// Tree heights, row by row.
+    matrix: Vec<usize>,
+    /// Directions trees are looked at from.
+    directions: Vec<Direction8>,
+    /// Line-of-sight tables, once built by [`Forest::preprocess`].
+    sightlines: Option<Sightlines>,
+}
+
+impl TryFrom<&str> for Forest {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let mut rows: usize = 0;
+        let mut cols: usize = 0;
+        let mut matrix: Vec<usize> = vec![];
+
+        for line in s.trim_end().lines() {
+            for c in line.chars() {
+                matrix.push(
+                    c.to_digit(10)
+                        .ok_or_else(|| anyhow::format_err!("invalid tree height '{c}'"))?
+                        as usize,
+                );
+            }
+
+            rows += 1;
+            if rows == 1 {
+                cols = matrix.len();
+            }
+            if cols == 0 || matrix.len() != rows * cols {
+                anyhow::bail!("forest rows must all have the same non-zero length");
+            }
+        }
+
+        if rows == 0 {
+            anyhow::bail!("forest must contain at least one tree");
+        }
+
+        Ok(Self {
+            rows,
+            cols,
+            matrix,
+            directions: vec![
+                Direction8::Left,
+                Direction8::Right,
+                Direction8::Up,
+                Direction8::Down,
+            ],
+            sightlines: None,
+        })
+    }
+}
+
+impl Forest {
+    /// Number of rows of trees.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns of trees.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get forest's matrix element value (returns None if indices are out of bounds).
+    pub fn element(&self, i: usize, j: usize) -> Option<usize> {
+        if i >= self.rows || j >= self.cols {
+            return None;
+        }
+
+        Some(self.matrix[i * self.cols + j])
+    }
+
+    /// Check wheter the tree at given position is at the edge of the forest or not.
+    fn is_edge(&self, i: usize, j: usize) -> bool {
+        i == 0 || i == self.rows - 1 || j == 0 || j == self.cols - 1
+    }
+
+    /// Position next to the given one in the given direction, if inside the forest.
+    fn step(&self, i: usize, j: usize, direction: Direction8) -> Option<(usize, usize)> {
+        let (x, y) = direction.unit_vector();
+        let i = i.checked_add_signed(y).filter(|i| *i < self.rows)?;
+        let j = j.checked_add_signed(x).filter(|j| *j < self.cols)?;
+        Some((i, j))
+    }
+
+    /// Iterate over the heights of the trees seen from the given position looking in the given
+    /// direction, up to the edge.
+    fn sightline(
+        &self,
+        i: usize,
+        j: usize,
+        direction: Direction8,
+    ) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.step(i, j, direction), move |(i, j)| {
+            self.step(*i, *j, direction)
+        })
+        .map_while(|(i, j)| self.element(i, j))
+    }
+
+    /// Include the four diagonal sightlines in visibility and scenic score computation.
+    pub fn include_diagonals(&mut self) {
+        self.directions = Direction8::ALL.to_vec();
+        // Tables of the cardinal directions only are now stale.
+        self.sightlines = None;
+    }
+
+    /// Check wheter the tree at given position is visible from at least one side (returns None
+    /// if indices are out of bounds).
+    pub fn is_visible(&self, i: usize, j: usize) -> Option<bool> {
+        let height = self.element(i, j)?;
+
+        // Edges are always visible.
+        if self.is_edge(i, j) {
+            return Some(true);
+        }
+
+        if let Some(sightlines) = &self.sightlines {
+            let idx = i * self.cols + j;
+            return Some(
+                sightlines
+                    .tallest
+                    .iter()
+                    .any(|tallest| tallest[idx].is_none_or(|h| h < height)),
+            );
+        }
+
+        Some(
+            self.directions
+                .iter()
+                .any(|direction| self.sightline(i, j, *direction).all(|h| h < height)),
+        )
+    }
+
+    /// Count the number of visible trees (including edges).
+    pub fn count_visible_trees(&self) -> usize {
+        // Forests thinner than 3 trees are made of edges only.
+        if self.rows < 3 || self.cols < 3 {
+            return self.rows * self.cols;
+        }
+
+        // Edges are always visible: save on iteration loops.
+        let mut count: usize = self.rows * 2 + (self.cols - 2) * 2;
+
+        // Loop only on inner trees.
+        for i in 1..self.rows - 1 {
+            for j in 1..self.cols - 1 {
+                if self.is_visible(i, j) == Some(true) {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Calculate the tree's scenic score (returns None if indices are out of bounds).
+    pub fn scenic_score(&self, i: usize, j: usize) -> Option<usize> {
+        let height = self.element(i, j)?;
+
+        if let Some(sightlines) = &self.sightlines {
+            let idx = i * self.cols + j;
+            return Some(
+                sightlines
+                    .distance
+                    .iter()
+                    .map(|distance| distance[idx])
+                    .product(),
+            );
+        }
+
+        let score = self
+            .directions
+            .iter()
+            .map(|direction| {
+                let mut distance: usize = 0;
+                for h in self.sightline(i, j, *direction) {
+                    distance += 1;
+                    if h >= height {
+                        break;
+                    }
+                }
+                distance
+            })
+            .product();
+
+        Some(score)
+    }
+
+    /// Find the highest scenic score possible for any tree.
+    pub fn highest_score(&self) -> usize {
+        let mut scores: Vec<usize> = vec![];
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                scores.extend(self.scenic_score(i, j));
+            }
+        }
+
+        // Forest contains at least one tree (see `Forest::try_from`).
+        scores.into_iter().max().unwrap_or_default()
+    }
+
+    /// Build the line-of-sight tables of every direction, after which [`Forest::is_visible`]
+    /// and [`Forest::scenic_score`] answer in constant time.
+    pub fn preprocess(&mut self) {
+        let mut sightlines = Sightlines {
+            tallest: vec![vec![None; self.matrix.len()]; self.directions.len()],
+            distance: vec![vec![0; self.matrix.len()]; self.directions.len()],
+        };
+
+        for (k, direction) in self.directions.iter().enumerate() {
+            // Lines start from the trees at the edge the trees look towards.
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    if self.step(i, j, *direction).is_some() {
+                        continue;
+                    }
+
+                    let line = std::iter::successors(Some((i, j)), |(i, j)| {
+                        self.step(*i, *j, direction.opposite())
+                    })
+                    .map(|(i, j)| i * self.cols + j);
+                    sightlines.scan(k, &self.matrix, line);
+                }
+            }
+        }
+
+        self.sightlines = Some(sightlines);
+    }
+}
+
+/// Line-of-sight tables of a [`Forest`], per direction.
+#[derive(Debug)]
+struct Sightlines {
+    /// Tallest tree between each tree and the edge.
+    tallest: Vec<Vec<Option<usize>>>,
+    /// Viewing distance of each tree.
+    distance: Vec<Vec<usize>>,
+}
+
+impl Sightlines {
+    /// Fill the tables of the given direction, walking a line of trees (matrix indices) starting
+    /// from the edge the trees look towards.
+    fn scan<I>(&mut self, direction: usize, matrix: &[usize], line: I)
+    where
+        I: Iterator<Item = usize>,
+    {
+        let mut tallest: Option<usize> = None;
+        // Trees not hidden yet by a taller one, as (position, height): heights are decreasing.
+        let mut blocking: Vec<(usize, usize)> = vec![];
+
+        for (position, idx) in line.enumerate() {
+            let height = matrix[idx];
+            self.tallest[direction][idx] = tallest;
+            tallest = tallest.max(Some(height));
+
+            while blocking.last().is_some_and(|(_, h)| *h < height) {
+                blocking.pop();
+            }
+            self.distance[direction][idx] = match blocking.last() {
+                Some((p, _)) => position - p,
+                None => position,
+            };
+            blocking.push((position, height));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"30373
+25512
+65332
+33549
+35390"#;
+
+    #[test]
+    fn queries() {
+        let mut forest = Forest::try_from(INPUT).unwrap();
+        forest.preprocess();
+
+        assert_eq!(Some(5), forest.element(2, 1));
+        assert_eq!(Some(3), forest.element(2, 2));
+        assert_eq!(Some(4), forest.element(3, 3));
+        assert_eq!(Some(0), forest.element(4, 4));
+        assert_eq!(None, forest.element(6, 0));
+        assert_eq!(None, forest.element(1, 6));
+
+        assert!(forest.is_edge(0, 0));
+        assert!(forest.is_edge(forest.rows() - 1, forest.cols() - 1));
+        assert!(forest.is_edge(2, forest.cols() - 1));
+        assert!(forest.is_edge(forest.rows() - 1, 2));
+        assert!(!forest.is_edge(2, 2));
+        assert!(!forest.is_edge(3, 3));
+        assert!(!forest.is_edge(1, 2));
+
+        assert_eq!(21, forest.count_visible_trees());
+        assert_eq!(Some(4), forest.scenic_score(1, 2));
+        assert_eq!(Some(8), forest.scenic_score(3, 2));
+        assert_eq!(8, forest.highest_score());
+
+        // Preprocessed queries match walking the sightlines.
+        let walking = Forest::try_from(INPUT).unwrap();
+        assert!(forest.sightlines.is_some() && walking.sightlines.is_none());
+        for i in 0..forest.rows() {
+            for j in 0..forest.cols() {
+                assert_eq!(walking.is_visible(i, j), forest.is_visible(i, j));
+                assert_eq!(walking.scenic_score(i, j), forest.scenic_score(i, j));
+            }
+        }
+        assert_eq!(21, walking.count_visible_trees());
+        assert_eq!(8, walking.highest_score());
+
+        // Positions outside the forest.
+        for forest in [&forest, &walking] {
+            for (i, j) in [(5, 0), (0, 5), (usize::MAX, usize::MAX)] {
+                assert_eq!(None, forest.is_visible(i, j));
+                assert_eq!(None, forest.scenic_score(i, j));
+            }
+        }
+    }
+
+    #[test]
+    fn diagonal_sightlines() {
+        let mut forest = Forest::try_from(INPUT).unwrap();
+        forest.include_diagonals();
+        let mut walking = Forest::try_from(INPUT).unwrap();
+        walking.include_diagonals();
+        forest.preprocess();
+        for i in 0..forest.rows() {
+            for j in 0..forest.cols() {
+                assert_eq!(walking.is_visible(i, j), forest.is_visible(i, j));
+                assert_eq!(walking.scenic_score(i, j), forest.scenic_score(i, j));
+            }
+        }
+
+        // The 4 at (3, 3) is hidden from the sides but visible through the up-right diagonal.
+        assert_eq!(
+            Some(false),
+            Forest::try_from(INPUT).unwrap().is_visible(3, 3)
+        );
+        assert_eq!(Some(true), forest.is_visible(3, 3));
+        assert_eq!(22, forest.count_visible_trees());
+        // Looking at the diagonals, the middle 5 in the second row sees 2 trees down-right and
+        // 1 tree in the other diagonal directions.
+        assert_eq!(Some(4 * 2), forest.scenic_score(1, 2));
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for input in [
+            "",
+            "\n",
+            "12a\n456",
+            "123\n45",
+            "12\n345",
+            "1234\n12\n12",
+            "1\n\n2",
+        ] {
+            assert!(Forest::try_from(input).is_err(), "{input:?}");
+        }
+    }
+}
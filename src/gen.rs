@@ -0,0 +1,33 @@
+use anyhow::Result;
+use day1::{cli::Args, generators};
+use std::{fs, process::ExitCode};
+
+fn run() -> Result<()> {
+    let args = Args::from_env(&["--day", "--size", "--seed", "--density", "--output"], &[])?;
+    let day = args
+        .parse_value("--day")?
+        .ok_or_else(|| anyhow::format_err!("missing `--day N` option"))?;
+    let size = args.parse_value("--size")?.unwrap_or(1000);
+    let seed = args.parse_value("--seed")?.unwrap_or(0);
+
+    let input = match args.parse_value("--density")? {
+        Some(density) if day == 14 => generators::cave(size, density, seed)?,
+        Some(_) => anyhow::bail!("`--density` is only supported by day 14"),
+        None => generators::generate(day, size, seed)?,
+    };
+    match args.value("--output") {
+        Some(path) => fs::write(path, input)?,
+        None => print!("{input}"),
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,369 @@
+//! Synthetic puzzle input generators, producing valid inputs of arbitrary size.
+
+use crate::rng::Rng;
+use anyhow::Result;
+use std::fmt::Write;
+
+/// Days supported by [`generate`].
+pub const DAYS: [u8; 9] = [1, 2, 3, 4, 5, 6, 8, 9, 14];
+
+/// Generate a random input for `day` of the given `size`.
+///
+/// The meaning of `size` depends on the day (number of elves, turns, rucksacks, ...), the same
+/// `seed` always produces the same input.
+pub fn generate(day: u8, size: usize, seed: u64) -> Result<String> {
+    let mut rng = Rng::new(seed);
+
+    Ok(match day {
+        1 => day1(size, &mut rng),
+        2 => day2(size, &mut rng),
+        3 => day3(size, &mut rng),
+        4 => day4(size, &mut rng),
+        5 => day5(size, &mut rng),
+        6 => day6(size, &mut rng),
+        8 => day8(size, &mut rng),
+        9 => day9(size, &mut rng),
+        14 => day14(size, CAVE_DENSITY, &mut rng),
+        d => anyhow::bail!("no input generator for day {d} (supported days: {DAYS:?})"),
+    })
+}
+
+/// Item types of the rucksacks, sorted by priority.
+const ITEMS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Shuffle `items` in place (Fisher-Yates).
+fn shuffle<T>(items: &mut [T], rng: &mut Rng) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i as u64 + 1) as usize);
+    }
+}
+
+/// `size` elves carrying 1 to 15 food items each.
+fn day1(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        for _ in 0..rng.range(1, 15) {
+            let _ = writeln!(input, "{}", rng.range(1000, 70000));
+        }
+        input.push('\n');
+    }
+
+    input
+}
+
+/// `size` strategy guide turns.
+fn day2(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let _ = writeln!(
+            input,
+            "{} {}",
+            (b'A' + rng.below(3) as u8) as char,
+            (b'X' + rng.below(3) as u8) as char
+        );
+    }
+
+    input
+}
+
+/// `size` rucksacks (rounded up to a multiple of 3), each with exactly one item shared by its
+/// compartments, and groups of 3 sharing exactly one badge.
+fn day3(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size.div_ceil(3) {
+        let mut items: Vec<char> = ITEMS.chars().collect();
+        shuffle(&mut items, rng);
+        let badge = items[0];
+
+        // Disjoint item pools for each rucksack of the group, so the badge is the only item
+        // they have in common.
+        for pool in items[1..].chunks(items.len() / 3).take(3) {
+            let (shared, pool) = (pool[0], &pool[1..]);
+            let (left, right) = pool.split_at(pool.len() / 2);
+            let len = rng.range(4, 16) as usize;
+
+            let mut compartments = [vec![shared], vec![shared]];
+            compartments[rng.below(2) as usize].push(badge);
+            for (compartment, pool) in compartments.iter_mut().zip([left, right]) {
+                while compartment.len() < len {
+                    compartment.push(*rng.choose(pool).unwrap_or(&shared));
+                }
+                shuffle(compartment, rng);
+                input.extend(compartment.iter());
+            }
+            input.push('\n');
+        }
+    }
+
+    input
+}
+
+/// `size` pairs of section assignments in `1..=99`.
+fn day4(size: usize, rng: &mut Rng) -> String {
+    let range = |rng: &mut Rng| {
+        let min = rng.range(1, 99);
+        (min, rng.range(min, 99))
+    };
+
+    let mut input = String::new();
+    for _ in 0..size {
+        let (a, b) = (range(rng), range(rng));
+        let _ = writeln!(input, "{}-{},{}-{}", a.0, a.1, b.0, b.1);
+    }
+
+    input
+}
+
+/// Storage of `size / 500` stacks (at least 9) followed by a procedure of `size` moves, each
+/// moving at most as many crates as the origin stack holds.
+fn day5(size: usize, rng: &mut Rng) -> String {
+    let stacks = (size / 500).max(9);
+    let mut heights: Vec<usize> = (0..stacks).map(|_| rng.range(1, 50) as usize).collect();
+
+    // Every stack must have a crate on the bottom row, so that stack positions are preserved.
+    let mut input = String::new();
+    for row in (0..heights.iter().copied().max().unwrap_or_default()).rev() {
+        let crates: Vec<String> = heights
+            .iter()
+            .map(|height| match row < *height {
+                true => format!("[{}]", (b'A' + rng.below(26) as u8) as char),
+                false => "   ".to_string(),
+            })
+            .collect();
+        let _ = writeln!(input, "{}", crates.join(" ").trim_end());
+    }
+    let labels: Vec<String> = (1..=stacks).map(|n| format!("{n:^3}")).collect();
+    let _ = writeln!(input, "{}\n", labels.join(" "));
+
+    for _ in 0..size {
+        let origin = loop {
+            let origin = rng.below(stacks as u64) as usize;
+            if heights[origin] > 0 {
+                break origin;
+            }
+        };
+        let destination = (origin + 1 + rng.below(stacks as u64 - 1) as usize) % stacks;
+        let amount = rng.range(1, heights[origin].min(30) as i64) as usize;
+
+        heights[origin] -= amount;
+        heights[destination] += amount;
+        let _ = writeln!(
+            input,
+            "move {amount} from {} to {}",
+            origin + 1,
+            destination + 1
+        );
+    }
+
+    input
+}
+
+/// Datastream of `size` characters (at least 14) whose start-of-message marker is at the very
+/// end: the rest of the stream uses only 3 distinct characters.
+fn day6(size: usize, rng: &mut Rng) -> String {
+    let mut letters: Vec<char> = ITEMS[..26].chars().collect();
+    shuffle(&mut letters, rng);
+
+    let mut input: String = (0..size.saturating_sub(14))
+        .map(|_| letters[rng.below(3) as usize])
+        .collect();
+    input.extend(letters[..14].iter());
+    input.push('\n');
+
+    input
+}
+
+/// Square forest of `size`x`size` trees.
+fn day8(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        input.extend((0..size).map(|_| (b'0' + rng.below(10) as u8) as char));
+        input.push('\n');
+    }
+
+    input
+}
+
+/// `size` head movements of 1 to 20 steps.
+fn day9(size: usize, rng: &mut Rng) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let direction = rng.choose(&['U', 'D', 'L', 'R']).unwrap_or(&'U');
+        let _ = writeln!(input, "{direction} {}", rng.range(1, 20));
+    }
+
+    input
+}
+
+/// Default density of the day14 rock formations.
+pub const CAVE_DENSITY: f64 = 0.5;
+
+/// Generate a day14 cave of the given `depth`, where `density` (in `0.0..=1.0`) is the
+/// probability of placing each rock formation.
+pub fn cave(depth: usize, density: f64, seed: u64) -> Result<String> {
+    if !(0.0..=1.0).contains(&density) {
+        anyhow::bail!("density must be in 0.0..=1.0, got {density}");
+    }
+
+    Ok(day14(depth, density, &mut Rng::new(seed)))
+}
+
+/// Cave with rock formations down to `depth` (clamped to `4..=490`, so that the sand pile
+/// resting on the floor never reaches x = 0), laid out in layers of cups and fractal ledges.
+fn day14(depth: usize, density: f64, rng: &mut Rng) -> String {
+    let depth = depth.clamp(4, 490) as i64;
+    let mut paths = Vec::new();
+
+    let mut y = 2;
+    while y < depth {
+        // Only the area below the source within the sand pile's slope can be reached.
+        let mut x = 500 - y;
+        while x < 500 + y {
+            let width = rng.range(3, 12);
+            // Keep the source column clear in the upper third, so sand reaches the lower layers.
+            let clear = y < depth / 3 && (x..=x + width).contains(&500);
+            if !clear && rng.chance(density) {
+                if rng.chance(0.5) {
+                    let bottom = (y + rng.range(2, 6)).min(depth);
+                    paths.push(vec![
+                        (x, y),
+                        (x, bottom),
+                        (x + width, bottom),
+                        (x + width, y),
+                    ]);
+                } else {
+                    ledges(
+                        &mut paths,
+                        (x + width / 2, y),
+                        width / 2,
+                        depth,
+                        density,
+                        rng,
+                    );
+                }
+            }
+            x += width + rng.range(2, 8);
+        }
+        y += rng.range(6, 14);
+    }
+
+    let mut input = String::new();
+    for path in paths {
+        let points: Vec<String> = path.iter().map(|(x, y)| format!("{x},{y}")).collect();
+        let _ = writeln!(input, "{}", points.join(" -> "));
+    }
+
+    input
+}
+
+/// Fractal formation: a ledge centered at `center`, with two smaller formations hanging below
+/// its ends.
+fn ledges(
+    paths: &mut Vec<Vec<(i64, i64)>>,
+    center: (i64, i64),
+    half_width: i64,
+    depth: i64,
+    density: f64,
+    rng: &mut Rng,
+) {
+    let (x, y) = center;
+    paths.push(vec![(x - half_width, y), (x + half_width, y)]);
+
+    let below = y + half_width + 1;
+    if half_width < 2 || below > depth {
+        return;
+    }
+    for x in [x - half_width, x + half_width] {
+        if rng.chance(density) {
+            ledges(paths, (x, below), half_width / 2, depth, density, rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        for day in DAYS {
+            let input = generate(day, 30, 42).unwrap();
+            assert_eq!(input, generate(day, 30, 42).unwrap());
+            assert_ne!(input, generate(day, 30, 43).unwrap());
+        }
+
+        assert!(generate(7, 10, 0).is_err());
+    }
+
+    #[test]
+    fn valid_inputs() {
+        let input = generate(1, 100, 1).unwrap();
+        assert_eq!(100, input.matches("\n\n").count());
+
+        let input = generate(3, 100, 1).unwrap();
+        let rucksacks: Vec<&str> = input.lines().collect();
+        assert_eq!(102, rucksacks.len());
+        for rucksack in &rucksacks {
+            let (left, right) = rucksack.split_at(rucksack.len() / 2);
+            assert_eq!(left.len(), right.len());
+            let shared: Vec<char> = left.chars().filter(|c| right.contains(*c)).collect();
+            assert!(shared.windows(2).all(|w| w[0] == w[1]), "{rucksack}");
+            assert!(!shared.is_empty());
+        }
+        for group in rucksacks.chunks(3) {
+            let badges = ITEMS
+                .chars()
+                .filter(|c| group.iter().all(|rucksack| rucksack.contains(*c)))
+                .count();
+            assert_eq!(1, badges);
+        }
+
+        let input = generate(5, 5000, 1).unwrap();
+        let (storage, procedure) = input.split_once("\n\n").unwrap();
+        let mut heights = [0; 10];
+        for row in storage.lines() {
+            for (idx, c) in row.chars().skip(1).step_by(4).enumerate() {
+                heights[idx] += c.is_ascii_uppercase() as usize;
+            }
+        }
+        assert!(heights.iter().all(|height| *height > 0));
+        assert_eq!(5000, procedure.lines().count());
+        for line in procedure.lines() {
+            let values: Vec<usize> = line
+                .split(' ')
+                .filter_map(|word| word.parse().ok())
+                .collect();
+            let (amount, origin, destination) = (values[0], values[1] - 1, values[2] - 1);
+            assert!(amount <= heights[origin] && origin != destination);
+            heights[origin] -= amount;
+            heights[destination] += amount;
+        }
+
+        let input = generate(6, 1000, 1).unwrap();
+        assert_eq!(1001, input.len());
+
+        let input = generate(8, 50, 1).unwrap();
+        assert!(input.lines().all(|line| line.len() == 50));
+
+        for (depth, density) in [(0, 0.5), (100, 1.0), (1000, 0.2)] {
+            let input = cave(depth, density, 1).unwrap();
+            assert!(!input.is_empty());
+            for path in input.lines() {
+                let points: Vec<(usize, usize)> = path
+                    .split(" -> ")
+                    .map(|point| {
+                        let (x, y) = point.split_once(',').unwrap();
+                        (x.parse().unwrap(), y.parse().unwrap())
+                    })
+                    .collect();
+                for pair in points.windows(2) {
+                    assert!(pair[0].0 == pair[1].0 || pair[0].1 == pair[1].1, "{path}");
+                }
+                assert!(points.iter().all(|(x, y)| *x > 0 && (1..=490).contains(y)));
+            }
+        }
+        for density in [-0.1, 1.5, f64::NAN, f64::INFINITY] {
+            assert!(cave(100, density, 1).is_err(), "{density}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Geometry primitives.
+
+use std::{
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// Point/vector in 3D space.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vec3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Vec3 {
+    /// Origin of the axes.
+    pub const ZERO: Vec3 = Vec3::new(0, 0, 0);
+
+    /// Unit vectors along the axes, in both directions.
+    pub const UNITS: [Vec3; 6] = [
+        Vec3::new(1, 0, 0),
+        Vec3::new(-1, 0, 0),
+        Vec3::new(0, 1, 0),
+        Vec3::new(0, -1, 0),
+        Vec3::new(0, 0, 1),
+        Vec3::new(0, 0, -1),
+    ];
+
+    /// Construct a new instance.
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Manhattan (taxicab) distance from `other`.
+    pub fn manhattan(&self, other: &Vec3) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    /// Chebyshev (chessboard) distance from `other`.
+    pub fn chebyshev(&self, other: &Vec3) -> u64 {
+        self.x
+            .abs_diff(other.x)
+            .max(self.y.abs_diff(other.y))
+            .max(self.z.abs_diff(other.z))
+    }
+
+    /// Dot product with `other`.
+    pub fn dot(&self, other: &Vec3) -> i64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Vector with each component replaced by its sign (-1, 0 or 1).
+    pub fn signum(&self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    /// Iterate over the 6 face-adjacent neighbors.
+    pub fn neighbors6(self) -> impl Iterator<Item = Vec3> {
+        Self::UNITS.into_iter().map(move |unit| self + unit)
+    }
+
+    /// Iterate over the 26 face-, edge- and corner-adjacent neighbors.
+    pub fn neighbors26(self) -> impl Iterator<Item = Vec3> {
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| Vec3::new(x, y, z))))
+            .filter(|offset| *offset != Self::ZERO)
+            .map(move |offset| self + offset)
+    }
+}
+
+impl Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl TryFrom<&str> for Vec3 {
+    type Error = anyhow::Error;
+
+    /// Parse comma separated coordinates (e.g. `2,-3,5`).
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let coordinates = value
+            .split(',')
+            .map(|coordinate| {
+                coordinate
+                    .trim()
+                    .parse::<i64>()
+                    .map_err(|_| anyhow::format_err!("invalid coordinate '{coordinate}'"))
+            })
+            .collect::<Result<Vec<i64>, Self::Error>>()?;
+
+        match coordinates[..] {
+            [x, y, z] => Ok(Self::new(x, y, z)),
+            _ => anyhow::bail!("expected 3 coordinates, found {}", coordinates.len()),
+        }
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl AddAssign for Vec3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl SubAssign for Vec3 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i64> for Vec3 {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn arithmetic() {
+        let a = Vec3::new(1, 2, 3);
+        let b = Vec3::new(-4, 0, 2);
+
+        assert_eq!(Vec3::new(-3, 2, 5), a + b);
+        assert_eq!(Vec3::new(5, 2, 1), a - b);
+        assert_eq!(Vec3::new(2, 4, 6), a * 2);
+        assert_eq!(Vec3::new(-1, -2, -3), -a);
+        assert_eq!(Vec3::new(-1, 0, 1), b.signum());
+        assert_eq!(8, a.manhattan(&b));
+        assert_eq!(5, a.chebyshev(&b));
+        assert_eq!(2, a.dot(&b));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(b, c);
+    }
+
+    #[test]
+    fn neighbors() {
+        let origin = Vec3::new(2, 2, 2);
+
+        let faces: HashSet<Vec3> = origin.neighbors6().collect();
+        assert_eq!(6, faces.len());
+        assert!(faces.iter().all(|n| n.manhattan(&origin) == 1));
+
+        let all: HashSet<Vec3> = origin.neighbors26().collect();
+        assert_eq!(26, all.len());
+        assert!(!all.contains(&origin));
+        assert!(all.iter().all(|n| n.chebyshev(&origin) == 1));
+        assert!(faces.is_subset(&all));
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(Vec3::new(2, -3, 5), Vec3::try_from("2,-3,5").unwrap());
+        assert_eq!(Vec3::new(1, 1, 1), Vec3::try_from(" 1, 1 ,1").unwrap());
+        assert!(Vec3::try_from("1,2").is_err());
+        assert!(Vec3::try_from("1,2,3,4").is_err());
+        assert!(Vec3::try_from("1,a,3").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Two-dimensional grid stored in row-major order.
+
+use anyhow::Result;
+use std::fmt::{self, Display};
+
+/// Rectangular grid of cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Construct a new instance from the row-major list of cells.
+    pub fn new(rows: usize, cols: usize, cells: Vec<T>) -> Result<Self> {
+        if rows * cols != cells.len() {
+            anyhow::bail!("{} cells can't fill a {rows}x{cols} grid", cells.len());
+        }
+
+        Ok(Self { rows, cols, cells })
+    }
+
+    /// Parse a grid from text, converting each character with `f`.
+    pub fn parse<F>(s: &str, mut f: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<T>,
+    {
+        let mut rows: usize = 0;
+        let mut cols: Option<usize> = None;
+        let mut cells: Vec<T> = vec![];
+
+        for line in s.lines().filter(|line| !line.is_empty()) {
+            let len = line.chars().count();
+            if *cols.get_or_insert(len) != len {
+                anyhow::bail!(
+                    "grid row {} has {len} cells, expected {}",
+                    rows + 1,
+                    cols.unwrap_or_default()
+                );
+            }
+
+            for c in line.chars() {
+                cells.push(f(c)?);
+            }
+            rows += 1;
+        }
+
+        Self::new(rows, cols.unwrap_or_default(), cells)
+    }
+
+    /// Number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Get cell at given position (returns None if indices are out of bounds).
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        if i >= self.rows || j >= self.cols {
+            return None;
+        }
+
+        self.cells.get(i * self.cols + j)
+    }
+
+    /// Get mutable cell at given position (returns None if indices are out of bounds).
+    pub fn get_mut(&mut self, i: usize, j: usize) -> Option<&mut T> {
+        if i >= self.rows || j >= self.cols {
+            return None;
+        }
+
+        self.cells.get_mut(i * self.cols + j)
+    }
+
+    /// Iterate over the rows of the grid.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        // `max(1)` keeps `chunks` happy on empty grids.
+        self.cells.chunks(self.cols.max(1))
+    }
+
+    /// Build a new `rows`x`cols` grid where each cell is taken from `self` at the position
+    /// returned by `source(i, j)`.
+    fn remap<F>(&self, rows: usize, cols: usize, source: F) -> Self
+    where
+        T: Clone,
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for i in 0..rows {
+            for j in 0..cols {
+                let (si, sj) = source(i, j);
+                cells.push(self.cells[si * self.cols + sj].clone());
+            }
+        }
+
+        Self { rows, cols, cells }
+    }
+
+    /// Return the transposed grid (rows become columns).
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |i, j| (j, i))
+    }
+
+    /// Return the grid rotated 90° clockwise.
+    pub fn rotate90(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |i, j| (self.rows - 1 - j, i))
+    }
+
+    /// Return the grid rotated 180°.
+    pub fn rotate180(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.rows, self.cols, |i, j| {
+            (self.rows - 1 - i, self.cols - 1 - j)
+        })
+    }
+
+    /// Return the grid rotated 270° clockwise (90° counterclockwise).
+    pub fn rotate270(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |i, j| (j, self.cols - 1 - i))
+    }
+
+    /// Return the grid mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.rows, self.cols, |i, j| (i, self.cols - 1 - j))
+    }
+
+    /// Return the grid mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.rows, self.cols, |i, j| (self.rows - 1 - i, j))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, row) in self.iter_rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transforms() {
+        let grid = Grid::parse("abc\ndef", Ok).unwrap();
+        assert_eq!((2, 3), (grid.rows(), grid.cols()));
+        assert_eq!(Some(&'f'), grid.get(1, 2));
+        assert_eq!(None, grid.get(2, 0));
+
+        assert_eq!("ad\nbe\ncf", grid.transpose().to_string());
+        assert_eq!("da\neb\nfc", grid.rotate90().to_string());
+        assert_eq!("fed\ncba", grid.rotate180().to_string());
+        assert_eq!("cf\nbe\nad", grid.rotate270().to_string());
+        assert_eq!("cba\nfed", grid.flip_horizontal().to_string());
+        assert_eq!("def\nabc", grid.flip_vertical().to_string());
+
+        assert_eq!(grid, grid.rotate90().rotate270());
+        assert_eq!(grid.rotate180(), grid.rotate90().rotate90());
+        assert_eq!(grid.rotate90(), grid.transpose().flip_horizontal());
+    }
+
+    #[test]
+    fn invalid_grids() {
+        assert!(Grid::parse("ab\nc", Ok).is_err());
+        assert!(Grid::new(2, 2, vec![1, 2, 3]).is_err());
+        assert_eq!(0, Grid::parse("", Ok).unwrap().rows());
+    }
+}
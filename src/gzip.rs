@@ -0,0 +1,426 @@
+//! Dependency free gzip (RFC 1952) and DEFLATE (RFC 1951) decompression.
+
+use std::io::{self, ErrorKind};
+
+/// Base lengths of length symbols 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+/// Extra bits of length symbols 257..=285.
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances of distance symbols 0..=29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+/// Extra bits of distance symbols 0..=29.
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which the code length code lengths are stored in dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Construct an `InvalidData` error.
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, format!("invalid gzip data: {msg}"))
+}
+
+/// Decompress gzip `data` (possibly made of several members), checking sizes and checksums.
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let start = out.len();
+        pos = header(data, pos)?;
+
+        let mut reader = BitReader::new(&data[pos..]);
+        inflate(&mut reader, &mut out)?;
+        pos += reader.pos;
+
+        let trailer = data
+            .get(pos..pos + 8)
+            .ok_or_else(|| invalid("truncated trailer"))?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != crc32(&out[start..]) {
+            return Err(invalid("checksum mismatch"));
+        }
+        if size != (out.len() - start) as u32 {
+            return Err(invalid("size mismatch"));
+        }
+        pos += 8;
+    }
+
+    Ok(out)
+}
+
+/// Parse the member header starting at `pos`, returning the position of the compressed data.
+fn header(data: &[u8], mut pos: usize) -> io::Result<usize> {
+    let fixed = data
+        .get(pos..pos + 10)
+        .ok_or_else(|| invalid("truncated header"))?;
+    if fixed[..2] != [0x1f, 0x8b] {
+        return Err(invalid("bad magic number"));
+    }
+    if fixed[2] != 8 {
+        return Err(invalid("unsupported compression method"));
+    }
+    let flags = fixed[3];
+    pos += 10;
+
+    // FEXTRA
+    if flags & 0x04 != 0 {
+        let len = data
+            .get(pos..pos + 2)
+            .ok_or_else(|| invalid("truncated header"))?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    // FNAME and FCOMMENT, zero terminated.
+    for flag in [0x08, 0x10] {
+        if flags & flag != 0 {
+            let len = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or_else(|| invalid("truncated header"))?;
+            pos += len + 1;
+        }
+    }
+    // FHCRC
+    if flags & 0x02 != 0 {
+        pos += 2;
+    }
+
+    match pos <= data.len() {
+        true => Ok(pos),
+        false => Err(invalid("truncated header")),
+    }
+}
+
+/// CRC-32 (IEEE) checksum of `data`.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| match crc & 1 {
+            1 => (crc >> 1) ^ 0xedb8_8320,
+            _ => crc >> 1,
+        })
+    })
+}
+
+/// Least significant bit first reader.
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position of the next byte to load.
+    pos: usize,
+    /// Loaded bits not consumed yet.
+    bits: u32,
+    /// Number of loaded bits.
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    /// Construct a new instance.
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    /// Read `n` (at most 16) bits.
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| invalid("unexpected end of data"))?;
+            self.bits |= (byte as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+
+        let value = self.bits & ((1 << n) - 1);
+        self.bits >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Discard the remaining bits of the current byte.
+    fn align(&mut self) {
+        self.bits = 0;
+        self.count = 0;
+    }
+}
+
+/// Canonical Huffman code.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; 16],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Construct a new instance from the code length of each symbol.
+    fn new(lengths: &[u8]) -> io::Result<Self> {
+        let mut counts = [0u16; 16];
+        for len in lengths {
+            counts[*len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // Reject over-subscribed codes (incomplete ones are allowed).
+        let mut left = 1i32;
+        for count in &counts[1..] {
+            left = (left << 1) - *count as i32;
+            if left < 0 {
+                return Err(invalid("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, len) in lengths.iter().enumerate() {
+            if *len != 0 {
+                symbols[offsets[*len as usize] as usize] = symbol as u16;
+                offsets[*len as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decode the next symbol.
+    fn decode(&self, reader: &mut BitReader) -> io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = *count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(invalid("bad Huffman code"))
+    }
+}
+
+/// Inflate a DEFLATE stream, appending to `out`.
+fn inflate(reader: &mut BitReader, out: &mut Vec<u8>) -> io::Result<()> {
+    let start = out.len();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored(reader, out)?,
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths)?;
+                let distances = Huffman::new(&[5; 30])?;
+                codes(reader, out, start, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic(reader)?;
+                codes(reader, out, start, &literals, &distances)?;
+            }
+            _ => return Err(invalid("reserved block type")),
+        }
+
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+/// Copy a stored block.
+fn stored(reader: &mut BitReader, out: &mut Vec<u8>) -> io::Result<()> {
+    reader.align();
+    let header = reader
+        .data
+        .get(reader.pos..reader.pos + 4)
+        .ok_or_else(|| invalid("truncated stored block"))?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(invalid("stored block length mismatch"));
+    }
+    reader.pos += 4;
+
+    let block = reader
+        .data
+        .get(reader.pos..reader.pos + len as usize)
+        .ok_or_else(|| invalid("truncated stored block"))?;
+    out.extend_from_slice(block);
+    reader.pos += len as usize;
+
+    Ok(())
+}
+
+/// Read the literal/length and distance codes of a dynamic block.
+fn dynamic(reader: &mut BitReader) -> io::Result<(Huffman, Huffman)> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+    if literals > 286 || distances > 30 {
+        return Err(invalid("too many codes"));
+    }
+
+    let mut lengths = [0u8; 19];
+    for idx in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[*idx] = reader.bits(3)? as u8;
+    }
+    let lengths_code = Huffman::new(&lengths)?;
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (len, repeat) = match lengths_code.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or_else(|| invalid("repeat with no previous length"))?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend((0..repeat).map(|_| len));
+    }
+    if lengths.len() > literals + distances {
+        return Err(invalid("too many code lengths"));
+    }
+    if lengths[256] == 0 {
+        return Err(invalid("missing end of block code"));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..literals])?,
+        Huffman::new(&lengths[literals..])?,
+    ))
+}
+
+/// Decode a Huffman-compressed block, where back references can't reach before `start`.
+fn codes(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    start: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let idx = symbol - 257;
+                if idx >= LENGTH_BASE.len() {
+                    return Err(invalid("bad length symbol"));
+                }
+                let len =
+                    LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx] as u32)? as usize;
+
+                let idx = distances.decode(reader)? as usize;
+                if idx >= DISTANCE_BASE.len() {
+                    return Err(invalid("bad distance symbol"));
+                }
+                let distance =
+                    DISTANCE_BASE[idx] as usize + reader.bits(DISTANCE_EXTRA[idx] as u32)? as usize;
+                if distance > out.len() - start {
+                    return Err(invalid("distance too far back"));
+                }
+
+                // Byte by byte, as the copy may overlap its own output.
+                for _ in 0..len {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn block_types() {
+        let hello = b"hello hello hello hello\n";
+
+        // Fixed Huffman codes.
+        let fixed = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0xc8, 0x40, 0x27, 0xb9, 0x00, 0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00,
+            0x00,
+        ];
+        assert_eq!(hello.to_vec(), decompress(&fixed).unwrap());
+
+        // Stored block, followed by a second member.
+        let mut stored = vec![
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x03, 0x01, 0x18, 0x00, 0xe7,
+            0xff,
+        ];
+        stored.extend_from_slice(hello);
+        stored.extend_from_slice(&[0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00, 0x00]);
+        stored.extend_from_slice(&fixed);
+        assert_eq!([&hello[..], hello].concat(), decompress(&stored).unwrap());
+
+        // Dynamic Huffman codes.
+        let dynamic = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x65, 0xce, 0x3b, 0x0a,
+            0x02, 0x41, 0x10, 0x00, 0xd1, 0xdc, 0x53, 0xcc, 0x15, 0xa6, 0xbf, 0xbb, 0x07, 0xd2,
+            0x6c, 0x58, 0x10, 0xf1, 0xfc, 0xda, 0xb4, 0x81, 0x43, 0x45, 0x05, 0x15, 0xbd, 0x75,
+            0xbd, 0xef, 0x63, 0x8e, 0xc7, 0xf3, 0x5a, 0xdf, 0xbc, 0xae, 0x31, 0x6f, 0xab, 0x96,
+            0xf4, 0x92, 0x5a, 0xd1, 0x4b, 0x7b, 0x69, 0x2d, 0xe9, 0x65, 0xbd, 0xac, 0x56, 0xf6,
+            0xf2, 0x5e, 0x5e, 0x4b, 0x7b, 0x45, 0xaf, 0xa8, 0x75, 0xf4, 0xca, 0x5e, 0x59, 0xcb,
+            0x7a, 0xfd, 0x10, 0x47, 0xad, 0x73, 0x43, 0x9c, 0xb5, 0x7c, 0x43, 0xfc, 0x53, 0x8d,
+            0x54, 0x27, 0x35, 0x48, 0x4d, 0x52, 0x27, 0xa9, 0x42, 0xaa, 0x92, 0x6a, 0xa4, 0x3a,
+            0xa9, 0x41, 0x6a, 0x92, 0x3a, 0x49, 0x15, 0x52, 0x95, 0x54, 0x23, 0xd5, 0x49, 0x0d,
+            0x52, 0x93, 0xd4, 0x49, 0xaa, 0x90, 0xaa, 0xa4, 0x1a, 0xa9, 0x4e, 0x6a, 0x90, 0x9a,
+            0xa4, 0x4e, 0x52, 0x85, 0x54, 0x25, 0xd5, 0x48, 0xf5, 0x8d, 0xfa, 0x01, 0x78, 0x7d,
+            0xd1, 0x54, 0xf8, 0x02, 0x00, 0x00,
+        ];
+        let expected: String = (0..40)
+            .map(|i| {
+                format!(
+                    "move {} from {} to {}\n",
+                    i % 7 + 1,
+                    i % 9 + 1,
+                    i * 5 % 9 + 1
+                )
+            })
+            .collect();
+        assert_eq!(expected.as_bytes(), decompress(&dynamic).unwrap());
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut data = vec![
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0xc8, 0x40, 0x27, 0xb9, 0x00, 0x00, 0x88, 0x59, 0x0b, 0x18, 0x00, 0x00,
+            0x00,
+        ];
+        assert!(decompress(&data[..20]).is_err());
+        assert!(decompress(&data[..27]).is_err());
+        assert!(decompress(&data[1..]).is_err());
+
+        data[22] ^= 1;
+        assert!(decompress(&data).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Closed integer intervals.
+
+/// Closed interval of integers `min..=max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Interval {
+    /// Construct a new instance, or `None` if `min > max`.
+    pub fn new(min: i64, max: i64) -> Option<Self> {
+        (min <= max).then_some(Self { min, max })
+    }
+
+    /// Number of integers in the interval.
+    pub fn len(&self) -> u64 {
+        self.max.abs_diff(self.min) + 1
+    }
+
+    /// Intervals always contain at least one integer.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Whether `other` is fully contained in the interval.
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min <= other.min && other.max <= self.max
+    }
+
+    /// Whether the interval shares at least one integer with `other`.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min <= other.max && other.min <= self.max
+    }
+
+    /// Integers shared with `other`, or `None` if the intervals are disjoint.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
+    /// Union with `other`, or `None` if it isn't an interval (the two are neither overlapping
+    /// nor adjacent).
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        (self.min <= other.max.saturating_add(1) && other.min <= self.max.saturating_add(1)).then(
+            || Self {
+                min: self.min.min(other.min),
+                max: self.max.max(other.max),
+            },
+        )
+    }
+}
+
+/// Union of the given intervals, as sorted disjoint intervals (adjacent ones are merged too).
+pub fn merge<I>(intervals: I) -> Vec<Interval>
+where
+    I: IntoIterator<Item = Interval>,
+{
+    let mut intervals: Vec<Interval> = intervals.into_iter().collect();
+    intervals.sort_unstable();
+
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.min <= last.max.saturating_add(1) => {
+                last.max = last.max.max(interval.max)
+            }
+            _ => merged.push(interval),
+        }
+    }
+
+    merged
+}
+
+/// Total number of integers shared by the intervals of each pair.
+pub fn overlap_len<I>(pairs: I) -> u64
+where
+    I: IntoIterator<Item = (Interval, Interval)>,
+{
+    pairs
+        .into_iter()
+        .filter_map(|(a, b)| a.intersection(&b))
+        .map(|intersection| intersection.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn interval(min: i64, max: i64) -> Interval {
+        Interval::new(min, max).unwrap()
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(None, Interval::new(3, 2));
+        assert_eq!(1, interval(-2, -2).len());
+        assert_eq!(11, interval(-5, 5).len());
+        assert_eq!(u64::MAX, interval(i64::MIN, i64::MAX - 1).len());
+    }
+
+    #[test]
+    fn set_operations() {
+        let (a, b) = (interval(2, 6), interval(4, 8));
+        assert!(!a.contains(&b));
+        assert!(a.contains(&interval(3, 6)));
+        assert!(a.contains(&a));
+        assert!(a.intersects(&b) && b.intersects(&a));
+        assert_eq!(Some(interval(4, 6)), a.intersection(&b));
+        assert_eq!(Some(interval(2, 8)), a.union(&b));
+
+        // Adjacent intervals: disjoint, but their union is an interval.
+        let (a, b) = (interval(-3, 0), interval(1, 1));
+        assert!(!a.intersects(&b));
+        assert_eq!(None, b.intersection(&a));
+        assert_eq!(Some(interval(-3, 1)), b.union(&a));
+        assert_eq!(None, a.union(&interval(2, 5)));
+
+        let (a, b) = (interval(i64::MIN, 0), interval(0, i64::MAX));
+        assert_eq!(Some(interval(0, 0)), a.intersection(&b));
+        assert_eq!(Some(interval(i64::MIN, i64::MAX)), a.union(&b));
+
+        assert_eq!(
+            3 + 1,
+            overlap_len([
+                (interval(2, 6), interval(4, 8)),
+                (interval(-3, 0), interval(1, 1)),
+                (interval(5, 5), interval(0, 9)),
+            ])
+        );
+        assert_eq!(0, overlap_len([]));
+    }
+
+    #[test]
+    fn merging() {
+        assert_eq!(
+            vec![interval(-8, 2), interval(4, 12), interval(20, 20)],
+            merge([
+                interval(12, 12),
+                interval(-2, 2),
+                interval(20, 20),
+                interval(4, 10),
+                interval(-8, 0),
+                interval(2, 2),
+                interval(11, 12),
+            ])
+        );
+        assert_eq!(
+            vec![interval(0, 10)],
+            merge([interval(0, 10), interval(2, 3)])
+        );
+        assert_eq!(
+            vec![interval(i64::MIN, i64::MAX)],
+            merge([interval(0, i64::MAX), interval(i64::MIN, -1)])
+        );
+        assert!(merge([]).is_empty());
+    }
+}
@@ -1,13 +1,102 @@
+pub mod balanced;
+pub mod cache;
+pub mod calories;
+pub mod cli;
+pub mod combinatorics;
+pub mod counter;
+pub mod cycle;
+pub mod direction;
+pub mod examples;
+pub mod forest;
+pub mod generators;
+pub mod geometry;
+pub mod grid;
+pub mod gzip;
+pub mod interval;
+pub mod math;
+pub mod nested;
+pub mod parse;
+pub mod priority;
+pub mod rng;
+pub mod span;
+pub mod trace;
+pub mod union_find;
+
+pub use calories::{rank_elves, Elf};
+
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, ErrorKind, Read},
+    path::{Path, PathBuf},
 };
 
+/// Read the input file at `path`, falling back to a gzip-compressed `path.gz` when `path` doesn't
+/// exist.
+///
+/// # Note
+/// zstd-compressed `path.zst` inputs are not supported: decoding them would take a decoder as
+/// large as the whole library or an external crate, while gzip already keeps large inputs small.
+/// They are detected and reported with an [`ErrorKind::Unsupported`] error.
+pub fn read_input<P>(path: P) -> Result<String, io::Error>
+where
+    P: AsRef<Path>,
+{
+    String::from_utf8(read_input_bytes(path)?)
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Like [`read_input`], for inputs which may not be valid UTF-8.
+///
+/// # Note
+/// Only gzip-compressed inputs are decompressed, `path.zst` is reported as unsupported (see
+/// [`read_input`]).
+pub fn read_input_bytes<P>(path: P) -> Result<Vec<u8>, io::Error>
+where
+    P: AsRef<Path>,
+{
+    let mut bytes = Vec::new();
+    open_input(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Open the input file at `path` for buffered reading, with the same gzip fallback as
+/// [`read_input`].
+///
+/// Plain inputs are streamed from the file, gzip-compressed ones are decompressed in memory
+/// first.
+pub fn open_input<P>(path: P) -> Result<Box<dyn BufRead>, io::Error>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let with_extension = |extension: &str| {
+        let mut compressed = OsString::from(path);
+        compressed.push(extension);
+        PathBuf::from(compressed)
+    };
+
+    if !path.exists() {
+        let gz = with_extension(".gz");
+        if gz.exists() {
+            return Ok(Box::new(io::Cursor::new(gzip::decompress(&fs::read(gz)?)?)));
+        }
+
+        let zst = with_extension(".zst");
+        if zst.exists() {
+            return Err(io::Error::new(
+                ErrorKind::Unsupported,
+                format!("{}: zstd inputs are not supported, use gzip", zst.display()),
+            ));
+        }
+    }
+
+    Ok(Box::new(BufReader::new(File::open(path)?)))
+}
+
 pub fn read_input_lines<P>(path: P) -> Result<Vec<String>, io::Error>
 where
     P: AsRef<Path>,
 {
-    let input_file = File::open(path)?;
-    BufReader::new(input_file).lines().collect()
+    Ok(read_input(path)?.lines().map(String::from).collect())
 }
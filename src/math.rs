@@ -0,0 +1,150 @@
+//! Number theory utilities.
+
+/// Greatest common divisor (`gcd(0, 0) == 0`).
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Least common multiple (`lcm(0, n) == 0`), or `None` on overflow.
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Least common multiple of all the given values, or `None` on overflow.
+pub fn lcm_all<I>(values: I) -> Option<u64>
+where
+    I: IntoIterator<Item = u64>,
+{
+    values.into_iter().try_fold(1, lcm)
+}
+
+/// Modular exponentiation: `base^exp mod modulus`.
+///
+/// # Panic
+/// Panics if `modulus` is 0.
+pub fn modpow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    assert!(modulus != 0, "modulus must be greater than 0");
+
+    // Intermediate products are computed as u128 so they can't overflow.
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+
+    result as u64
+}
+
+/// Extended Euclidean algorithm: return `(g, x, y)` such that `a*x + b*y == g == gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+
+    let (g, x, y) = extended_gcd(b, a % b);
+    (g, y, x - (a / b) * y)
+}
+
+/// Solve the system of congruences `x ≡ residue (mod modulus)` for each `(residue, modulus)`
+/// pair using the Chinese Remainder Theorem.
+///
+/// Moduli don't need to be pairwise coprime. Return the smallest non-negative solution along
+/// with the modulus of the combined congruence, or `None` if the system has no solution (or a
+/// modulus is 0, or the combined modulus overflows).
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    // All the values below are smaller than 2^64, so products of two of them fit in u128.
+    let mut x: u128 = 0;
+    let mut m: u128 = 1;
+
+    for &(residue, modulus) in congruences {
+        if modulus == 0 {
+            return None;
+        }
+
+        let (residue, modulus) = (residue as u128 % modulus as u128, modulus as u128);
+        let (g, p, _) = extended_gcd(m as i128, modulus as i128);
+        let g = g as u128;
+        let diff = (residue + modulus - x % modulus) % modulus;
+        if !diff.is_multiple_of(g) {
+            return None;
+        }
+
+        let reduced = modulus / g;
+        let lcm = m * reduced;
+        if lcm > u64::MAX as u128 {
+            return None;
+        }
+
+        let p = p.rem_euclid(reduced as i128) as u128;
+        let step = (diff / g) % reduced * p % reduced;
+        x = (x + m * step) % lcm;
+        m = lcm;
+    }
+
+    Some((x as u64, m as u64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(0, gcd(0, 0));
+        assert_eq!(7, gcd(0, 7));
+        assert_eq!(7, gcd(7, 0));
+        assert_eq!(6, gcd(48, 18));
+        assert_eq!(1, gcd(17, 23));
+
+        assert_eq!(Some(0), lcm(0, 5));
+        assert_eq!(Some(144), lcm(48, 18));
+        assert_eq!(None, lcm(u64::MAX, u64::MAX - 1));
+        assert_eq!(Some(u64::MAX), lcm(u64::MAX, u64::MAX));
+
+        // Day 11 example divisors.
+        assert_eq!(Some(96577), lcm_all([23, 19, 13, 17]));
+        assert_eq!(Some(1), lcm_all([]));
+    }
+
+    #[test]
+    fn modular_exponentiation() {
+        assert_eq!(0, modpow(5, 3, 1));
+        assert_eq!(1, modpow(0, 0, 7));
+        assert_eq!(0, modpow(0, 5, 7));
+        assert_eq!(445, modpow(4, 13, 497));
+        // Would overflow without u128 intermediates.
+        assert_eq!(
+            modpow(u64::MAX - 1, 2, u64::MAX),
+            1 // (-1)^2 mod u64::MAX
+        );
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((0, 1)), crt(&[]));
+        // Non coprime moduli.
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 4), (2, 6)]));
+        assert_eq!(None, crt(&[(1, 0)]));
+        // Large moduli.
+        let (x, m) = crt(&[(1, 4_294_967_291), (2, 4_294_967_279)]).unwrap();
+        assert_eq!(4_294_967_291 * 4_294_967_279, m);
+        assert_eq!(1, x % 4_294_967_291);
+        assert_eq!(2, x % 4_294_967_279);
+        assert_eq!(None, crt(&[(0, u64::MAX), (0, u64::MAX - 1)]));
+    }
+}
@@ -0,0 +1,245 @@
+//! Nested bracketed lists (e.g. `[[1,2],[3,[4]]]`).
+
+use crate::span::SpanError;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Value of a nested list: either a plain value or a list of nested values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Nested<T> {
+    Value(T),
+    List(Vec<Nested<T>>),
+}
+
+impl<T> Nested<T> {
+    /// Compare with `other` using `cmp` to compare plain values.
+    ///
+    /// Lists are compared lexicographically; a plain value compared with a list is treated as a
+    /// list containing only that value. This is not a total order consistent with `==` (`3` and
+    /// `[[3]]` compare as equal), hence no [`Ord`] implementation.
+    pub fn cmp_by<F>(&self, other: &Self, cmp: F) -> Ordering
+    where
+        F: Fn(&T, &T) -> Ordering + Copy,
+    {
+        match (self, other) {
+            (Self::Value(a), Self::Value(b)) => cmp(a, b),
+            (Self::List(a), Self::List(b)) => Self::cmp_lists(a, b, cmp),
+            (Self::Value(_), Self::List(b)) => Self::cmp_lists(std::slice::from_ref(self), b, cmp),
+            (Self::List(a), Self::Value(_)) => Self::cmp_lists(a, std::slice::from_ref(other), cmp),
+        }
+    }
+
+    fn cmp_lists<F>(a: &[Self], b: &[Self], cmp: F) -> Ordering
+    where
+        F: Fn(&T, &T) -> Ordering + Copy,
+    {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.cmp_by(b, cmp))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len()))
+    }
+
+    /// Compare with `other`, with the natural ordering of plain values (see [`Nested::cmp_by`]).
+    pub fn compare(&self, other: &Self) -> Ordering
+    where
+        T: Ord,
+    {
+        self.cmp_by(other, T::cmp)
+    }
+
+    /// Depth of the deepest value (plain values have depth 0).
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::Value(_) => 0,
+            Self::List(items) => 1 + items.iter().map(Self::depth).max().unwrap_or_default(),
+        }
+    }
+}
+
+impl<T> FromStr for Nested<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    type Err = SpanError;
+
+    /// Parse a nested list (or a plain value), without whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Lists being built, innermost last (kept on the heap so that deep nesting can't
+        // overflow the stack).
+        let mut open: Vec<Vec<Self>> = vec![];
+        let mut parsed: Option<Self> = None;
+        let mut offset: usize = 0;
+
+        while offset < s.len() {
+            if parsed.is_some() && open.is_empty() {
+                return Err(SpanError::at(s, offset, 1, "unexpected data after value"));
+            }
+
+            match s.as_bytes()[offset] {
+                b'[' => {
+                    if parsed.is_some() {
+                        return Err(SpanError::at(s, offset, 1, "expected ',' or ']'"));
+                    }
+                    open.push(vec![]);
+                    offset += 1;
+                }
+                b']' => {
+                    let mut items = open
+                        .pop()
+                        .ok_or_else(|| SpanError::at(s, offset, 1, "unmatched ']'"))?;
+                    match parsed.take() {
+                        Some(item) => items.push(item),
+                        None if !items.is_empty() => {
+                            return Err(SpanError::at(s, offset, 1, "expected value after ','"))
+                        }
+                        None => {}
+                    }
+                    parsed = Some(Self::List(items));
+                    offset += 1;
+                }
+                b',' => {
+                    match (parsed.take(), open.last_mut()) {
+                        (Some(item), Some(items)) => items.push(item),
+                        _ => return Err(SpanError::at(s, offset, 1, "unexpected ','")),
+                    }
+                    offset += 1;
+                }
+                _ => {
+                    let len = s[offset..]
+                        .find(['[', ']', ','])
+                        .unwrap_or(s.len() - offset);
+                    let token = &s[offset..offset + len];
+                    if parsed.is_some() {
+                        return Err(SpanError::new(s, token, "expected ',' or ']'"));
+                    }
+                    parsed = Some(Self::Value(
+                        token.parse().map_err(|e| SpanError::new(s, token, e))?,
+                    ));
+                    offset += len;
+                }
+            }
+        }
+
+        if !open.is_empty() {
+            return Err(SpanError::at(s, s.len(), 0, "unclosed '['"));
+        }
+        parsed.ok_or_else(|| SpanError::at(s, 0, 0, "empty input"))
+    }
+}
+
+impl<T> Display for Nested<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{value}"),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Nested<u32> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        use Nested::{List, Value};
+
+        assert_eq!(
+            List(vec![
+                List(vec![Value(1), Value(2)]),
+                List(vec![Value(3), List(vec![Value(4)])])
+            ]),
+            parse("[[1,2],[3,[4]]]")
+        );
+        assert_eq!(List(vec![]), parse("[]"));
+        assert_eq!(Value(10), parse("10"));
+        assert_eq!(3, parse("[[[]],[1]]").depth());
+
+        for s in [
+            "[[1,2],[3,[4]]]",
+            "[]",
+            "[[[]]]",
+            "[1,[2,[3,[4,[5,6,7]]]],8,9]",
+            "42",
+        ] {
+            assert_eq!(s, parse(s).to_string());
+        }
+
+        let deep = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+        assert_eq!(1000, parse(&deep).depth());
+    }
+
+    #[test]
+    fn adversarial_input() {
+        for (s, column) in [
+            ("", 1),
+            ("[", 2),
+            ("]", 1),
+            ("[1,]", 4),
+            ("[,1]", 2),
+            ("[1 2]", 2),
+            ("[1][2]", 4),
+            ("[1,a]", 4),
+            ("[[1]2]", 5),
+            ("[1,2]]", 6),
+            ("1[", 2),
+        ] {
+            let error = s.parse::<Nested<u32>>().unwrap_err();
+            assert_eq!(column, error.column(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        // Day 13 example pairs, the 1st, 2nd, 4th and 6th are in the right order.
+        const PAIRS: [(&str, &str, bool); 8] = [
+            ("[1,1,3,1,1]", "[1,1,5,1,1]", true),
+            ("[[1],[2,3,4]]", "[[1],4]", true),
+            ("[9]", "[[8,7,6]]", false),
+            ("[[4,4],4,4]", "[[4,4],4,4,4]", true),
+            ("[7,7,7,7]", "[7,7,7]", false),
+            ("[]", "[3]", true),
+            ("[[[]]]", "[[]]", false),
+            (
+                "[1,[2,[3,[4,[5,6,7]]]],8,9]",
+                "[1,[2,[3,[4,[5,6,0]]]],8,9]",
+                false,
+            ),
+        ];
+
+        for (left, right, ordered) in PAIRS {
+            assert_eq!(
+                ordered,
+                parse(left).compare(&parse(right)).is_lt(),
+                "{left} {right}"
+            );
+        }
+
+        // Custom value ordering.
+        let (a, b) = (parse("[1,[9]]"), parse("[1,[2]]"));
+        assert_eq!(Ordering::Greater, a.compare(&b));
+        assert_eq!(Ordering::Less, a.cmp_by(&b, |x, y| y.cmp(x)));
+        assert_eq!(Ordering::Equal, parse("[[[3]]]").compare(&parse("3")));
+    }
+}
@@ -0,0 +1,94 @@
+//! Input normalization shared by the day parsers, selecting how strictly the puzzle grammars
+//! are enforced.
+
+use crate::cli::Args;
+use std::borrow::Cow;
+
+/// How strictly inputs are parsed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject any deviation from the puzzle grammar.
+    #[default]
+    Strict,
+    /// Tolerate Windows line endings, trailing whitespace, repeated blank lines and stray spaces.
+    Lenient,
+}
+
+impl ParseMode {
+    /// Command line flag selecting the lenient mode.
+    pub const FLAG: &'static str = "--lenient";
+
+    /// Construct a new instance from the `--lenient` flag.
+    pub fn from_args(args: &Args) -> Self {
+        match args.flag(Self::FLAG) {
+            true => Self::Lenient,
+            false => Self::Strict,
+        }
+    }
+
+    /// Normalize the layout of `input`: in lenient mode, convert line endings, strip trailing
+    /// whitespace and leading blank lines, and collapse runs of blank lines into one.
+    pub fn normalize<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        self.lenient(input, false)
+    }
+
+    /// Like [`ParseMode::normalize`], also trimming leading whitespace and collapsing runs of
+    /// whitespace within lines, for grammars where columns carry no meaning.
+    pub fn normalize_tokens<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        self.lenient(input, true)
+    }
+
+    fn lenient<'a>(&self, input: &'a str, tokens: bool) -> Cow<'a, str> {
+        if *self == Self::Strict {
+            return Cow::Borrowed(input);
+        }
+
+        let mut normalized = String::with_capacity(input.len());
+        let mut blank = true;
+        for line in input.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                // Keep a single blank line, dropping the leading ones.
+                if !blank {
+                    normalized.push('\n');
+                }
+                blank = true;
+                continue;
+            }
+
+            match tokens {
+                true => normalized.push_str(&line.split_whitespace().collect::<Vec<_>>().join(" ")),
+                false => normalized.push_str(line),
+            }
+            normalized.push('\n');
+            blank = false;
+        }
+
+        Cow::Owned(normalized)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        let input = "\r\n  [D]  \r\nmove  1 from\t2 to 1 \r\n\r\n\n\nR 4\n\n\n";
+
+        assert_eq!(input, ParseMode::Strict.normalize(input));
+        assert_eq!(input, ParseMode::Strict.normalize_tokens(input));
+        assert_eq!(
+            "  [D]\nmove  1 from\t2 to 1\n\nR 4\n\n",
+            ParseMode::Lenient.normalize(input)
+        );
+        assert_eq!(
+            "[D]\nmove 1 from 2 to 1\n\nR 4\n\n",
+            ParseMode::Lenient.normalize_tokens(input)
+        );
+
+        // Well formed inputs are left untouched.
+        let input = "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 2 to 1\n";
+        assert_eq!(input, ParseMode::Lenient.normalize(input));
+    }
+}
@@ -0,0 +1,105 @@
+//! Priorities of ASCII items.
+
+use anyhow::Result;
+
+/// Table mapping ASCII items to their priorities, starting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorityTable {
+    /// Items, in priority order.
+    items: Vec<char>,
+    /// Priority of each ASCII item (0 if the item has none).
+    priorities: [usize; 128],
+}
+
+impl Default for PriorityTable {
+    /// Lowercase items `a` to `z` have priorities 1 through 26, uppercase items `A` to `Z` have
+    /// priorities 27 through 52.
+    fn default() -> Self {
+        let items: String = ('a'..='z').chain('A'..='Z').collect();
+        Self::new(&items).expect("default items are distinct ASCII characters")
+    }
+}
+
+impl PriorityTable {
+    /// Construct a new instance from the items listed in priority order: the first one has
+    /// priority 1, the second one priority 2 and so on.
+    pub fn new(items: &str) -> Result<Self> {
+        let mut table = Self {
+            items: Vec::with_capacity(items.len()),
+            priorities: [0; 128],
+        };
+
+        for item in items.chars() {
+            if !item.is_ascii() || item.is_ascii_control() {
+                anyhow::bail!("item {item:?} is not a printable ASCII character");
+            }
+            if table.priorities[item as usize] != 0 {
+                anyhow::bail!("item '{item}' is listed more than once");
+            }
+            table.items.push(item);
+            table.priorities[item as usize] = table.items.len();
+        }
+
+        Ok(table)
+    }
+
+    /// Priority of `item` (`None` if the item has none).
+    pub fn priority(&self, item: char) -> Option<usize> {
+        match self.priorities.get(item as usize) {
+            Some(0) | None => None,
+            Some(priority) => Some(*priority),
+        }
+    }
+
+    /// Item with the given priority (`None` if no item has it).
+    pub fn item(&self, priority: usize) -> Option<char> {
+        self.items.get(priority.checked_sub(1)?).copied()
+    }
+
+    /// Number of items with a priority, which is also the highest priority.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether no item has a priority.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_table() {
+        let table = PriorityTable::default();
+        assert_eq!(52, table.len());
+        assert_eq!(Some(16), table.priority('p'));
+        assert_eq!(Some(38), table.priority('L'));
+        assert_eq!(None, table.priority('1'));
+        assert_eq!(None, table.priority('é'));
+
+        for priority in 1..=table.len() {
+            assert_eq!(
+                Some(priority),
+                table.priority(table.item(priority).unwrap())
+            );
+        }
+        assert_eq!(None, table.item(0));
+        assert_eq!(None, table.item(53));
+    }
+
+    #[test]
+    fn custom_table() {
+        let table = PriorityTable::new("0123456789-").unwrap();
+        assert_eq!(Some(1), table.priority('0'));
+        assert_eq!(Some(11), table.priority('-'));
+        assert_eq!(None, table.priority('a'));
+        assert!(PriorityTable::new("").unwrap().is_empty());
+
+        for items in ["aba", "aé", "a\n"] {
+            assert!(PriorityTable::new(items).is_err(), "{items:?}");
+        }
+    }
+}
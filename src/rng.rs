@@ -0,0 +1,99 @@
+//! Small deterministic pseudo-random number generator (xorshift64*).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Pseudo-random number generator: not cryptographically secure, but fast, dependency free and
+/// reproducible given the seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Construct a new instance from `seed`.
+    pub fn new(seed: u64) -> Self {
+        // State must never be 0, scramble the seed so that close seeds diverge quickly.
+        Self {
+            state: (seed ^ 0x9e37_79b9_7f4a_7c15).wrapping_mul(0xbf58_476d_1ce4_e5b9) | 1,
+        }
+    }
+
+    /// Construct a new instance seeded from the system clock.
+    pub fn from_time() -> Self {
+        Self::new(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64),
+        )
+    }
+
+    /// Return the next random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Return a random number in `0..n` (0 if `n` is 0).
+    pub fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+
+        // Multiply-shift maps the full u64 range onto 0..n with negligible bias.
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Return a random number in `min..=max`.
+    pub fn range(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+
+        let span = max.abs_diff(min);
+        if span == u64::MAX {
+            return self.next_u64() as i64;
+        }
+
+        min.wrapping_add(self.below(span + 1) as i64)
+    }
+
+    /// Return `true` with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 <= probability
+    }
+
+    /// Return a random element of `items`.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len() as u64) as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible_and_bounded() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..1000 {
+            let n = rng.range(-2, 3);
+            assert!((-2..=3).contains(&n));
+            seen[(n + 2) as usize] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+
+        assert_eq!(0, rng.below(0));
+        assert_eq!(5, rng.range(5, 5));
+        assert_eq!(None, rng.choose::<u8>(&[]));
+        assert_eq!(Some(&1), rng.choose(&[1]));
+        assert!(rng.chance(1.0));
+        assert!(!rng.chance(-1.0));
+    }
+}
@@ -0,0 +1,162 @@
+//! Parse errors pointing at the offending span of the input.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+/// Parse error located at a span of the input, rendered with a caret-highlighted snippet:
+///
+/// ```text
+/// line 2, column 10: expected ',' after x coordinate
+///   |
+/// 2 | 503,4 -> 5024 -> 502,9
+///   |          ^^^^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanError {
+    /// Line number (indexing from 1).
+    line: usize,
+    /// Column number, in characters (indexing from 1).
+    column: usize,
+    /// Span length, in characters.
+    len: usize,
+    /// Source line containing the span.
+    snippet: String,
+    /// Error message.
+    message: String,
+}
+
+impl SpanError {
+    /// Construct a new instance pointing at `span`, which must be a subslice of `input`.
+    ///
+    /// If `span` doesn't belong to `input` the error points at the start of the input.
+    pub fn new<M: Display>(input: &str, span: &str, message: M) -> Self {
+        Self::at(input, Self::offset(input, span), span.len(), message)
+    }
+
+    /// Byte offset of `span` in `input` (0 if `span` doesn't belong to `input`).
+    fn offset(input: &str, span: &str) -> usize {
+        (span.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|offset| offset + span.len() <= input.len())
+            .unwrap_or_default()
+    }
+
+    /// Construct a new instance pointing at `len` bytes starting at byte `offset` of `input`.
+    pub fn at<M: Display>(input: &str, offset: usize, len: usize, message: M) -> Self {
+        let mut offset = offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = input[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |idx| offset + idx);
+        let span_end = (offset + len).min(line_end);
+        let snippet = &input[line_start..line_end];
+
+        Self {
+            line: input[..line_start].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            len: input
+                .get(offset..span_end)
+                .map_or(0, |span| span.chars().count()),
+            snippet: snippet.trim_end_matches('\r').to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Relocate an error raised parsing `span` on its own so that it points at the same place in
+    /// `input`, of which `span` is a subslice.
+    pub fn within(self, input: &str, span: &str) -> Self {
+        let line_start: usize = span
+            .split_inclusive('\n')
+            .take(self.line - 1)
+            .map(str::len)
+            .sum();
+        let start = span[line_start..]
+            .char_indices()
+            .nth(self.column - 1)
+            .map_or(span.len(), |(idx, _)| line_start + idx);
+        let len = span[start..]
+            .chars()
+            .take(self.len)
+            .map(char::len_utf8)
+            .sum();
+
+        Self::at(input, Self::offset(input, span) + start, len, self.message)
+    }
+
+    /// Line number (indexing from 1).
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column number (indexing from 1).
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for SpanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(
+            f,
+            "{gutter} | {}{}",
+            " ".repeat(self.column - 1),
+            "^".repeat(self.len.max(1))
+        )
+    }
+}
+
+impl Error for SpanError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snippets() {
+        const INPUT: &str = "498,4 -> 498,6\n503,4 -> 5024 -> 502,9\n";
+
+        let span = &INPUT[24..28];
+        let error = SpanError::new(INPUT, span, "expected ',' after x coordinate");
+        assert_eq!((2, 10), (error.line(), error.column()));
+        assert_eq!(
+            "line 2, column 10: expected ',' after x coordinate
+  |
+2 | 503,4 -> 5024 -> 502,9
+  |          ^^^^",
+            error.to_string()
+        );
+
+        // Empty span at the end of the input.
+        let error = SpanError::at(INPUT, INPUT.len(), 0, "unexpected end of input");
+        assert_eq!((3, 1), (error.line(), error.column()));
+        assert!(error.to_string().ends_with("  | ^"));
+
+        // Span not belonging to the input.
+        let error = SpanError::new(INPUT, &String::from("498"), "foreign span");
+        assert_eq!((1, 1), (error.line(), error.column()));
+
+        // Columns are counted in characters, spans don't extend past the line.
+        let error = SpanError::at("é,x\nabc", 3, 10, "invalid");
+        assert_eq!((1, 3), (error.line(), error.column()));
+        assert!(error.to_string().ends_with("  |   ^"));
+
+        // Error raised on a line parsed on its own.
+        let line = &INPUT[15..37];
+        let error = SpanError::new(line, &line[9..13], "expected ','").within(INPUT, line);
+        assert_eq!((2, 10), (error.line(), error.column()));
+        assert!(error.to_string().ends_with("  |          ^^^^"));
+    }
+}
@@ -0,0 +1,347 @@
+//! Intermediate state dumps, written as one JSON object per line, or as readable lines.
+
+use crate::cli::Args;
+use anyhow::Result;
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Value of a traced field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceValue {
+    Int(i64),
+    Text(String),
+    List(Vec<TraceValue>),
+}
+
+impl Display for TraceValue {
+    /// Render value as JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Text(text) => {
+                write!(f, "\"")?;
+                for c in text.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Self::List(values) => {
+                write!(f, "[")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl TraceValue {
+    /// Render value as plain text.
+    fn plain(&self) -> String {
+        match self {
+            Self::Int(i) => i.to_string(),
+            Self::Text(text) => text.clone(),
+            Self::List(values) => format!(
+                "[{}]",
+                values
+                    .iter()
+                    .map(Self::plain)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl From<i64> for TraceValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<isize> for TraceValue {
+    fn from(value: isize) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<usize> for TraceValue {
+    fn from(value: usize) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<&str> for TraceValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for TraceValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl<T: Into<TraceValue>> From<Vec<T>> for TraceValue {
+    fn from(value: Vec<T>) -> Self {
+        Self::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Trace record formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line.
+    Json,
+    /// One `event step N: key=value, ...` line, for reading along.
+    Text,
+}
+
+/// Writer of intermediate states.
+pub struct Tracer {
+    /// Trace destination.
+    out: Box<dyn Write>,
+    /// Trace one step every `every`.
+    every: usize,
+    format: TraceFormat,
+}
+
+impl Tracer {
+    /// Command line options configuring the tracer.
+    pub const OPTIONS: [&'static str; 2] = ["--trace", "--trace-every"];
+    /// Command line flag writing the trace to the standard output as text, for the binaries
+    /// supporting it.
+    pub const TEXT_FLAG: &'static str = "--trace-text";
+
+    /// Construct a new instance writing to `out` one step every `every`, as JSON.
+    pub fn new(out: Box<dyn Write>, every: usize) -> Result<Self> {
+        Self::with_format(out, every, TraceFormat::Json)
+    }
+
+    /// Construct a new instance writing to `out` one step every `every`, in the given format.
+    pub fn with_format(out: Box<dyn Write>, every: usize, format: TraceFormat) -> Result<Self> {
+        if every == 0 {
+            anyhow::bail!("trace interval must be greater than 0");
+        }
+
+        Ok(Self { out, every, format })
+    }
+
+    /// Construct a new instance writing to the file at `path`.
+    pub fn create<P: AsRef<Path>>(path: P, every: usize) -> Result<Self> {
+        Self::new(Box::new(BufWriter::new(File::create(path)?)), every)
+    }
+
+    /// Construct a new instance from the `--trace FILE` (or `--trace-text`) and
+    /// `--trace-every K` options, or `None` if tracing wasn't requested.
+    pub fn from_args(args: &Args) -> Result<Option<Self>> {
+        let every = args.parse_value("--trace-every")?.unwrap_or(1);
+        match (args.value("--trace"), args.flag(Self::TEXT_FLAG)) {
+            (Some(_), true) => {
+                anyhow::bail!("`--trace` and `{}` can't be used together", Self::TEXT_FLAG)
+            }
+            (Some(path), false) => Self::create(path, every).map(Some),
+            (None, true) => {
+                Self::with_format(Box::new(io::stdout()), every, TraceFormat::Text).map(Some)
+            }
+            (None, false) => Ok(None),
+        }
+    }
+
+    /// Record the state of `step` for `event` (skipped unless `step` is a multiple of the
+    /// trace interval).
+    pub fn record(
+        &mut self,
+        event: &str,
+        step: usize,
+        fields: &[(&str, TraceValue)],
+    ) -> Result<()> {
+        if !step.is_multiple_of(self.every) {
+            return Ok(());
+        }
+
+        if self.format == TraceFormat::Text {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{key}={}", value.plain()))
+                .collect();
+            writeln!(self.out, "{event} step {step}: {}", fields.join(", "))?;
+            return Ok(());
+        }
+
+        write!(
+            self.out,
+            "{{\"event\":{},\"step\":{step}",
+            TraceValue::from(event)
+        )?;
+        for (key, value) in fields {
+            write!(self.out, ",{}:{value}", TraceValue::from(*key))?;
+        }
+        writeln!(self.out, "}}")?;
+
+        Ok(())
+    }
+
+    /// Flush the records still buffered, reporting any error writing them.
+    pub fn finish(mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        // Errors can't be reported while dropping, and the records still buffered are lost:
+        // `Tracer::finish` reports them.
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Writer sharing its buffer, to inspect what the tracer wrote.
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn records() {
+        let buffer = Shared::default();
+        let mut tracer = Tracer::new(Box::new(buffer.clone()), 2).unwrap();
+
+        for step in 0..4 {
+            tracer
+                .record(
+                    "rope",
+                    step,
+                    &[
+                        ("x", TraceValue::from(-(step as i64))),
+                        ("stacks", TraceValue::from(vec!["A\"B", "C\\"])),
+                    ],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(
+            "{\"event\":\"rope\",\"step\":0,\"x\":0,\"stacks\":[\"A\\\"B\",\"C\\\\\"]}
+{\"event\":\"rope\",\"step\":2,\"x\":-2,\"stacks\":[\"A\\\"B\",\"C\\\\\"]}
+",
+            String::from_utf8(buffer.0.borrow().clone()).unwrap()
+        );
+
+        assert!(Tracer::new(Box::new(buffer), 0).is_err());
+    }
+
+    #[test]
+    fn from_args() {
+        let args = |args: &[&str]| {
+            Args::parse(
+                args.iter().map(|arg| arg.to_string()),
+                &Tracer::OPTIONS,
+                &[Tracer::TEXT_FLAG],
+            )
+            .unwrap()
+        };
+        let path = std::env::temp_dir().join(format!("trace-args-{}.jsonl", std::process::id()));
+
+        // The file is given as the option value, separated by a space.
+        let mut tracer = Tracer::from_args(&args(&["--trace", path.to_str().unwrap()]))
+            .unwrap()
+            .unwrap();
+        tracer.record("turn", 1, &[]).unwrap();
+        tracer.finish().unwrap();
+        assert_eq!(
+            "{\"event\":\"turn\",\"step\":1}\n",
+            std::fs::read_to_string(&path).unwrap()
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(Tracer::from_args(&args(&[])).unwrap().is_none());
+        assert!(Tracer::from_args(&args(&["--trace-text"]))
+            .unwrap()
+            .is_some());
+        assert!(Tracer::from_args(&args(&["--trace-text", "--trace", "out.json"])).is_err());
+    }
+
+    #[test]
+    fn text_records() {
+        let buffer = Shared::default();
+        let mut tracer =
+            Tracer::with_format(Box::new(buffer.clone()), 1, TraceFormat::Text).unwrap();
+        tracer
+            .record(
+                "part1",
+                1,
+                &[
+                    ("player", TraceValue::from("Paper")),
+                    ("stacks", TraceValue::from(vec!["A\"B", "C"])),
+                    ("total", TraceValue::from(8_usize)),
+                ],
+            )
+            .unwrap();
+        tracer.record("part1", 2, &[]).unwrap();
+
+        assert_eq!(
+            "part1 step 1: player=Paper, stacks=[A\"B, C], total=8\npart1 step 2: \n",
+            String::from_utf8(buffer.0.borrow().clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn finish() {
+        /// Writer failing to write anything.
+        struct Full;
+
+        impl Write for Full {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::StorageFull.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // Records are buffered, the error only shows flushing them.
+        let mut tracer = Tracer::new(Box::new(BufWriter::new(Full)), 1).unwrap();
+        tracer.record("rope", 1, &[]).unwrap();
+        assert!(tracer.finish().is_err());
+
+        let buffer = Shared::default();
+        let mut tracer = Tracer::new(Box::new(BufWriter::new(buffer.clone())), 1).unwrap();
+        tracer.record("rope", 1, &[]).unwrap();
+        assert!(buffer.0.borrow().is_empty());
+        tracer.finish().unwrap();
+        assert_eq!(
+            "{\"event\":\"rope\",\"step\":1}\n",
+            String::from_utf8(buffer.0.borrow().clone()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Disjoint set forest.
+
+/// Disjoint sets of the elements `0..len`, with path compression and union by rank.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    /// Size of each set, valid for its representative only.
+    size: Vec<usize>,
+    /// Number of disjoint sets.
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Construct a new instance where each of the `len` elements is in its own set.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+            size: vec![1; len],
+            sets: len,
+        }
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Representative of the set containing `x`.
+    ///
+    /// # Panic
+    /// Panics if `x` is out of bounds.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Path compression: point every element on the path straight to the root.
+        let mut x = x;
+        while self.parent[x] != root {
+            let next = self.parent[x];
+            self.parent[x] = root;
+            x = next;
+        }
+
+        root
+    }
+
+    /// Merge the sets containing `x` and `y`, returning whether they were disjoint.
+    ///
+    /// # Panic
+    /// Panics if `x` or `y` is out of bounds.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut x, mut y) = (self.find(x), self.find(y));
+        if x == y {
+            return false;
+        }
+
+        // Union by rank: attach the shallower tree under the deeper one.
+        if self.rank[x] < self.rank[y] {
+            (x, y) = (y, x);
+        }
+        if self.rank[x] == self.rank[y] {
+            self.rank[x] += 1;
+        }
+        self.parent[y] = x;
+        self.size[x] += self.size[y];
+        self.sets -= 1;
+
+        true
+    }
+
+    /// Whether `x` and `y` are in the same set.
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    /// Size of the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Number of disjoint sets.
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    /// Elements of each set, sorted by smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        // Index of the group of each representative.
+        let mut group: Vec<Option<usize>> = vec![None; self.len()];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            match group[root] {
+                Some(idx) => groups[idx].push(x),
+                None => {
+                    group[root] = Some(groups.len());
+                    groups.push(vec![x]);
+                }
+            }
+        }
+
+        groups
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn disjoint_sets() {
+        let mut sets = UnionFind::new(8);
+        assert_eq!(8, sets.sets());
+        assert!(!sets.connected(0, 1));
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.union(5, 6));
+
+        assert_eq!(4, sets.sets());
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(3, 4));
+        assert_eq!(4, sets.set_size(2));
+        assert_eq!(1, sets.set_size(7));
+        assert_eq!(
+            vec![vec![0, 1, 2, 3], vec![4], vec![5, 6], vec![7]],
+            sets.groups()
+        );
+
+        assert!(UnionFind::new(0).is_empty());
+    }
+
+    #[test]
+    fn long_chain() {
+        // Merging a chain of singletons one by one keeps finds cheap.
+        let mut sets = UnionFind::new(100_000);
+        for x in 1..sets.len() {
+            assert!(sets.union(x - 1, x));
+        }
+        assert_eq!(1, sets.sets());
+        assert_eq!(100_000, sets.set_size(0));
+        assert!(sets.connected(0, 99_999));
+        assert!(sets.rank.iter().all(|rank| *rank <= 17));
+    }
+}